@@ -1,7 +1,11 @@
-use domo::public::account::Account;
-use domo::public::Client;
+use domo::public::account::{
+    connector_account_source, AccessLevel, Account, AccountManifest, AccountType, ShareTarget,
+};
+use domo::public::{Client, GroupId, UserId};
 
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 
 use structopt::StructOpt;
 
@@ -18,26 +22,101 @@ pub enum AccountCommand {
         #[structopt(short = "o", long = "offset")]
         offset: Option<u32>,
     },
+    /// Get a list of every Account for which the user has permissions.
+    #[structopt(name = "list-all")]
+    ListAll,
+    /// Finds accounts by name, e.g. find "Snowflake*" --type snowflake
+    #[structopt(name = "find")]
+    Find {
+        /// A name pattern where * matches anything and ? any one character, ignoring case
+        name_pattern: String,
+        /// Only accounts of this type
+        #[structopt(long = "type")]
+        account_type: Option<String>,
+    },
     /// When creating an Account, you must specify the Account Type properties.
     /// The Account Type properties are different, depending on the type of Account you are trying to create.
     /// To retrieve which Account Type properties to specify, use the GET /v1/accounts/account-types/{ACCOUNT_TYPE_ID} endpoint.
+    ///
+    /// Opens the editor with the type's properties filled in, unless --name is given,
+    /// in which case the account is created straight from --property flags.
     #[structopt(name = "create")]
     Create {
         /// The account type to use when creating this account
-        account_type: String,
+        #[structopt(required_unless = "type")]
+        account_type: Option<String>,
+        /// The account type, for scripts that prefer a flag
+        #[structopt(long = "type", conflicts_with = "account-type")]
+        r#type: Option<String>,
+        /// The account name. Skips the editor.
+        #[structopt(short = "n", long = "name")]
+        name: Option<String>,
+        /// An account type property, e.g. --property username=svc_domo. May be repeated.
+        #[structopt(
+            short = "p",
+            long = "property",
+            number_of_values = 1,
+            parse(try_from_str = util::parse_key_value)
+        )]
+        properties: Vec<(String, String)>,
+    },
+    /// Creates and updates accounts to match a yaml manifest, then lists any that need re-authorizing.
+    ///
+    /// The manifest has a list of accounts, each with a name, type, and properties.
+    /// A property is either a value or, for credentials, { env: VARIABLE } to read it from the environment.
+    /// Accounts are matched by name.
+    #[structopt(name = "apply")]
+    Apply {
+        #[structopt(short = "f", long = "file", parse(from_os_str))]
+        file: PathBuf,
+        /// Print the plan without changing anything
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+        /// Also patch properties the api does not return, such as credentials
+        #[structopt(long = "force")]
+        force: bool,
     },
     /// Retrieves an account by id
     #[structopt(name = "retrieve")]
     Retrieve { id: String },
+    /// Lists the streams and datasets whose metadata refers to an account, where the api includes it.
+    /// Check this before rotating or deleting an account, but an empty list does not prove it is unused.
+    #[structopt(name = "usage")]
+    Usage { id: String },
     /// Updates the specified Account’s metadata as well as the Account’s Type properties.
     #[structopt(name = "update")]
     Update { id: String },
     /// Deletes an Account from your Domo instance.
     #[structopt(name = "delete")]
     Delete { id: String },
-    /// Share an Account with a User.
+    /// Share an Account with a User, or with --group a Group. --access-level needs the v2 shares api.
     #[structopt(name = "share")]
-    Share { account_id: String, user_id: u64 },
+    Share {
+        account_id: String,
+        #[structopt(required_unless = "group")]
+        user_id: Option<UserId>,
+        /// Share with this Group instead of a User
+        #[structopt(short = "g", long = "group", conflicts_with = "user-id")]
+        group: Option<GroupId>,
+        /// CAN_VIEW, CAN_EDIT, CAN_SHARE, or OWNER
+        #[structopt(short = "a", long = "access-level")]
+        access_level: Option<AccessLevel>,
+    },
+    /// Makes a user an owner of an account, e.g. when its current owner leaves. Prints the previous owners.
+    #[structopt(name = "chown")]
+    Chown {
+        account_id: String,
+        owner_id: UserId,
+        /// Also unshare the account from the previous owners
+        #[structopt(long = "remove-previous")]
+        remove_previous: bool,
+    },
+    /// Lists the Users an Account is shared with.
+    #[structopt(name = "list-shares")]
+    ListShares { account_id: String },
+    /// Stops sharing an Account with a User.
+    #[structopt(name = "unshare")]
+    Unshare { account_id: String, user_id: UserId },
     /// Get a list of all Account Types for which the user has permissions.
     #[structopt(name = "list-types")]
     ListTypes {
@@ -49,6 +128,14 @@ pub enum AccountCommand {
     /// Retrieves an account type by id
     #[structopt(name = "retrieve-type")]
     RetrieveType { id: String },
+    /// Prints a connector_account! declaration for an account type, generated from its template
+    #[structopt(name = "generate-type")]
+    GenerateType {
+        id: String,
+        /// The name of the struct to declare
+        #[structopt(long = "struct", default_value = "ConnectorProperties")]
+        struct_name: String,
+    },
 }
 
 pub async fn execute(dc: Client, editor: &str, template: Option<String>, command: AccountCommand) {
@@ -57,7 +144,45 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             let r = dc.get_accounts(limit, offset).await.unwrap();
             util::vec_obj_template_output(r, template);
         }
-        AccountCommand::Create { account_type } => {
+        AccountCommand::ListAll => {
+            let r = dc.get_accounts_all().await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        AccountCommand::Find {
+            name_pattern,
+            account_type,
+        } => {
+            let r = dc
+                .find_accounts(&name_pattern, account_type.as_deref())
+                .await
+                .unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        AccountCommand::Create {
+            account_type,
+            r#type,
+            name: Some(name),
+            properties,
+        } => {
+            let props: HashMap<String, String> = properties.into_iter().collect();
+            let r = Account {
+                name: Some(name),
+                account_type: Some(AccountType {
+                    id: account_type.or(r#type),
+                    properties: Some(props),
+                    ..Default::default()
+                }),
+                ..Account::new()
+            };
+            let r = dc.post_account(r).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        AccountCommand::Create {
+            account_type,
+            r#type,
+            ..
+        } => {
+            let account_type = account_type.or(r#type).unwrap();
             //Go get the account type and then populate the template accordingly
             let mut r = Account::template();
             let mut at = dc.get_account_type(&account_type).await.unwrap();
@@ -79,24 +204,89 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             let r = dc.post_account(r).await.unwrap();
             util::obj_template_output(r, template);
         }
+        AccountCommand::Apply {
+            file,
+            dry_run,
+            force,
+        } => {
+            let manifest: AccountManifest =
+                serde_yaml::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let plan = dc.plan_accounts(&manifest, force).await.unwrap();
+            println!("{}", plan);
+            if dry_run {
+                return;
+            }
+            let invalid = dc.apply_accounts(plan).await.unwrap();
+            for a in &invalid {
+                println!(
+                    "! {} ({}) needs to be re-authorized",
+                    a.name.as_deref().unwrap_or(""),
+                    a.id.as_deref().unwrap_or("")
+                );
+            }
+        }
         AccountCommand::Retrieve { id } => {
             let r = dc.get_account(&id).await.unwrap();
             util::obj_template_output(r, template);
         }
+        AccountCommand::Usage { id } => {
+            let r = dc.get_account_usage(&id).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
         AccountCommand::Update { id } => {
             let r = dc.get_account(&id).await.unwrap();
             let r = util::edit_obj(editor, r, "").unwrap();
-            dc.patch_account(&id, r).await.unwrap();
+            let r = dc.patch_account(&id, r).await.unwrap();
+            util::obj_template_output(r, template);
         }
         AccountCommand::Delete { id } => {
             dc.delete_account(&id).await.unwrap();
         }
         AccountCommand::Share {
             account_id,
-            user_id,
+            user_id: Some(user_id),
+            group: None,
+            access_level: None,
         } => {
             dc.post_account_share(&account_id, user_id).await.unwrap();
         }
+        AccountCommand::Share {
+            account_id,
+            user_id,
+            group,
+            access_level,
+        } => {
+            let target = match (user_id, group) {
+                (Some(id), _) => ShareTarget::User(id),
+                (None, Some(id)) => ShareTarget::Group(id),
+                (None, None) => unreachable!("structopt requires a user or group"),
+            };
+            let access_level = access_level.unwrap_or(AccessLevel::CanView);
+            dc.post_account_share_v2(&account_id, target, access_level)
+                .await
+                .unwrap();
+        }
+        AccountCommand::Chown {
+            account_id,
+            owner_id,
+            remove_previous,
+        } => {
+            let r = dc
+                .set_account_owner(&account_id, owner_id, remove_previous)
+                .await
+                .unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        AccountCommand::ListShares { account_id } => {
+            let r = dc.get_account_shares(&account_id).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        AccountCommand::Unshare {
+            account_id,
+            user_id,
+        } => {
+            dc.delete_account_share(&account_id, user_id).await.unwrap();
+        }
         AccountCommand::ListTypes { limit, offset } => {
             let r = dc.get_account_types(limit, offset).await.unwrap();
             util::vec_obj_template_output(r, template);
@@ -105,5 +295,12 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             let r = dc.get_account_type(&id).await.unwrap();
             util::obj_template_output(r, template);
         }
+        AccountCommand::GenerateType { id, struct_name } => {
+            let r = dc.get_account_type(&id).await.unwrap();
+            match connector_account_source(&r, &struct_name) {
+                Some(source) => print!("{}", source),
+                None => util::fail(format!("account type {} has no template", id)),
+            }
+        }
     }
 }
@@ -1,5 +1,11 @@
-use domo::public::Client;
+use domo::public::activity::{ActivityCheckpoint, EntryFilter, GroupBy, IpRange, ObjectType};
+use domo::public::{Client, DataSetId, UserId};
 
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 use super::util;
@@ -18,8 +24,159 @@ pub enum ActivityCommand {
         #[structopt(short = "o", long = "offset")]
         offset: Option<u32>,
         #[structopt(short = "u", long = "user")]
-        user_id: Option<u64>,
+        user_id: Option<UserId>,
     },
+    /// Retrieves every activity log entry between two times, paging past the 1000 entry limit.
+    /// Times are milliseconds, RFC 3339, a date, or relative such as -7d.
+    #[structopt(name = "list-all")]
+    ListAll {
+        #[structopt(
+            short = "s",
+            long = "since",
+            default_value = "-1d",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        since: DateTime<Utc>,
+        /// Defaults to now
+        #[structopt(
+            long = "until",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        until: Option<DateTime<Utc>>,
+        #[structopt(flatten)]
+        filter: FilterArgs,
+    },
+    /// Appends the activity log entries since the last run to a file as newline delimited json.
+    /// The checkpoint file records where the last run stopped and is created on the first run.
+    #[structopt(name = "export")]
+    Export {
+        #[structopt(long = "since-checkpoint", parse(from_os_str))]
+        checkpoint: PathBuf,
+        /// Defaults to stdout
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Where a new checkpoint starts
+        #[structopt(
+            short = "s",
+            long = "start",
+            default_value = "-7d",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        start: DateTime<Utc>,
+        /// How many days of entries to fetch at once, for backfilling a long range
+        #[structopt(short = "p", long = "parallel", default_value = "1")]
+        parallel: usize,
+        /// The most requests per second across all the parallel fetches
+        #[structopt(long = "rate-limit")]
+        rate_limit: Option<f64>,
+        #[structopt(flatten)]
+        filter: FilterArgs,
+    },
+    /// Counts activity log entries by user, object-type, or event, e.g. report --group-by user --since -7d
+    #[structopt(name = "report")]
+    Report {
+        #[structopt(short = "g", long = "group-by", default_value = "user")]
+        group_by: GroupBy,
+        #[structopt(
+            short = "s",
+            long = "since",
+            default_value = "-7d",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        since: DateTime<Utc>,
+        /// Defaults to now
+        #[structopt(
+            long = "until",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        until: Option<DateTime<Utc>>,
+        #[structopt(flatten)]
+        filter: FilterArgs,
+    },
+    /// Polls the activity log and posts new entries to a url as json arrays, retrying failed posts.
+    /// Runs until stopped.
+    #[structopt(name = "forward")]
+    Forward {
+        #[structopt(long = "url")]
+        url: String,
+        /// Seconds between polls
+        #[structopt(short = "i", long = "interval", default_value = "60")]
+        interval: u64,
+        /// Records what has been sent, so a restart carries on where it stopped
+        #[structopt(short = "c", long = "checkpoint", parse(from_os_str))]
+        checkpoint: Option<PathBuf>,
+        /// Where to start without a checkpoint
+        #[structopt(
+            short = "s",
+            long = "start",
+            default_value = "now",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        start: DateTime<Utc>,
+        /// The most entries to send in one post
+        #[structopt(short = "b", long = "batch-size", default_value = "500")]
+        batch_size: usize,
+        #[structopt(flatten)]
+        filter: FilterArgs,
+    },
+    /// Creates a dataset with an APPEND stream for to-dataset to load activity log entries into
+    #[structopt(name = "create-dataset")]
+    CreateDataset { name: String },
+    /// Appends activity log entries to a dataset made by create-dataset, e.g. to-dataset <id> --since -1d
+    #[structopt(name = "to-dataset")]
+    ToDataset {
+        dataset_id: DataSetId,
+        #[structopt(
+            short = "s",
+            long = "since",
+            default_value = "-1d",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        since: DateTime<Utc>,
+        /// Defaults to now
+        #[structopt(
+            long = "until",
+            allow_hyphen_values = true,
+            parse(try_from_str = util::parse_time)
+        )]
+        until: Option<DateTime<Utc>>,
+        #[structopt(flatten)]
+        filter: FilterArgs,
+    },
+}
+
+/// Options narrowing which entries are read
+#[derive(StructOpt, Debug)]
+pub struct FilterArgs {
+    #[structopt(short = "u", long = "user")]
+    user_id: Option<UserId>,
+    /// Only entries about this kind of object, e.g. DATASET, PAGE or USER. May be repeated.
+    #[structopt(short = "t", long = "object-type", number_of_values = 1)]
+    object_types: Vec<ObjectType>,
+    /// Only entries whose event text contains this, ignoring case
+    #[structopt(long = "event")]
+    event_text: Option<String>,
+    /// Only entries from this address or CIDR block, e.g. 10.0.0.0/8. May be repeated.
+    #[structopt(long = "ip", number_of_values = 1)]
+    ip_ranges: Vec<IpRange>,
+}
+
+impl From<FilterArgs> for EntryFilter {
+    fn from(f: FilterArgs) -> Self {
+        EntryFilter {
+            user_id: f.user_id,
+            object_types: f.object_types,
+            event_text: f.event_text,
+            ip_ranges: f.ip_ranges,
+        }
+    }
 }
 
 pub async fn execute(dc: Client, template: Option<String>, command: ActivityCommand) {
@@ -37,5 +194,119 @@ pub async fn execute(dc: Client, template: Option<String>, command: ActivityComm
                 .unwrap();
             util::vec_obj_template_output(r, template);
         }
+        ActivityCommand::ListAll {
+            since,
+            until,
+            filter,
+        } => {
+            let until = until.unwrap_or_else(Utc::now);
+            let r: Vec<_> = dc
+                .get_entries_all(since..until, filter.into())
+                .try_collect()
+                .await
+                .unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        ActivityCommand::Export {
+            checkpoint: path,
+            output,
+            start,
+            parallel,
+            rate_limit,
+            filter,
+        } => {
+            let dc = match rate_limit {
+                Some(r) => dc.rate_limit(r),
+                None => dc,
+            };
+            let mut checkpoint = ActivityCheckpoint::load(&path).unwrap();
+            let mut out: Box<dyn Write> = match output {
+                Some(f) => Box::new(BufWriter::new(
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(f)
+                        .unwrap(),
+                )),
+                None => Box::new(io::stdout()),
+            };
+            let n = dc
+                .export_entries(&mut checkpoint, start, filter.into(), parallel, &mut out)
+                .await
+                .unwrap();
+            out.flush().unwrap();
+            checkpoint.save(&path).unwrap();
+            eprintln!("Exported {} entries", n);
+        }
+        ActivityCommand::Report {
+            group_by,
+            since,
+            until,
+            filter,
+        } => {
+            let until = until.unwrap_or_else(Utc::now);
+            let r = dc
+                .get_entry_counts(since..until, filter.into(), group_by)
+                .await
+                .unwrap();
+            if template.is_some() {
+                util::vec_obj_template_output(r, template);
+            } else {
+                let columns = vec![String::from("key"), String::from("count")];
+                let rows: Vec<Vec<String>> = r
+                    .into_iter()
+                    .map(|c| vec![c.key, c.count.to_string()])
+                    .collect();
+                util::print_table(&columns, &rows);
+            }
+        }
+        ActivityCommand::Forward {
+            url,
+            interval,
+            checkpoint: path,
+            start,
+            batch_size,
+            filter,
+        } => {
+            let mut checkpoint = match &path {
+                Some(p) => ActivityCheckpoint::load(p).unwrap(),
+                None => ActivityCheckpoint::default(),
+            };
+            let filter: EntryFilter = filter.into();
+            loop {
+                match dc
+                    .forward_entries(&url, &mut checkpoint, start, filter.clone(), batch_size)
+                    .await
+                {
+                    Ok(n) => {
+                        if let Some(p) = &path {
+                            checkpoint.save(p).unwrap();
+                        }
+                        eprintln!("Forwarded {} entries", n);
+                    }
+                    //Keep polling, the checkpoint has not moved so the entries are sent next time
+                    Err(e) => eprintln!("Forwarding failed: {}", e),
+                }
+                async_std::task::sleep(std::time::Duration::from_secs(interval)).await;
+            }
+        }
+        ActivityCommand::CreateDataset { name } => {
+            let r = dc.create_entry_dataset(&name).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        ActivityCommand::ToDataset {
+            dataset_id,
+            since,
+            until,
+            filter,
+        } => {
+            let until = until.unwrap_or_else(Utc::now);
+            let r = dc
+                .append_entries_to_dataset(&dataset_id, since..until, filter.into())
+                .await
+                .unwrap();
+            eprintln!("Appended {} entries", r.rows);
+            util::obj_template_output(r, template);
+        }
     }
 }
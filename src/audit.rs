@@ -0,0 +1,31 @@
+use domo::public::Client;
+
+use std::time::Duration;
+use structopt::StructOpt;
+
+use super::util;
+
+/// Reports that read across several apis
+#[derive(StructOpt, Debug)]
+pub enum AuditCommand {
+    /// Lists pages with no cards, datasets with no stream and stale data, and groups with no members.
+    /// Nothing is changed.
+    #[structopt(name = "orphans")]
+    Orphans {
+        /// A dataset without a stream counts as stale when its data is older than this many days
+        #[structopt(long = "stale-days", default_value = "90")]
+        stale_days: u64,
+    },
+}
+
+pub async fn execute(dc: Client, template: Option<String>, command: AuditCommand) {
+    match command {
+        AuditCommand::Orphans { stale_days } => {
+            let r = dc
+                .find_orphans(Duration::from_secs(stale_days * 24 * 60 * 60))
+                .await
+                .unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+    }
+}
@@ -1,7 +1,13 @@
+use domo::public::buzz::replay_fixtures;
+use domo::public::buzz::BuzzExport;
+use domo::public::buzz::EventRouter;
 use domo::public::buzz::Integration;
 use domo::public::buzz::Subscription;
 use domo::public::Client;
 
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
 use structopt::StructOpt;
 
 use super::util;
@@ -21,6 +27,10 @@ pub enum BuzzCommand {
     #[structopt(name = "retrieve")]
     Retrieve { id: String },
 
+    /// Updates an integration, keeping its subscriptions
+    #[structopt(name = "update")]
+    Update { id: String },
+
     /// Permanently deletes a Buzz integration.
     #[structopt(name = "delete")]
     Delete { id: String },
@@ -36,6 +46,64 @@ pub enum BuzzCommand {
     /// Delete a subscription
     #[structopt(name = "delete-subscription")]
     DeleteSubscription { id: String, subscription_id: String },
+
+    /// Writes every integration and its subscriptions as yaml, with header values replaced by env references
+    #[structopt(name = "export")]
+    Export {
+        /// Where to write the yaml. Prints it when not given.
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Creates the integrations in an exported file, skipping names that already exist
+    #[structopt(name = "import")]
+    Import {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+
+    /// Receives events posted to subscription urls and prints each one. Needs the tide feature.
+    #[structopt(name = "serve")]
+    Serve {
+        /// The address to listen on
+        #[structopt(short = "a", long = "addr", default_value = "0.0.0.0:8080")]
+        addr: String,
+        /// Only accept events with this header, e.g. --header x-my-api-key=ABC123. May be repeated.
+        #[structopt(
+            long = "header",
+            number_of_values = 1,
+            parse(try_from_str = util::parse_key_value)
+        )]
+        headers: Vec<(String, String)>,
+        /// Save every event to this directory as a json fixture, for buzz replay
+        #[structopt(long = "capture", parse(from_os_str))]
+        capture: Option<PathBuf>,
+    },
+
+    /// Prints events saved by buzz serve --capture, from a fixture file or a directory of them
+    #[structopt(name = "replay")]
+    Replay {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+
+    /// Runs a slash command bot that echoes each command back to its channel. Needs the tide feature.
+    #[structopt(name = "bot")]
+    Bot {
+        /// The address to listen on
+        #[structopt(short = "a", long = "addr", default_value = "0.0.0.0:8080")]
+        addr: String,
+        /// Only accept events with this header, e.g. --header x-my-api-key=ABC123. May be repeated.
+        #[structopt(
+            long = "header",
+            number_of_values = 1,
+            parse(try_from_str = util::parse_key_value)
+        )]
+        headers: Vec<(String, String)>,
+        /// The commands to answer, e.g. --command echo. May be repeated.
+        #[structopt(short = "c", long = "command", number_of_values = 1, required = true)]
+        commands: Vec<String>,
+    },
 }
 
 pub async fn execute(dc: Client, editor: &str, template: Option<String>, command: BuzzCommand) {
@@ -54,6 +122,12 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             let r = dc.get_integration(&id).await.unwrap();
             util::obj_template_output(r, template);
         }
+        BuzzCommand::Update { id } => {
+            let r = dc.get_integration(&id).await.unwrap();
+            let r = util::edit_obj(editor, r, "").unwrap();
+            let r = dc.put_integration(&id, r).await.unwrap();
+            util::obj_template_output(r, template);
+        }
         BuzzCommand::Delete { id } => {
             dc.delete_integration(&id).await.unwrap();
         }
@@ -75,5 +149,115 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
                 .await
                 .unwrap();
         }
+        BuzzCommand::Export { output } => {
+            let r = dc.export_integrations().await.unwrap();
+            let yaml = serde_yaml::to_string(&r).unwrap();
+            match output {
+                Some(path) => fs::write(path, yaml).unwrap(),
+                None => print!("{}", yaml),
+            }
+        }
+        BuzzCommand::Import { file } => {
+            let export: BuzzExport =
+                serde_yaml::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let r = dc.import_integrations(export).await.unwrap();
+            for i in &r.created {
+                println!(
+                    "+ created {} ({})",
+                    i.name.as_deref().unwrap_or(""),
+                    i.id.as_deref().unwrap_or("")
+                );
+            }
+            for name in &r.skipped {
+                println!("= skipped {}, it already exists", name);
+            }
+        }
+        BuzzCommand::Serve {
+            addr,
+            headers,
+            capture,
+        } => {
+            serve(&addr, headers, capture, template).await;
+        }
+        BuzzCommand::Replay { path } => {
+            let n = replay_fixtures(Arc::new(print_events(template)), &path)
+                .await
+                .unwrap();
+            eprintln!("Replayed {} events", n);
+        }
+        BuzzCommand::Bot {
+            addr,
+            headers,
+            commands,
+        } => {
+            bot(&addr, headers, commands).await;
+        }
+    }
+}
+
+/// A router that prints every event
+fn print_events(template: Option<String>) -> EventRouter {
+    EventRouter::new().on_any(move |event| {
+        let template = template.clone();
+        async move {
+            util::obj_template_output(event, template);
+            Ok(())
+        }
+    })
+}
+
+#[cfg(feature = "tide")]
+async fn serve(
+    addr: &str,
+    headers: Vec<(String, String)>,
+    capture: Option<PathBuf>,
+    template: Option<String>,
+) {
+    let mut server = domo::public::buzz::EventServer::new().router(print_events(template));
+    for (name, value) in &headers {
+        server = server.header(name, value);
+    }
+    if let Some(dir) = capture {
+        server = server.capture(dir);
     }
+    eprintln!("Listening on {}", addr);
+    server.listen(addr).await.unwrap();
+}
+
+#[cfg(not(feature = "tide"))]
+async fn serve(
+    _addr: &str,
+    _headers: Vec<(String, String)>,
+    _capture: Option<PathBuf>,
+    _template: Option<String>,
+) {
+    util::fail("buzz serve requires building with the tide feature")
+}
+
+#[cfg(feature = "tide")]
+async fn bot(addr: &str, headers: Vec<(String, String)>, commands: Vec<String>) {
+    use domo::webhook::buzz::Message;
+    let mut bot = domo::public::buzz::SlashCommandBot::new();
+    for (name, value) in &headers {
+        bot = bot.header(name, value);
+    }
+    for name in &commands {
+        bot = bot.command(name, |command| async move {
+            let who = command
+                .author
+                .as_ref()
+                .and_then(|a| a.display_name.clone())
+                .unwrap_or_else(|| String::from("someone"));
+            let text = format!("{} ran /{} {}", who, command.name, command.text);
+            eprintln!("{}", text);
+            Ok(Some(Message { title: None, text }))
+        });
+    }
+    eprintln!("Listening on {}", addr);
+    bot.listen(addr).await.unwrap();
+}
+
+#[cfg(not(feature = "tide"))]
+async fn bot(_addr: &str, _headers: Vec<(String, String)>, _commands: Vec<String>) {
+    util::fail("buzz bot requires building with the tide feature")
 }
@@ -0,0 +1,59 @@
+use domo::public::{CardId, Client, PageId, UserId};
+
+use structopt::StructOpt;
+
+use super::util;
+
+/// Wraps the card api
+#[derive(StructOpt, Debug)]
+pub enum CardCommand {
+    /// Get a list of cards.
+    #[structopt(name = "list")]
+    List {
+        #[structopt(short = "l", long = "limit")]
+        limit: Option<u32>,
+        #[structopt(short = "o", long = "offset")]
+        offset: Option<u32>,
+    },
+
+    /// Get a list of every card, optionally only those owned by a user.
+    #[structopt(name = "list-all")]
+    ListAll {
+        /// Only cards owned by this user
+        #[structopt(long = "owner")]
+        owner: Option<UserId>,
+    },
+
+    /// Retrieves the metadata of a card.
+    #[structopt(name = "retrieve")]
+    Retrieve { id: CardId },
+
+    /// Lists the cards on a page.
+    #[structopt(name = "page")]
+    Page { page_id: PageId },
+}
+
+pub async fn execute(dc: Client, template: Option<String>, command: CardCommand) {
+    match command {
+        CardCommand::List { limit, offset } => {
+            let r = dc.get_cards(limit, offset).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        CardCommand::ListAll { owner: None } => {
+            let r = dc.get_cards_all().await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        CardCommand::ListAll { owner: Some(id) } => {
+            let r = dc.get_user_cards(id).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        CardCommand::Retrieve { id } => {
+            let r = dc.get_card(id).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        CardCommand::Page { page_id } => {
+            let r = dc.get_page_cards(page_id).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use super::util;
-use domo::public::dataset::{DataSet, Policy};
-use domo::public::Client;
+use domo::public::dataset::{diff_schema, DataSet, Policy, Schema};
+use domo::public::{Client, DataSetId, ProgressReader};
 
+use async_std::io::{self, BufReader};
+use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 
 use structopt::StructOpt;
 
@@ -28,49 +33,133 @@ pub enum DataSetCommand {
 
     /// Retrieves the details of an existing DataSet.
     #[structopt(name = "retrieve")]
-    Retrieve { id: String },
+    Retrieve { id: DataSetId },
 
     /// Update a dataset
     #[structopt(name = "update")]
-    Update { id: String },
+    Update { id: DataSetId },
+
+    /// Update only the name and/or description of a dataset without touching the schema
+    #[structopt(name = "update-metadata")]
+    UpdateMetadata {
+        id: DataSetId,
+        #[structopt(short = "n", long = "name")]
+        name: Option<String>,
+        #[structopt(short = "d", long = "description")]
+        description: Option<String>,
+    },
+
+    /// Wait until a dataset reflects data newer than the given time.
+    #[structopt(name = "wait")]
+    Wait {
+        id: DataSetId,
+        /// An ISO-8601 timestamp the data must be newer than. Defaults to now.
+        #[structopt(short = "s", long = "since")]
+        since: Option<DateTime<Utc>>,
+        /// How many seconds to wait before giving up
+        #[structopt(long = "timeout", default_value = "600")]
+        timeout: u64,
+    },
+
+    /// Report the streams and PDP policies that reference a dataset. Helpful before deleting it,
+    /// but cards and pages built on the dataset can not be found through the public API, so check those too.
+    #[structopt(name = "inspect")]
+    Inspect { id: DataSetId },
 
     /// Permanently deletes a DataSet from your Domo instance. This can be done for all DataSets, not just those created through the API.
     #[structopt(name = "delete")]
-    Delete { id: String },
+    Delete { id: DataSetId },
 
     /// Import data into a DataSet in your Domo instance. This request will replace the data currently in the DataSet.
     #[structopt(name = "import")]
     Import {
-        /// A csv file that will replace all of the data in this dataset
+        /// A csv file that will replace all of the data in this dataset. Use - to read from stdin.
         #[structopt(parse(from_os_str))]
         file: PathBuf,
         /// The dataset to import the data into
-        id: String,
+        id: DataSetId,
+    },
+
+    /// Append the rows of a csv file to the data currently in a DataSet. Meant for small incremental loads.
+    #[structopt(name = "append")]
+    Append {
+        /// A csv file whose rows will be added to this dataset
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// The dataset to append the data to
+        id: DataSetId,
     },
 
     /// Export data from a DataSet in your Domo instance.
     #[structopt(name = "export")]
-    Export { id: String },
+    Export {
+        id: DataSetId,
+        /// Write the csv to this file instead of stdout. With --resumable, the parts are joined into it once the export completes.
+        #[structopt(long = "output", parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Export in chunks into this directory, resuming from its checkpoint if one exists
+        #[structopt(long = "resumable", parse(from_os_str))]
+        resumable: Option<PathBuf>,
+        /// The number of rows in each chunk of a resumable export
+        #[structopt(long = "chunk-rows", default_value = "100000")]
+        chunk_rows: u32,
+    },
 
     /// Returns data from the DataSet based on your SQL query.
     #[structopt(name = "query")]
-    Query { id: String, sql: String },
+    Query { id: DataSetId, sql: String },
+
+    /// Inspect and manage a dataset schema.
+    #[structopt(name = "schema")]
+    Schema {
+        #[structopt(subcommand)]
+        command: SchemaCommand,
+    },
 
     /// List the Personalized Data Permission (PDP) policies for a specified DataSet.
-    ListPolicies { id: String },
+    ListPolicies { id: DataSetId },
 
     /// Create a PDP policy for user and or group access to data within a DataSet.
     /// Users and groups must exist before creating PDP policy.
-    CreatePolicy { id: String },
+    CreatePolicy { id: DataSetId },
 
     /// Retrieve a policy from a DataSet within Domo. A DataSet is required for a PDP policy to exist.
-    RetrievePolicy { id: String, policy_id: u32 },
+    RetrievePolicy { id: DataSetId, policy_id: u32 },
 
     /// Update the specific PDP policy for a DataSet by providing values to parameters passed.
-    UpdatePolicy { id: String, policy_id: u32 },
+    UpdatePolicy { id: DataSetId, policy_id: u32 },
 
     /// Permanently deletes a PDP policy on a DataSet in your Domo instance.
-    DeletePolicy { id: String, policy_id: u32 },
+    DeletePolicy { id: DataSetId, policy_id: u32 },
+}
+
+/// Wraps dataset schema management
+#[derive(StructOpt, Debug)]
+pub enum SchemaCommand {
+    /// Show only the schema of a dataset
+    #[structopt(name = "show")]
+    Show { id: DataSetId },
+
+    /// Update only the schema of a dataset. Opens the current schema in an editor unless a file is given.
+    #[structopt(name = "update")]
+    Update {
+        id: DataSetId,
+        /// A yaml file containing the new schema
+        #[structopt(short = "f", long = "file", parse(from_os_str))]
+        file: Option<PathBuf>,
+        /// Apply the schema even if it removes columns, dropping their data
+        #[structopt(long = "allow-removals")]
+        allow_removals: bool,
+    },
+
+    /// Show the differences between a dataset's current schema and a yaml schema file
+    #[structopt(name = "diff")]
+    Diff {
+        id: DataSetId,
+        /// A yaml file containing the desired schema
+        #[structopt(short = "f", long = "file", parse(from_os_str))]
+        file: PathBuf,
+    },
 }
 
 pub async fn execute(dc: Client, editor: &str, template: Option<String>, command: DataSetCommand) {
@@ -80,21 +169,7 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             util::vec_obj_template_output(r, template);
         }
         DataSetCommand::ListAll {} => {
-            let mut offset = 0_u32;
-            let mut r: Vec<DataSet> = Vec::new();
-            loop {
-                let mut ret = dc.get_datasets(Some(50), Some(offset)).await.unwrap();
-                let mut b = false;
-                if ret.len() < 50 {
-                    b = true;
-                }
-                //Either way slurp all the elements into the aggregator
-                r.append(&mut ret);
-                offset += 50;
-                if b {
-                    break;
-                }
-            }
+            let r = dc.get_datasets_all().await.unwrap();
             util::vec_obj_template_output(r, template);
         }
         DataSetCommand::Create {} => {
@@ -113,20 +188,120 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             let r = dc.put_dataset(&id, r).await.unwrap();
             util::obj_template_output(r, template);
         }
+        DataSetCommand::UpdateMetadata {
+            id,
+            name,
+            description,
+        } => {
+            let r = dc
+                .update_dataset_metadata(&id, name.as_deref(), description.as_deref())
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
+        DataSetCommand::Wait { id, since, timeout } => {
+            let since = since.unwrap_or_else(Utc::now);
+            let r = dc
+                .wait_for_dataset_update(&id, since, Duration::from_secs(timeout))
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
+        DataSetCommand::Inspect { id } => {
+            let r = dc.get_dataset_usage(&id).await.unwrap();
+            util::obj_template_output(r, template);
+        }
         DataSetCommand::Delete { id } => {
             dc.delete_dataset(&id).await.unwrap();
         }
         DataSetCommand::Import { file, id } => {
-            dc.put_dataset_data(&id, file).await.unwrap();
+            let progress = util::progress_bar("Importing");
+            if file.as_os_str() == "-" {
+                let stdin = ProgressReader::new(BufReader::new(io::stdin()), None, progress);
+                dc.put_dataset_data_reader(&id, stdin).await.unwrap();
+            } else {
+                dc.put_dataset_data_with_progress(&id, file, progress)
+                    .await
+                    .unwrap();
+            }
+            util::progress_finish();
         }
-        DataSetCommand::Export { id } => {
-            let r = dc.get_dataset_data(&id).await.unwrap();
+        DataSetCommand::Append { file, id } => {
+            dc.append_dataset_data(&id, file).await.unwrap();
+        }
+        DataSetCommand::Export {
+            id,
+            output,
+            resumable: Some(dir),
+            chunk_rows,
+        } => {
+            let r = dc
+                .export_dataset_resumable(&id, &dir, chunk_rows)
+                .await
+                .unwrap();
+            if let Some(path) = output {
+                let mut out = fs::File::create(path).unwrap();
+                for part in &r.parts {
+                    let mut f = fs::File::open(dir.join(part)).unwrap();
+                    std::io::copy(&mut f, &mut out).unwrap();
+                }
+            }
+            util::obj_template_output(r, template);
+        }
+        DataSetCommand::Export {
+            id,
+            output: Some(path),
+            ..
+        } => {
+            let r = dc
+                .get_dataset_data_with_progress(&id, util::progress_bar("Exporting"))
+                .await
+                .unwrap();
+            util::progress_finish();
+            fs::write(path, r).unwrap();
+        }
+        DataSetCommand::Export { id, .. } => {
+            let r = dc
+                .get_dataset_data_with_progress(&id, util::progress_bar("Exporting"))
+                .await
+                .unwrap();
+            util::progress_finish();
             util::csv_template_output(r, template);
         }
         DataSetCommand::Query { id, sql } => {
             let r = dc.post_dataset_query(&id, &sql).await.unwrap();
             util::query_template_output(r, template);
         }
+        DataSetCommand::Schema { command } => match command {
+            SchemaCommand::Show { id } => {
+                let r = dc.get_dataset(&id).await.unwrap();
+                util::obj_template_output(r.schema.unwrap_or_default(), template);
+            }
+            SchemaCommand::Update {
+                id,
+                file,
+                allow_removals,
+            } => {
+                let desired: Schema = match file {
+                    Some(file) => serde_yaml::from_str(&fs::read_to_string(file).unwrap()).unwrap(),
+                    None => {
+                        let r = dc.get_dataset(&id).await.unwrap();
+                        util::edit_obj(editor, r.schema.unwrap_or_default(), "").unwrap()
+                    }
+                };
+                match dc.apply_schema(&id, desired, allow_removals).await {
+                    Ok(diff) => println!("{}", diff),
+                    Err(e) => util::fail(e),
+                }
+            }
+            SchemaCommand::Diff { id, file } => {
+                let desired: Schema =
+                    serde_yaml::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+                let current = dc.get_dataset(&id).await.unwrap();
+                let diff = diff_schema(&current.schema.unwrap_or_default(), &desired);
+                println!("{}", diff);
+            }
+        },
         DataSetCommand::ListPolicies { id } => {
             let r = dc.get_dataset_policies(&id).await.unwrap();
             util::vec_obj_template_output(r, template);
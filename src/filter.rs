@@ -0,0 +1,302 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A client side filter over listed objects, e.g. `department == 'Finance' && role == 'Privileged'`.
+///
+/// Comparisons are `field op literal` with ==, !=, <, <=, >, >= or `contains`. Fields use the json
+/// names and may be dotted to reach nested objects. Literals are quoted strings, numbers, true, false, or null.
+/// Comparisons combine with &&, || and !, and can be grouped with parentheses.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Compare { field: String, op: Op, value: Value },
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl Filter {
+    /// True when the object matches the filter. Missing fields compare as null.
+    pub fn matches(&self, v: &Value) -> bool {
+        match self {
+            Filter::Compare { field, op, value } => {
+                let actual = field
+                    .split('.')
+                    .try_fold(v, |v, f| v.get(f))
+                    .unwrap_or(&Value::Null);
+                compare(actual, *op, value)
+            }
+            Filter::Not(f) => !f.matches(v),
+            Filter::And(a, b) => a.matches(v) && b.matches(v),
+            Filter::Or(a, b) => a.matches(v) || b.matches(v),
+        }
+    }
+}
+
+/// Keeps only the items matching the filter, or every item if there is no filter
+pub fn apply<T: Serialize>(items: Vec<T>, filter: &Option<Filter>) -> Vec<T> {
+    match filter {
+        Some(f) => items
+            .into_iter()
+            .filter(|i| f.matches(&serde_json::to_value(i).unwrap_or(Value::Null)))
+            .collect(),
+        None => items,
+    }
+}
+
+fn compare(actual: &Value, op: Op, expected: &Value) -> bool {
+    if op == Op::Contains {
+        return match (actual, expected) {
+            (Value::String(a), Value::String(e)) => a.to_lowercase().contains(&e.to_lowercase()),
+            (Value::Array(a), e) => a.contains(e),
+            _ => false,
+        };
+    }
+    let ord = match (actual, expected) {
+        (Value::Number(a), Value::Number(e)) => a.as_f64().partial_cmp(&e.as_f64()),
+        (Value::String(a), Value::String(e)) => Some(a.cmp(e)),
+        //Ids and numbers are sometimes strings in the api, so let 'x' == 12 work
+        (Value::String(a), Value::Number(e)) => a
+            .parse::<f64>()
+            .ok()
+            .and_then(|a| a.partial_cmp(&e.as_f64()?)),
+        (Value::Number(a), Value::String(e)) => e
+            .parse::<f64>()
+            .ok()
+            .and_then(|e| a.as_f64()?.partial_cmp(&e)),
+        (a, e) if a == e => Some(Ordering::Equal),
+        _ => None,
+    };
+    match op {
+        Op::Eq => ord == Some(Ordering::Equal),
+        Op::Ne => ord != Some(Ordering::Equal),
+        Op::Lt => ord == Some(Ordering::Less),
+        Op::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+        Op::Gt => ord == Some(Ordering::Greater),
+        Op::Ge => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+        Op::Contains => unreachable!(),
+    }
+}
+
+#[derive(Debug)]
+pub struct FilterParseError(String);
+
+impl std::error::Error for FilterParseError {}
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid filter: {}", self.0)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let f = p.or()?;
+        match p.peek() {
+            None => Ok(f),
+            Some(t) => Err(FilterParseError(format!("unexpected {}", t))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Value),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Literal(v) => write!(f, "{}", v),
+            Token::Op(op) => write!(f, "{:?}", op),
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
+            Token::Not => write!(f, "!"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, FilterParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut lit = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some('\\') => lit.extend(chars.next()),
+                        Some(ch) => lit.push(ch),
+                        None => return Err(FilterParseError(String::from("unterminated string"))),
+                    }
+                }
+                tokens.push(Token::Literal(Value::String(lit)));
+            }
+            '=' | '!' | '<' | '>' | '&' | '|' => {
+                chars.next();
+                let next = chars.peek().copied();
+                let token = match (c, next) {
+                    ('=', Some('=')) => Token::Op(Op::Eq),
+                    ('!', Some('=')) => Token::Op(Op::Ne),
+                    ('<', Some('=')) => Token::Op(Op::Le),
+                    ('>', Some('=')) => Token::Op(Op::Ge),
+                    ('&', Some('&')) => Token::And,
+                    ('|', Some('|')) => Token::Or,
+                    ('<', _) => Token::Op(Op::Lt),
+                    ('>', _) => Token::Op(Op::Gt),
+                    ('!', _) => Token::Not,
+                    _ => return Err(FilterParseError(format!("unexpected {}", c))),
+                };
+                if !matches!(token, Token::Op(Op::Lt) | Token::Op(Op::Gt) | Token::Not) {
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_alphanumeric() || "._-+".contains(ch) {
+                        word.push(ch);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if word.is_empty() {
+                    return Err(FilterParseError(format!("unexpected {}", c)));
+                }
+                tokens.push(match word.as_str() {
+                    "contains" => Token::Op(Op::Contains),
+                    "true" | "false" | "null" => {
+                        Token::Literal(serde_json::from_str(&word).unwrap())
+                    }
+                    _ => {
+                        match word.parse::<f64>() {
+                            Ok(_) => Token::Literal(serde_json::from_str(&word).map_err(|_| {
+                                FilterParseError(format!("invalid number {}", word))
+                            })?),
+                            Err(_) => Token::Ident(word),
+                        }
+                    }
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, FilterParseError> {
+        let t = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| FilterParseError(String::from("unexpected end of filter")))?;
+        self.pos += 1;
+        Ok(t)
+    }
+
+    fn or(&mut self) -> Result<Filter, FilterParseError> {
+        let mut f = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            f = Filter::Or(Box::new(f), Box::new(self.and()?));
+        }
+        Ok(f)
+    }
+
+    fn and(&mut self) -> Result<Filter, FilterParseError> {
+        let mut f = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            f = Filter::And(Box::new(f), Box::new(self.unary()?));
+        }
+        Ok(f)
+    }
+
+    fn unary(&mut self) -> Result<Filter, FilterParseError> {
+        match self.next()? {
+            Token::Not => Ok(Filter::Not(Box::new(self.unary()?))),
+            Token::Open => {
+                let f = self.or()?;
+                match self.next()? {
+                    Token::Close => Ok(f),
+                    t => Err(FilterParseError(format!("expected ) but found {}", t))),
+                }
+            }
+            Token::Ident(field) => {
+                let op = match self.next()? {
+                    Token::Op(op) => op,
+                    t => {
+                        return Err(FilterParseError(format!(
+                            "expected a comparison after {} but found {}",
+                            field, t
+                        )))
+                    }
+                };
+                match self.next()? {
+                    Token::Literal(value) => Ok(Filter::Compare { field, op, value }),
+                    //Allow unquoted words as string values, e.g. role == Admin
+                    Token::Ident(word) => Ok(Filter::Compare {
+                        field,
+                        op,
+                        value: Value::String(word),
+                    }),
+                    t => Err(FilterParseError(format!(
+                        "expected a value but found {}",
+                        t
+                    ))),
+                }
+            }
+            t => Err(FilterParseError(format!(
+                "expected a field but found {}",
+                t
+            ))),
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use domo::public::group::Group;
-use domo::public::Client;
+use domo::public::{Client, GroupId, UserId};
 
+use std::path::PathBuf;
 use structopt::StructOpt;
 
+use super::filter::{self, Filter};
 use super::util;
 
 /// Wraps the group api
@@ -15,35 +17,75 @@ pub enum GroupCommand {
         limit: Option<u32>,
         #[structopt(short = "o", long = "offset")]
         offset: Option<u32>,
+        /// Only show matches, e.g. "name contains 'finance' && active == true"
+        #[structopt(short = "f", long = "filter")]
+        filter: Option<Filter>,
+    },
+    /// Get a list of every group in your Domo instance.
+    #[structopt(name = "list-all")]
+    ListAll {
+        /// Only show matches, e.g. "name contains 'finance' && active == true"
+        #[structopt(short = "f", long = "filter")]
+        filter: Option<Filter>,
     },
     /// Creates a new group in your Domo instance.
     #[structopt(name = "create")]
     CreateGroup {},
     /// Retrieves the details of an existing group.
     #[structopt(name = "retrieve")]
-    Retrieve { id: String },
+    Retrieve { id: GroupId },
     /// Updates the specified group by providing values to parameters passed.
     /// Any parameter left out of the request will cause the specific group’s attribute to remain unchanged.
     #[structopt(name = "update")]
-    UpdateGroup { id: String },
+    UpdateGroup { id: GroupId },
     /// Permanently deletes a group from your Domo instance.
     #[structopt(name = "delete")]
-    DeleteGroup { id: String },
-    /// List the users in a group in your Domo instance.
+    DeleteGroup { id: GroupId },
+    /// List the users in a group in your Domo instance. Lists every user unless --limit or --offset is given.
     #[structopt(name = "list-users")]
-    ListUsers { id: String },
+    ListUsers {
+        id: GroupId,
+        #[structopt(short = "l", long = "limit")]
+        limit: Option<u32>,
+        #[structopt(short = "o", long = "offset")]
+        offset: Option<u32>,
+    },
     /// Add user to a group in your Domo instance.
     #[structopt(name = "add-user")]
-    AddUser { group_id: String, user_id: String },
+    AddUser { group_id: GroupId, user_id: UserId },
     /// Remove a user from a group in your Domo instance.
     #[structopt(name = "remove-user")]
-    RemoveUser { group_id: String, user_id: String },
+    RemoveUser { group_id: GroupId, user_id: UserId },
+    /// Add every user id listed in a file, one per line, to a group
+    #[structopt(name = "add-users")]
+    AddUsers {
+        group_id: GroupId,
+        #[structopt(short = "f", long = "file", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Remove every user id listed in a file, one per line, from a group
+    #[structopt(name = "remove-users")]
+    RemoveUsers {
+        group_id: GroupId,
+        #[structopt(short = "f", long = "file", parse(from_os_str))]
+        file: PathBuf,
+    },
 }
 
 pub async fn execute(dc: Client, editor: &str, template: Option<String>, command: GroupCommand) {
     match command {
-        GroupCommand::List { limit, offset } => {
+        GroupCommand::List {
+            limit,
+            offset,
+            filter,
+        } => {
             let r = dc.get_groups(limit, offset).await.unwrap();
+            let r = filter::apply(r, &filter);
+            util::vec_obj_template_output(r, template);
+        }
+        GroupCommand::ListAll { filter } => {
+            let r = dc.get_groups_all().await.unwrap();
+            let r = filter::apply(r, &filter);
             util::vec_obj_template_output(r, template);
         }
         GroupCommand::CreateGroup {} => {
@@ -53,27 +95,47 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             util::obj_template_output(r, template);
         }
         GroupCommand::Retrieve { id } => {
-            let r = dc.get_group(&id).await.unwrap();
+            let r = dc.get_group(id).await.unwrap();
             util::obj_template_output(r, template);
         }
         GroupCommand::UpdateGroup { id } => {
-            let r = dc.get_group(&id).await.unwrap();
+            let r = dc.get_group(id).await.unwrap();
             let r = util::edit_obj(editor, r, "").unwrap();
-            let r = dc.put_group(&id, r).await.unwrap();
+            let r = dc.put_group(id, r).await.unwrap();
             util::obj_template_output(r, template);
         }
         GroupCommand::DeleteGroup { id } => {
-            dc.delete_group(&id).await.unwrap();
+            dc.delete_group(id).await.unwrap();
         }
-        GroupCommand::ListUsers { id } => {
-            let r = dc.get_group_users(&id).await.unwrap();
+        GroupCommand::ListUsers {
+            id,
+            limit: None,
+            offset: None,
+        } => {
+            let r = dc.get_group_users_all(id).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        GroupCommand::ListUsers { id, limit, offset } => {
+            let r = dc.get_group_users(id, limit, offset).await.unwrap();
             util::vec_obj_template_output(r, template);
         }
         GroupCommand::AddUser { group_id, user_id } => {
-            dc.put_group_user(&group_id, &user_id).await.unwrap();
+            dc.put_group_user(group_id, user_id).await.unwrap();
         }
         GroupCommand::RemoveUser { group_id, user_id } => {
-            dc.delete_group_user(&group_id, &user_id).await.unwrap();
+            dc.delete_group_user(group_id, user_id).await.unwrap();
+        }
+        GroupCommand::AddUsers { group_id, file } => {
+            let ids: Vec<UserId> = util::read_ids(&file);
+            if let Err(e) = dc.add_group_users(group_id, &ids).await {
+                util::fail(e);
+            }
+        }
+        GroupCommand::RemoveUsers { group_id, file } => {
+            let ids: Vec<UserId> = util::read_ids(&file);
+            if let Err(e) = dc.remove_group_users(group_id, &ids).await {
+                util::fail(e);
+            }
         }
     }
 }
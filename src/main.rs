@@ -1,12 +1,18 @@
 use domo::public::Client;
 
+use futures::FutureExt;
+use std::panic::AssertUnwindSafe;
 use structopt::StructOpt;
 
 mod account;
 mod activity;
+mod audit;
 mod buzz;
+mod card;
 mod dataset;
+mod filter;
 mod group;
+mod notify;
 mod page;
 mod stream;
 mod user;
@@ -43,13 +49,18 @@ struct DomoApp {
     #[structopt(long = "clientsecret", env = "DOMO_API_CLIENT_SECRET")]
     client_secret: String,
 
-    /// This application can output in different formats, like json, csv, or yaml.
+    /// This application can output in different formats, like json, csv, yaml, or table.
     /// It will default to yaml where possible as it is easier to read in the terminal.
     /// You can override if you'd like to output a more convient format.
     #[structopt(short = "t", long = "template")]
-    /// Defines the output template. Can be json, csv, yaml, and debug. Used if the command supports variable output
+    /// Defines the output template. Can be json, csv, yaml, table, and debug. Used if the command supports variable output
     template: Option<String>,
 
+    /// A Buzz webhook url to post a message to when the command finishes or fails.
+    /// Useful for long running uploads, reconciles and exports.
+    #[structopt(long = "notify-buzz-url", env = "DOMO_NOTIFY_BUZZ_URL")]
+    notify_buzz_url: Option<String>,
+
     /// The different apis will be available as subcommands
     #[structopt(subcommand)]
     command: DomoCommand,
@@ -72,6 +83,13 @@ enum DomoCommand {
         command: activity::ActivityCommand,
     },
 
+    /// Reports that read across several apis
+    #[structopt(name = "audit")]
+    Audit {
+        #[structopt(subcommand)]
+        command: audit::AuditCommand,
+    },
+
     /// Wraps the buzz api
     #[structopt(name = "buzz")]
     Buzz {
@@ -79,6 +97,13 @@ enum DomoCommand {
         command: buzz::BuzzCommand,
     },
 
+    /// Wraps the card api
+    #[structopt(name = "card")]
+    Card {
+        #[structopt(subcommand)]
+        command: card::CardCommand,
+    },
+
     /// Wraps the dataset api
     #[structopt(name = "dataset")]
     DataSet {
@@ -135,32 +160,42 @@ async fn main() {
 
     let dc = Client::new(&app.host, &app.client_id, &app.client_secret);
 
-    match app.command {
-        DomoCommand::Account { command } => {
-            account::execute(dc, &app.editor, app.template, command).await
-        }
-        DomoCommand::Activity { command } => activity::execute(dc, app.template, command).await,
-        DomoCommand::Buzz { command } => {
-            buzz::execute(dc, &app.editor, app.template, command).await
-        }
-        DomoCommand::DataSet { command } => {
-            dataset::execute(dc, &app.editor, app.template, command).await
-        }
-        DomoCommand::Group { command } => {
-            group::execute(dc, &app.editor, app.template, command).await
-        }
-        DomoCommand::Page { command } => {
-            page::execute(dc, &app.editor, app.template, command).await
-        }
-        DomoCommand::Stream { command } => {
-            stream::execute(dc, &app.editor, app.template, command).await
-        }
-        DomoCommand::User { command } => {
-            user::execute(dc, &app.editor, app.template, command).await
-        }
-        DomoCommand::Webhook { command } => wh::execute(&app.editor, command).await,
-        DomoCommand::Workflow { command } => {
-            workflow::execute(dc, &app.editor, app.template, command).await
+    match app.notify_buzz_url {
+        Some(url) => notify::init(url),
+        None => return execute(dc, &app.editor, app.template, app.command).await,
+    }
+    let result = AssertUnwindSafe(execute(dc, &app.editor, app.template, app.command))
+        .catch_unwind()
+        .await;
+    match &result {
+        Ok(()) => notify::send(Ok(())).await,
+        Err(panic) => {
+            let reason = panic
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| panic.downcast_ref::<&str>().copied())
+                .unwrap_or("unknown error");
+            notify::send(Err(reason)).await
         }
     }
+    if let Err(panic) = result {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+async fn execute(dc: Client, editor: &str, template: Option<String>, command: DomoCommand) {
+    match command {
+        DomoCommand::Account { command } => account::execute(dc, editor, template, command).await,
+        DomoCommand::Activity { command } => activity::execute(dc, template, command).await,
+        DomoCommand::Audit { command } => audit::execute(dc, template, command).await,
+        DomoCommand::Buzz { command } => buzz::execute(dc, editor, template, command).await,
+        DomoCommand::Card { command } => card::execute(dc, template, command).await,
+        DomoCommand::DataSet { command } => dataset::execute(dc, editor, template, command).await,
+        DomoCommand::Group { command } => group::execute(dc, editor, template, command).await,
+        DomoCommand::Page { command } => page::execute(dc, editor, template, command).await,
+        DomoCommand::Stream { command } => stream::execute(dc, editor, template, command).await,
+        DomoCommand::User { command } => user::execute(dc, editor, template, command).await,
+        DomoCommand::Webhook { command } => wh::execute(editor, command).await,
+        DomoCommand::Workflow { command } => workflow::execute(dc, editor, template, command).await,
+    }
 }
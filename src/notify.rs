@@ -0,0 +1,75 @@
+use domo::webhook::buzz::Message;
+
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use super::util;
+
+/// Where and since when a run reports its outcome, set once by main from --notify-buzz-url
+struct Notify {
+    url: String,
+    start: Instant,
+}
+
+static NOTIFY: OnceLock<Notify> = OnceLock::new();
+
+/// Posts the outcome of this run to url when it finishes or fails
+pub fn init(url: String) {
+    let _ = NOTIFY.set(Notify {
+        url,
+        start: Instant::now(),
+    });
+}
+
+/// Posts that the command finished, or why it failed, if --notify-buzz-url was given.
+/// A notification that can not be posted is reported on stderr but does not change the outcome.
+pub async fn send(outcome: Result<(), &str>) {
+    let notify = match NOTIFY.get() {
+        Some(n) => n,
+        None => return,
+    };
+    let command = command_line();
+    let elapsed = util::human_duration(notify.start.elapsed());
+    let message = match outcome {
+        Ok(()) => Message {
+            title: Some(format!("domo {} finished", command)),
+            text: format!("Finished in {}", elapsed),
+        },
+        Err(reason) => Message {
+            title: Some(format!("domo {} failed", command)),
+            text: format!("Failed after {}:\n```\n{}\n```", elapsed, reason),
+        },
+    };
+    if let Err(e) = domo::webhook::Client::new()
+        .post_buzz_message(&notify.url, message)
+        .await
+    {
+        eprintln!("Could not post to --notify-buzz-url: {}", e);
+    }
+}
+
+/// The arguments the cli was run with, minus credentials and urls that may hold tokens
+fn command_line() -> String {
+    const SECRET: &[&str] = &[
+        "--clientid",
+        "--clientsecret",
+        "--notify-buzz-url",
+        "--token",
+        "--url",
+    ];
+    let mut args = Vec::new();
+    let mut skip_value = false;
+    for arg in std::env::args().skip(1) {
+        if skip_value {
+            skip_value = false;
+            continue;
+        }
+        let name = arg.split('=').next().unwrap_or_default();
+        if SECRET.contains(&name) {
+            skip_value = !arg.contains('=');
+            continue;
+        }
+        args.push(arg);
+    }
+    args.join(" ")
+}
@@ -1,6 +1,10 @@
 use domo::public::page::Collection;
 use domo::public::page::Page;
-use domo::public::Client;
+use domo::public::page::PageBundle;
+use domo::public::{CardId, Client, GroupId, PageId, UserId};
+
+use std::fs;
+use std::path::PathBuf;
 
 use structopt::StructOpt;
 
@@ -16,29 +20,152 @@ pub enum PageCommand {
         #[structopt(short = "o", long = "offset")]
         offset: Option<u32>,
     },
+    /// Get a list of every page.
+    #[structopt(name = "list-all")]
+    ListAll {},
+
     #[structopt(name = "create")]
     Create {},
 
     #[structopt(name = "retrieve")]
-    Retrieve { id: u64 },
+    Retrieve { id: PageId },
 
     #[structopt(name = "update")]
-    Update { id: u64 },
+    Update { id: PageId },
 
     #[structopt(name = "delete")]
-    Delete { id: u64 },
+    Delete { id: PageId },
 
     #[structopt(name = "list-collections")]
-    ListCollections { id: u64 },
+    ListCollections { id: PageId },
 
     #[structopt(name = "create-collection")]
-    CreateCollection { id: u64 },
+    CreateCollection { id: PageId },
 
     #[structopt(name = "update-collection")]
-    UpdateCollection { id: u64, collection_id: u64 },
+    UpdateCollection { id: PageId, collection_id: u64 },
 
     #[structopt(name = "delete-collection")]
-    DeleteCollection { id: u64, collection_id: u64 },
+    DeleteCollection { id: PageId, collection_id: u64 },
+
+    /// Copies a page with its cards and collections. Sub pages and sharing are not copied.
+    #[structopt(name = "clone")]
+    Clone {
+        id: PageId,
+        /// The name of the copy
+        #[structopt(short = "n", long = "name")]
+        name: String,
+        /// Put the copy under this page instead of next to the original
+        #[structopt(short = "p", long = "parent")]
+        parent: Option<PageId>,
+    },
+
+    /// Shares a page with more users and groups
+    #[structopt(name = "share")]
+    Share {
+        id: PageId,
+        #[structopt(short = "u", long = "user", number_of_values = 1)]
+        users: Vec<UserId>,
+        #[structopt(short = "g", long = "group", number_of_values = 1)]
+        groups: Vec<GroupId>,
+    },
+
+    /// Stops sharing a page with users and groups
+    #[structopt(name = "unshare")]
+    Unshare {
+        id: PageId,
+        #[structopt(short = "u", long = "user", number_of_values = 1)]
+        users: Vec<UserId>,
+        #[structopt(short = "g", long = "group", number_of_values = 1)]
+        groups: Vec<GroupId>,
+    },
+
+    /// Puts a page's collections in the given order. Every collection on the page must be listed.
+    #[structopt(name = "reorder-collections")]
+    ReorderCollections {
+        id: PageId,
+        #[structopt(required = true)]
+        collection_ids: Vec<u64>,
+    },
+
+    /// Replaces the cards in a collection
+    #[structopt(name = "set-collection-cards")]
+    SetCollectionCards {
+        id: PageId,
+        collection_id: u64,
+        card_ids: Vec<CardId>,
+    },
+
+    /// Lists everyone who can see a page, directly or through a group, with how they got access
+    #[structopt(name = "access-report")]
+    AccessReport { id: PageId },
+
+    /// Stops users other than the owner from changing the page
+    #[structopt(name = "lock")]
+    Lock { id: PageId },
+
+    /// Lets users the page is shared with change it again
+    #[structopt(name = "unlock")]
+    Unlock { id: PageId },
+
+    /// Gives a page to a new owner
+    #[structopt(name = "chown")]
+    Chown {
+        id: PageId,
+        owner_id: UserId,
+        /// Also give away every sub page
+        #[structopt(short = "r", long = "recursive")]
+        recursive: bool,
+    },
+
+    /// Adds cards to a page
+    #[structopt(name = "add-cards")]
+    AddCards {
+        id: PageId,
+        #[structopt(required = true)]
+        card_ids: Vec<CardId>,
+    },
+
+    /// Removes cards from a page
+    #[structopt(name = "remove-cards")]
+    RemoveCards {
+        id: PageId,
+        #[structopt(required = true)]
+        card_ids: Vec<CardId>,
+    },
+
+    /// Moves a page and its sub pages under another page.
+    /// Everyone the page is shared with gains access to the new parent, and the moved pages are seen
+    /// through the new parent's sharing instead of the old; the report lists who gains or loses access.
+    #[structopt(name = "move")]
+    Move {
+        id: PageId,
+        #[structopt(short = "p", long = "parent")]
+        parent: PageId,
+        /// Print who would gain or lose access without moving the page
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+    },
+
+    /// Saves a page with its sharing and collections as a yaml bundle
+    #[structopt(name = "export")]
+    Export {
+        id: PageId,
+        /// Write to this file instead of stdout
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Creates a page from a yaml bundle written by page export, possibly in another instance.
+    /// Card, user, and group ids in the bundle must exist in the target instance.
+    #[structopt(name = "import")]
+    Import {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// Create the page under this page instead of the parent in the bundle
+        #[structopt(short = "p", long = "parent")]
+        parent: Option<PageId>,
+    },
 }
 
 pub async fn execute(dc: Client, editor: &str, template: Option<String>, command: PageCommand) {
@@ -47,6 +174,10 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             let r = dc.get_pages(limit, offset).await.unwrap();
             util::vec_obj_template_output(r, template);
         }
+        PageCommand::ListAll {} => {
+            let r = dc.get_pages_all().await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
         PageCommand::Create {} => {
             let r = Page::template();
             let r = util::edit_obj(editor, r, "").unwrap();
@@ -94,10 +225,97 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
                 }
             };
             let r = util::edit_obj(editor, r, "").unwrap();
-            dc.put_page_collection(id, collection_id, r).await.unwrap();
+            let r = dc.put_page_collection(id, collection_id, r).await.unwrap();
+            util::obj_template_output(r, template);
         }
         PageCommand::DeleteCollection { id, collection_id } => {
             dc.delete_page_collection(id, collection_id).await.unwrap();
         }
+        PageCommand::Clone { id, name, parent } => {
+            let r = dc.clone_page(id, &name, parent).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::Share { id, users, groups } => {
+            let r = dc.share_page_with(id, &users, &groups).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::Unshare { id, users, groups } => {
+            let r = dc.unshare_page(id, &users, &groups).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::ReorderCollections { id, collection_ids } => {
+            match dc.reorder_collections(id, &collection_ids).await {
+                Ok(r) => util::obj_template_output(r, template),
+                Err(e) => util::fail(e),
+            }
+        }
+        PageCommand::SetCollectionCards {
+            id,
+            collection_id,
+            card_ids,
+        } => {
+            let r = dc
+                .set_collection_cards(id, collection_id, &card_ids)
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::AccessReport { id } => {
+            let r = dc.get_page_access(id).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        PageCommand::Lock { id } => {
+            let r = dc.set_page_locked(id, true).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::Unlock { id } => {
+            let r = dc.set_page_locked(id, false).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::Chown {
+            id,
+            owner_id,
+            recursive,
+        } => {
+            let r = dc.set_page_owner(id, owner_id, recursive).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        PageCommand::AddCards { id, card_ids } => {
+            let r = dc.add_cards_to_page(id, &card_ids).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::RemoveCards { id, card_ids } => {
+            let r = dc.remove_cards_from_page(id, &card_ids).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        PageCommand::Move {
+            id,
+            parent,
+            dry_run,
+        } => {
+            let r = if dry_run {
+                dc.plan_page_move(id, parent).await
+            } else {
+                dc.move_page(id, parent).await
+            };
+            match r {
+                Ok(report) => util::obj_template_output(report, template),
+                Err(e) => util::fail(e),
+            }
+        }
+        PageCommand::Export { id, output } => {
+            let r = dc.export_page(id).await.unwrap();
+            let yaml = serde_yaml::to_string(&r).unwrap();
+            match output {
+                Some(path) => fs::write(path, yaml).unwrap(),
+                None => print!("{}", yaml),
+            }
+        }
+        PageCommand::Import { file, parent } => {
+            let bundle: PageBundle =
+                serde_yaml::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let r = dc.import_page(bundle, parent).await.unwrap();
+            util::obj_template_output(r, template);
+        }
     }
 }
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Account, AccountType};
+use crate::public::{Client, PropertyValue};
+
+/// The accounts an instance should have, read from a file by `domo account apply`
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AccountManifest {
+    pub accounts: Vec<AccountSpec>,
+}
+
+/// One declared account. Accounts are matched to existing ones by name.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct AccountSpec {
+    /// The name of the Account
+    pub name: String,
+
+    /// The id of the Account Type
+    #[serde(rename = "type")]
+    pub account_type: String,
+
+    /// The Account Type properties
+    pub properties: HashMap<String, PropertyValue>,
+}
+
+/// What apply_accounts will do, built by plan_accounts
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPlan {
+    /// Declared accounts with no Account of that name
+    pub create: Vec<Account>,
+
+    /// Existing Accounts whose properties differ from the manifest, to be patched
+    pub update: Vec<AccountUpdate>,
+
+    /// Names of declared Accounts that already match the manifest
+    pub unchanged: Vec<String>,
+}
+
+/// An existing Account to patch and why
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountUpdate {
+    /// The id of the existing Account
+    pub id: String,
+
+    /// The Account as declared, sent as the patch
+    pub account: Account,
+
+    /// The properties that differ, or that the api does not return when the update is forced
+    pub changed: Vec<String>,
+}
+
+impl std::fmt::Display for AccountPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        for a in &self.create {
+            lines.push(format!("+ create {}", a.name.as_deref().unwrap_or("")));
+        }
+        for u in &self.update {
+            lines.push(format!(
+                "~ update {} ({}): {}",
+                u.account.name.as_deref().unwrap_or(""),
+                u.id,
+                u.changed.join(", ")
+            ));
+        }
+        for name in &self.unchanged {
+            lines.push(format!("= {} is up to date", name));
+        }
+        if lines.is_empty() {
+            return write!(f, "No accounts declared");
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Client {
+    /// Matches the manifest's accounts to the existing Accounts by name and plans what to create and update.
+    ///
+    /// Property values are read from the environment here, and any unset variables are reported together.
+    /// An existing Account of a different type is an error, as the type of an Account can not be changed.
+    /// Nothing is changed.
+    ///
+    /// Existing Accounts are only patched when a declared property differs from what the api returns.
+    /// The api leaves credentials out, so those can not be compared; with force every declared property
+    /// the api does not return counts as changed, e.g. to push a rotated password.
+    pub async fn plan_accounts(
+        &self,
+        manifest: &AccountManifest,
+        force: bool,
+    ) -> Result<AccountPlan, Box<dyn Error + Send + Sync + 'static>> {
+        let mut unset = Vec::new();
+        let mut declared = Vec::new();
+        for spec in &manifest.accounts {
+            let mut properties = HashMap::new();
+            for (k, v) in &spec.properties {
+                match v.resolve() {
+                    Ok(s) => {
+                        properties.insert(k.clone(), s);
+                    }
+                    Err(env) => unset.push(env.to_string()),
+                }
+            }
+            declared.push(Account {
+                name: Some(spec.name.clone()),
+                account_type: Some(AccountType {
+                    id: Some(spec.account_type.clone()),
+                    properties: Some(properties),
+                    ..Default::default()
+                }),
+                ..Account::new()
+            });
+        }
+        if !unset.is_empty() {
+            unset.sort();
+            unset.dedup();
+            return Err(format!("environment variables not set: {}", unset.join(", ")).into());
+        }
+
+        let existing = self.get_accounts_all().await?;
+        let mut plan = AccountPlan::default();
+        for account in declared {
+            let current = existing.iter().find(|a| a.name == account.name);
+            match current {
+                Some(current) => {
+                    let current_type = current.account_type.as_ref().and_then(|t| t.id.as_ref());
+                    let wanted_type = account.account_type.as_ref().and_then(|t| t.id.as_ref());
+                    if current_type.is_some() && current_type != wanted_type {
+                        return Err(format!(
+                            "account {} is a {} account, not {}",
+                            account.name.as_deref().unwrap_or(""),
+                            current_type.map(String::as_str).unwrap_or(""),
+                            wanted_type.map(String::as_str).unwrap_or("")
+                        )
+                        .into());
+                    }
+                    let id = current.id.clone().ok_or("account without an id")?;
+                    let returned = self
+                        .get_account(&id)
+                        .await?
+                        .account_type
+                        .and_then(|t| t.properties)
+                        .unwrap_or_default();
+                    let mut changed: Vec<String> = account
+                        .account_type
+                        .as_ref()
+                        .and_then(|t| t.properties.as_ref())
+                        .into_iter()
+                        .flatten()
+                        .filter(|(k, v)| match returned.get(*k) {
+                            Some(current) => current != *v,
+                            None => force,
+                        })
+                        .map(|(k, _)| k.clone())
+                        .collect();
+                    if changed.is_empty() {
+                        plan.unchanged.push(account.name.unwrap_or_default());
+                    } else {
+                        changed.sort();
+                        plan.update.push(AccountUpdate {
+                            id,
+                            account,
+                            changed,
+                        });
+                    }
+                }
+                None => plan.create.push(account),
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Creates and patches the planned Accounts, then returns the ones that need to be re-authorized,
+    /// that is those Domo reports as not valid.
+    pub async fn apply_accounts(
+        &self,
+        plan: AccountPlan,
+    ) -> Result<Vec<Account>, Box<dyn Error + Send + Sync + 'static>> {
+        let mut invalid = Vec::new();
+        for account in plan.create {
+            let created = self.post_account(account).await?;
+            if created.valid == Some(false) {
+                invalid.push(created);
+            }
+        }
+        for u in plan.update {
+            let patched = self.patch_account(&u.id, u.account).await?;
+            if patched.valid == Some(false) {
+                invalid.push(patched);
+            }
+        }
+        Ok(invalid)
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use super::{Account, AccountType};
+
+/// An Account Type's properties as a struct, so a missing credential is a compile error
+/// rather than a rejected request.
+///
+/// Structs are declared with connector_account!, and `domo account generate-type <type>` writes the
+/// declaration from the live Account Type template, so the type id and property names match the instance.
+pub trait ConnectorAccount {
+    /// The id of the Account Type
+    const ACCOUNT_TYPE: &'static str;
+
+    /// The properties, named as in the Account Type's template
+    fn properties(self) -> HashMap<String, String>;
+
+    /// An Account of this type, ready for post_account
+    fn into_account(self, name: &str) -> Account
+    where
+        Self: Sized,
+    {
+        Account {
+            name: Some(String::from(name)),
+            account_type: Some(AccountType {
+                id: Some(String::from(Self::ACCOUNT_TYPE)),
+                properties: Some(self.properties()),
+                ..Default::default()
+            }),
+            ..Account::new()
+        }
+    }
+}
+
+/// Declares a struct of account properties and its ConnectorAccount impl.
+/// Each field is followed by the property name it is sent as.
+#[macro_export]
+macro_rules! connector_account {
+    ($(#[$doc:meta])* $name:ident, $type_id:literal {
+        $($(#[$field_doc:meta])* $field:ident: $property:literal,)*
+    }) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            $($(#[$field_doc])* pub $field: String,)*
+        }
+
+        impl $crate::public::account::ConnectorAccount for $name {
+            const ACCOUNT_TYPE: &'static str = $type_id;
+
+            fn properties(self) -> ::std::collections::HashMap<String, String> {
+                let mut properties = ::std::collections::HashMap::new();
+                $(properties.insert(String::from($property), self.$field);)*
+                properties
+            }
+        }
+    };
+}
+
+/// Writes a connector_account! declaration for an Account Type from its template.
+/// Returns None when the type has no id or template.
+pub fn connector_account_source(account_type: &AccountType, struct_name: &str) -> Option<String> {
+    let type_id = account_type.id.as_ref()?;
+    let template = account_type.default_template()?;
+    let mut out = String::new();
+    out.push_str(&format!(
+        "// Generated from the {:?} Account Type template on {}\n",
+        type_id,
+        chrono::Utc::now().format("%Y-%m-%d")
+    ));
+    out.push_str("connector_account!(\n");
+    if let Some(title) = account_type.name.as_ref().or(template.title.as_ref()) {
+        out.push_str(&format!("    /// {}\n", title));
+    }
+    out.push_str(&format!("    {}, {:?} {{\n", struct_name, type_id));
+    for p in template.properties.iter().flatten() {
+        let name = match &p.name {
+            Some(n) => n,
+            None => continue,
+        };
+        if let Some(prompt) = &p.prompt {
+            out.push_str(&format!("        /// {}\n", prompt));
+        }
+        if p.required != Some(true) {
+            out.push_str("        /// Optional in the template\n");
+        }
+        out.push_str(&format!("        {}: {:?},\n", field_name(name), name));
+    }
+    out.push_str("    }\n);\n");
+    Some(out)
+}
+
+/// A snake case Rust field name for a property name such as accessKey or private-key
+fn field_name(property: &str) -> String {
+    let mut field = String::new();
+    for c in property.chars() {
+        if c.is_ascii_uppercase() {
+            if !field.is_empty() && !field.ends_with('_') {
+                field.push('_');
+            }
+            field.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            field.push(c);
+        } else if !field.ends_with('_') {
+            field.push('_');
+        }
+    }
+    if field.is_empty() || field.starts_with(|c: char| c.is_ascii_digit()) {
+        field.insert(0, '_');
+    }
+    match field.as_str() {
+        "type" | "match" | "ref" | "use" | "mod" | "fn" | "impl" | "self" | "where" | "loop" => {
+            format!("r#{}", field)
+        }
+        _ => field,
+    }
+}
@@ -1,6 +1,23 @@
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::{collections::HashMap, error::Error};
 
+use super::{GroupId, UserId};
+
+pub mod apply;
+pub use apply::{AccountManifest, AccountPlan};
+
+pub mod usage;
+pub use usage::AccountUsage;
+
+pub mod connectors;
+pub use connectors::{connector_account_source, ConnectorAccount};
+
+mod validate;
+pub use validate::{
+    validate_account_properties, InvalidAccountProperties, PropertyViolation, ViolationReason,
+};
+
 /// The Account API allows you to create, update, validate and share accounts in Domo.
 /// If you would like to manage a large number of accounts at scale from agencies or other 3rd party vendors that you currently manage individually through the Data Center in Domo, the Accounts API makes that possible.
 /// Note – The Accounts API will only return information for accounts you own or for accounts that have been shared with you in Domo.
@@ -57,6 +74,18 @@ pub struct AccountType {
     pub templates: Option<HashMap<String, AccountTemplate>>,
 }
 
+impl AccountType {
+    /// The template used to create Accounts of this type, the one named default or else the only one
+    pub fn default_template(&self) -> Option<&AccountTemplate> {
+        let templates = self.templates.as_ref()?;
+        match templates.get("default") {
+            Some(t) => Some(t),
+            None if templates.len() == 1 => templates.values().next(),
+            None => None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default, rename_all = "camelCase")]
 pub struct AccountTemplate {
@@ -92,6 +121,60 @@ pub struct Property {
     pub required: Option<bool>,
 }
 
+/// Someone an Account is shared with
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AccountShare {
+    /// The User the Account is shared with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<ShareUser>,
+
+    /// The Group the Account is shared with, for shares made through the v2 shares api
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<ShareGroup>,
+
+    /// What the User or Group can do with the Account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_level: Option<AccessLevel>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ShareGroup {
+    /// The ID of the Group
+    pub id: Option<GroupId>,
+
+    /// The name of the Group
+    pub name: Option<String>,
+}
+
+string_enum!(
+    /// What a User or Group can do with a shared Account
+    AccessLevel {
+        CanView => "CAN_VIEW",
+        CanEdit => "CAN_EDIT",
+        CanShare => "CAN_SHARE",
+        Owner => "OWNER",
+    }
+);
+
+/// Who a v2 share gives an Account to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareTarget {
+    User(UserId),
+    Group(GroupId),
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ShareUser {
+    /// The ID of the User
+    pub id: Option<UserId>,
+
+    /// The name of the User
+    pub name: Option<String>,
+}
+
 #[derive(Serialize)]
 struct ListParams {
     pub limit: Option<u32>,
@@ -121,16 +204,60 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns every Account the user can see, paging through get_accounts
+    pub async fn get_accounts_all(
+        &self,
+    ) -> Result<Vec<Account>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(50, |limit, offset| {
+            self.get_accounts(Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
+    /// Finds Accounts by name, and optionally by type, reading every Account.
+    /// The pattern is a case insensitive glob where * matches any run of characters and ? any one character.
+    /// The type matches the Account Type id or name, ignoring case.
+    pub async fn find_accounts(
+        &self,
+        name_pattern: &str,
+        account_type: Option<&str>,
+    ) -> Result<Vec<Account>, Box<dyn Error + Send + Sync + 'static>> {
+        let pattern = regex::escape(name_pattern)
+            .replace("\\*", ".*")
+            .replace("\\?", ".");
+        let name_re = regex::RegexBuilder::new(&format!("^{}$", pattern))
+            .case_insensitive(true)
+            .build()?;
+        let type_matches = |t: &Option<AccountType>| match account_type {
+            None => true,
+            Some(wanted) => t.as_ref().is_some_and(|t| {
+                [&t.id, &t.name]
+                    .iter()
+                    .any(|v| v.as_deref().is_some_and(|v| v.eq_ignore_ascii_case(wanted)))
+            }),
+        };
+        Ok(self
+            .get_accounts_all()
+            .await?
+            .into_iter()
+            .filter(|a| a.name.as_deref().is_some_and(|n| name_re.is_match(n)))
+            .filter(|a| type_matches(&a.account_type))
+            .collect())
+    }
+
     /// Create an Account
     /// When creating an Account, you must specify the Account Type properties.
     /// The Account Type properties are different, depending on the type of Account you are trying to create.
     /// To retrieve which Account Type properties to specify, use the GET /v1/accounts/account-types/{ACCOUNT_TYPE_ID} endpoint.
     /// Returns an Account object when successful.
     /// The returned object will not contain any properties within the Account Type object.
+    /// The properties are checked against the Account Type's template first, see validate_account.
     pub async fn post_account(
         &self,
         account: Account,
     ) -> Result<Account, Box<dyn Error + Send + Sync + 'static>> {
+        self.validate_account(&account, false).await?;
         let at = self.get_access_token("account").await?;
         let mut response = surf::post(&format!("{}{}", self.host, "/v1/accounts"))
             .header("Authorization", at)
@@ -164,12 +291,28 @@ impl super::Client {
     /// Updates the specified Account’s metadata as well as the Account’s Type properties.
     /// Returns the updated Account.
     ///
-    /// TODO: Should probably return the updated object for consistency
+    /// Any properties given are checked against the Account Type's template first, see validate_account.
     pub async fn patch_account(
         &self,
         id: &str,
         account: Account,
-    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    ) -> Result<Account, Box<dyn Error + Send + Sync + 'static>> {
+        if let Some(AccountType {
+            id: type_id,
+            properties: Some(properties),
+            ..
+        }) = &account.account_type
+        {
+            //A patch may leave out the type, so look it up on the existing Account
+            let type_id = match type_id {
+                Some(t) => Some(t.clone()),
+                None => self.get_account(id).await?.account_type.and_then(|t| t.id),
+            };
+            if let Some(type_id) = type_id {
+                self.check_account_properties(&type_id, properties, true)
+                    .await?;
+            }
+        }
         let at = self.get_access_token("account").await?;
         let mut response = surf::patch(&format!("{}{}{}", self.host, "/v1/accounts/", id))
             .header("Authorization", at)
@@ -202,7 +345,7 @@ impl super::Client {
     pub async fn post_account_share(
         &self,
         account_id: &str,
-        user_id: u64,
+        user_id: UserId,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("account").await?;
         // The User to share the Account with.
@@ -211,7 +354,7 @@ impl super::Client {
         #[derive(Serialize, Deserialize, Debug, Default)]
         #[serde(default, rename_all = "camelCase")]
         struct User {
-            id: u64,
+            id: UserId,
         }
         #[derive(Serialize, Deserialize, Debug, Default)]
         #[serde(default)]
@@ -235,6 +378,165 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Share an Account with a User or Group at the given access level, using the v2 shares api.
+    pub async fn post_account_share_v2(
+        &self,
+        account_id: &str,
+        target: ShareTarget,
+        access_level: AccessLevel,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("account").await?;
+        let (kind, id) = match target {
+            ShareTarget::User(id) => ("USER", id.0),
+            ShareTarget::Group(id) => ("GROUP", id.0),
+        };
+        let obj = json!({
+            "type": kind,
+            "id": id,
+            "accessLevel": access_level,
+        });
+        let mut response = surf::post(format!(
+            "{}{}{}{}",
+            self.host, "/v2/accounts/", account_id, "/shares"
+        ))
+        .header("Authorization", at)
+        .body(surf::Body::from_json(&obj)?)
+        .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Makes a User an owner of an Account through the v2 shares api.
+    /// The previous owners are read from the v2 shares, since only those carry access levels.
+    /// With remove_previous the other owning Users lose their shares, so they lose access entirely.
+    /// Returns the IDs of the previous owners.
+    pub async fn set_account_owner(
+        &self,
+        account_id: &str,
+        owner_id: UserId,
+        remove_previous: bool,
+    ) -> Result<Vec<UserId>, Box<dyn Error + Send + Sync + 'static>> {
+        let previous: Vec<UserId> = self
+            .get_account_shares_v2(account_id)
+            .await?
+            .into_iter()
+            .filter(|s| s.access_level == Some(AccessLevel::Owner))
+            .filter_map(|s| s.user?.id)
+            .filter(|id| *id != owner_id)
+            .collect();
+        self.post_account_share_v2(account_id, ShareTarget::User(owner_id), AccessLevel::Owner)
+            .await?;
+        if remove_previous {
+            for id in &previous {
+                self.delete_account_share(account_id, *id).await?;
+            }
+        }
+        Ok(previous)
+    }
+
+    /// Lists the Users an Account is shared with.
+    pub async fn get_account_shares(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<AccountShare>, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("account").await?;
+        let mut response = surf::get(format!(
+            "{}{}{}{}",
+            self.host, "/v1/accounts/", account_id, "/shares"
+        ))
+        .header("Authorization", at)
+        .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Lists the Users and Groups an Account is shared with and their access levels, using the v2 shares api.
+    pub async fn get_account_shares_v2(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<AccountShare>, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("account").await?;
+        let mut response = surf::get(format!(
+            "{}{}{}{}",
+            self.host, "/v2/accounts/", account_id, "/shares"
+        ))
+        .header("Authorization", at)
+        .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Stops sharing an Account with a User.
+    pub async fn delete_account_share(
+        &self,
+        account_id: &str,
+        user_id: UserId,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("account").await?;
+        let mut response = surf::delete(format!(
+            "{}{}{}{}{}",
+            self.host, "/v1/accounts/", account_id, "/shares/", user_id
+        ))
+        .header("Authorization", at)
+        .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Checks an Account's properties against the template of its Account Type.
+    /// A partial check, as for a patch, allows required properties to be left out.
+    /// Accounts without a type id or properties, and types without a template, are not checked.
+    pub async fn validate_account(
+        &self,
+        account: &Account,
+        partial: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        match &account.account_type {
+            Some(AccountType {
+                id: Some(type_id),
+                properties: Some(properties),
+                ..
+            }) => {
+                self.check_account_properties(type_id, properties, partial)
+                    .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn check_account_properties(
+        &self,
+        type_id: &str,
+        properties: &HashMap<String, String>,
+        partial: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let account_type = self.get_account_type(type_id).await?;
+        let template = match account_type.default_template() {
+            Some(t) => t,
+            None => return Ok(()),
+        };
+        let violations = validate_account_properties(template, properties, partial);
+        if !violations.is_empty() {
+            return Err(Box::new(InvalidAccountProperties {
+                account_type: String::from(type_id),
+                violations,
+            }));
+        }
+        Ok(())
+    }
+
     /// Get a list of all Account Types for which the user has permissions.
     /// Returns all Account Type objects that meet argument criteria from original request.
     pub async fn get_account_types(
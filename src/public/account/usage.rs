@@ -0,0 +1,101 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::public::{Client, DataSetId, Paginator, PubAPIError, StreamId};
+
+/// A stream whose metadata refers to an Account
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AccountUsage {
+    /// The id of the stream
+    pub stream_id: Option<StreamId>,
+
+    /// The id of the DataSet the stream feeds
+    pub dataset_id: Option<DataSetId>,
+
+    /// The name of the DataSet the stream feeds
+    pub dataset_name: Option<String>,
+
+    /// Where in the stream's json the account was found, e.g. dataSource.account.id
+    pub found_at: String,
+}
+
+impl Client {
+    /// Lists the streams, and the DataSets they feed, whose metadata refers to an Account.
+    ///
+    /// The public api has no lookup from an Account to what uses it, and only some stream types
+    /// include their account in the stream json. This reads every stream and looks for a field named
+    /// like account whose value is the id, so an empty result does not prove the Account is unused.
+    /// DataSets without a stream are not fed by a connector, so they are not checked.
+    pub async fn get_account_usage(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<AccountUsage>, Box<dyn Error + Send + Sync + 'static>> {
+        let streams = Paginator::new(500, |limit, offset| self.get_streams_json(limit, offset))
+            .all()
+            .await?;
+        let mut usage = Vec::new();
+        for stream in streams {
+            if let Some(found_at) = find_account(&stream, account_id, "", false) {
+                let dataset = stream.get("dataSet");
+                usage.push(AccountUsage {
+                    stream_id: serde_json::from_value(stream["id"].clone()).ok(),
+                    dataset_id: dataset.and_then(|d| serde_json::from_value(d["id"].clone()).ok()),
+                    dataset_name: dataset.and_then(|d| d["name"].as_str().map(String::from)),
+                    found_at,
+                });
+            }
+        }
+        Ok(usage)
+    }
+
+    /// A page of streams as plain json, so fields the Stream struct does not know about are kept
+    async fn get_streams_json(
+        &self,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Value>, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("data").await?;
+        let mut response = surf::get(format!(
+            "{}/v1/streams?limit={}&offset={}",
+            self.host, limit, offset
+        ))
+        .header("Authorization", at)
+        .await?;
+        if !response.status().is_success() {
+            let e: Box<PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+}
+
+/// The path of the first field that refers to the account: a field named like account holding the id,
+/// or an id field inside an object named like account.
+fn find_account(v: &Value, account_id: &str, path: &str, in_account: bool) -> Option<String> {
+    match v {
+        Value::Object(map) => map.iter().find_map(|(k, v)| {
+            let path = if path.is_empty() {
+                k.clone()
+            } else {
+                format!("{}.{}", path, k)
+            };
+            let named = k.to_lowercase().contains("account");
+            let is_id = match v {
+                Value::String(s) => s == account_id,
+                Value::Number(n) => n.to_string() == account_id,
+                _ => false,
+            };
+            if is_id && (named || (in_account && k == "id")) {
+                return Some(path);
+            }
+            find_account(v, account_id, &path, named)
+        }),
+        Value::Array(items) => items.iter().enumerate().find_map(|(i, v)| {
+            find_account(v, account_id, &format!("{}[{}]", path, i), in_account)
+        }),
+        _ => None,
+    }
+}
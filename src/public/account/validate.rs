@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use super::AccountTemplate;
+
+/// Why a property was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationReason {
+    /// The template requires the property but it was not given
+    Missing,
+    /// The value does not match the template's regex
+    NoMatch { regex: String },
+    /// The template does not define the property
+    Unknown,
+}
+
+/// A single property that does not satisfy the account type's template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyViolation {
+    pub property: String,
+    pub reason: ViolationReason,
+}
+
+impl std::fmt::Display for PropertyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reason {
+            ViolationReason::Missing => write!(f, "{} is required", self.property),
+            ViolationReason::NoMatch { regex } => {
+                write!(f, "{} does not match {}", self.property, regex)
+            }
+            ViolationReason::Unknown => {
+                write!(f, "{} is not a property of this type", self.property)
+            }
+        }
+    }
+}
+
+/// Account properties rejected by the account type's template
+#[derive(Debug)]
+pub struct InvalidAccountProperties {
+    pub account_type: String,
+    pub violations: Vec<PropertyViolation>,
+}
+
+impl std::error::Error for InvalidAccountProperties {}
+impl std::fmt::Display for InvalidAccountProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid properties for account type {}: ",
+            self.account_type
+        )?;
+        let violations: Vec<String> = self.violations.iter().map(|v| v.to_string()).collect();
+        write!(f, "{}", violations.join(", "))
+    }
+}
+
+/// Checks properties against a template's required flags and regexes.
+/// A partial check, as for a patch, skips the required properties that were not given.
+/// A regex the regex crate can not compile is not checked.
+pub fn validate_account_properties(
+    template: &AccountTemplate,
+    properties: &HashMap<String, String>,
+    partial: bool,
+) -> Vec<PropertyViolation> {
+    let defined = template.properties.as_deref().unwrap_or_default();
+    let mut violations = Vec::new();
+    for p in defined {
+        let name = match &p.name {
+            Some(n) => n,
+            None => continue,
+        };
+        match properties.get(name) {
+            None => {
+                if !partial && p.required.unwrap_or(false) {
+                    violations.push(PropertyViolation {
+                        property: name.clone(),
+                        reason: ViolationReason::Missing,
+                    });
+                }
+            }
+            Some(value) => {
+                let regex = match p.regex.as_deref().filter(|r| !r.is_empty()) {
+                    Some(r) => r,
+                    None => continue,
+                };
+                //The template regexes describe the whole value
+                if let Ok(re) = Regex::new(&format!("^(?:{})$", regex)) {
+                    if !re.is_match(value) {
+                        violations.push(PropertyViolation {
+                            property: name.clone(),
+                            reason: ViolationReason::NoMatch {
+                                regex: String::from(regex),
+                            },
+                        });
+                    }
+                }
+            }
+        }
+    }
+    let mut unknown: Vec<&String> = properties
+        .keys()
+        .filter(|k| !defined.iter().any(|p| p.name.as_ref() == Some(*k)))
+        .collect();
+    unknown.sort();
+    violations.extend(unknown.into_iter().map(|k| PropertyViolation {
+        property: k.clone(),
+        reason: ViolationReason::Unknown,
+    }));
+    violations
+}
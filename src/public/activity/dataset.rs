@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use serde::Serialize;
+
+use super::{EntryFilter, LogEntry};
+use crate::public::dataset::{diff_schema, Column, DataSet, Schema};
+use crate::public::stream::{Stream, StreamUploader, UploadReport};
+use crate::public::{Client, DataSetId, UserId};
+
+/// The columns entries are written as, in order, with their Domo types
+const ENTRY_COLUMNS: &[(&str, &str)] = &[
+    ("time", "DATETIME"),
+    ("userId", "STRING"),
+    ("userName", "STRING"),
+    ("userType", "STRING"),
+    ("actorId", "LONG"),
+    ("actorType", "STRING"),
+    ("objectType", "STRING"),
+    ("objectId", "STRING"),
+    ("objectName", "STRING"),
+    ("eventText", "STRING"),
+    ("additionalComment", "STRING"),
+    ("device", "STRING"),
+    ("browserDetails", "STRING"),
+    ("ipAddress", "STRING"),
+];
+
+/// The schema of a DataSet that holds activity log entries
+pub fn entry_schema() -> Schema {
+    Schema {
+        columns: Some(
+            ENTRY_COLUMNS
+                .iter()
+                .map(|(name, column_type)| Column {
+                    name: Some(String::from(*name)),
+                    column_type: Some(String::from(*column_type)),
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// A LogEntry flattened to a csv row in the order of entry_schema
+#[derive(Serialize)]
+struct EntryRow {
+    time: Option<String>,
+    user_id: Option<String>,
+    user_name: Option<String>,
+    user_type: Option<String>,
+    actor_id: Option<UserId>,
+    actor_type: Option<String>,
+    object_type: Option<String>,
+    object_id: Option<String>,
+    object_name: Option<String>,
+    event_text: Option<String>,
+    additional_comment: Option<String>,
+    device: Option<String>,
+    browser_details: Option<String>,
+    ip_address: Option<String>,
+}
+
+impl From<LogEntry> for EntryRow {
+    fn from(e: LogEntry) -> Self {
+        EntryRow {
+            time: e.time.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()),
+            user_id: e.user_id.map(|id| id.to_string()),
+            user_name: e.user_name,
+            user_type: e.user_type.map(String::from),
+            actor_id: e.actor_id,
+            actor_type: e.actor_type,
+            object_type: e.object_type.map(String::from),
+            object_id: e.object_id,
+            object_name: e.object_name,
+            event_text: e.event_text,
+            additional_comment: e.additional_comment,
+            device: e.device,
+            browser_details: e.browser_details,
+            ip_address: e.ip_address,
+        }
+    }
+}
+
+impl Client {
+    /// Creates a DataSet with entry_schema and an APPEND stream to load activity log entries into
+    pub async fn create_entry_dataset(
+        &self,
+        name: &str,
+    ) -> Result<Stream, Box<dyn Error + Send + Sync + 'static>> {
+        let mut dataset = DataSet::new();
+        dataset.name = Some(String::from(name));
+        dataset.description = Some(String::from("Domo activity log entries"));
+        dataset.schema = Some(entry_schema());
+        let mut stream = Stream::new();
+        stream.update_method = Some(String::from("APPEND"));
+        stream.dataset = Some(dataset);
+        self.post_stream(stream).await
+    }
+
+    /// Appends the entries in the range to a DataSet through its stream.
+    ///
+    /// The DataSet must have an APPEND stream and the columns of entry_schema, as made by create_entry_dataset.
+    /// Entries are read in full before the upload starts.
+    pub async fn append_entries_to_dataset(
+        &self,
+        dataset_id: &DataSetId,
+        range: Range<DateTime<Utc>>,
+        filter: EntryFilter,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>> {
+        let stream = self
+            .get_stream_search_dataset_id(dataset_id)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("dataset {} has no stream", dataset_id))?;
+        let stream_id = stream.id.ok_or("stream without an id")?;
+        if stream.update_method.as_deref() != Some("APPEND") {
+            return Err(format!(
+                "stream {} uses {}, it must be APPEND so earlier entries are kept",
+                stream_id,
+                stream
+                    .update_method
+                    .as_deref()
+                    .unwrap_or("no update method")
+            )
+            .into());
+        }
+        let current = self
+            .get_dataset(dataset_id)
+            .await?
+            .schema
+            .unwrap_or_default();
+        let diff = diff_schema(&current, &entry_schema());
+        if !diff.is_empty() {
+            return Err(format!(
+                "dataset {} does not have the activity log columns:\n{}",
+                dataset_id, diff
+            )
+            .into());
+        }
+        let entries: Vec<LogEntry> = self.get_entries_all(range, filter).try_collect().await?;
+        StreamUploader::new(self, stream_id)
+            .upload_rows(entries.into_iter().map(EntryRow::from))
+            .await
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::{EntryFilter, LogEntry};
+use crate::public::{write_atomic, Client};
+
+/// Records how far an activity export has got, so the next run only pulls newer entries
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ActivityCheckpoint {
+    /// The time of the newest entry exported
+    pub last_time: Option<DateTime<Utc>>,
+
+    /// The entries exported with exactly last_time, as json. The next run starts at last_time
+    /// so entries sharing that millisecond aren't lost, and skips these.
+    pub seen_at_last_time: Vec<String>,
+}
+
+impl ActivityCheckpoint {
+    /// Reads a checkpoint, or starts a new one if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync + 'static>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        write_atomic(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Writes the entries newer than the checkpoint to out as newline delimited json, and moves the checkpoint
+    /// up to the newest entry written. A new checkpoint starts at start. Returns how many entries were written.
+    /// Up to concurrency days are fetched at once, see get_entries_parallel; entries are written in time order.
+    ///
+    /// Save the checkpoint only once out has been flushed; if a run fails before then, the next run repeats it.
+    pub async fn export_entries(
+        &self,
+        checkpoint: &mut ActivityCheckpoint,
+        start: DateTime<Utc>,
+        filter: EntryFilter,
+        concurrency: usize,
+        out: &mut impl Write,
+    ) -> Result<usize, Box<dyn Error + Send + Sync + 'static>> {
+        let mut written = 0;
+        self.for_each_new_entry(checkpoint, start, filter, concurrency, |_, line| {
+            writeln!(out, "{}", line)?;
+            written += 1;
+            Ok(())
+        })
+        .await?;
+        Ok(written)
+    }
+
+    /// Returns the entries newer than the checkpoint in time order, and moves the checkpoint up to the newest.
+    /// A new checkpoint starts at start.
+    pub async fn get_new_entries(
+        &self,
+        checkpoint: &mut ActivityCheckpoint,
+        start: DateTime<Utc>,
+        filter: EntryFilter,
+    ) -> Result<Vec<LogEntry>, Box<dyn Error + Send + Sync + 'static>> {
+        let mut entries = Vec::new();
+        self.for_each_new_entry(checkpoint, start, filter, 1, |entry, _| {
+            entries.push(entry);
+            Ok(())
+        })
+        .await?;
+        Ok(entries)
+    }
+
+    /// Passes each entry newer than the checkpoint, with its json, to f, advancing the checkpoint as it goes
+    async fn for_each_new_entry(
+        &self,
+        checkpoint: &mut ActivityCheckpoint,
+        start: DateTime<Utc>,
+        filter: EntryFilter,
+        concurrency: usize,
+        mut f: impl FnMut(LogEntry, &str) -> Result<(), Box<dyn Error + Send + Sync + 'static>>,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let from = checkpoint.last_time.unwrap_or(start);
+        let seen: HashSet<String> = checkpoint.seen_at_last_time.drain(..).collect();
+        let mut entries =
+            Box::pin(self.get_entries_parallel(from..Utc::now(), filter, concurrency));
+        while let Some(entry) = entries.try_next().await? {
+            let line = serde_json::to_string(&entry)?;
+            if entry.time == Some(from) && seen.contains(&line) {
+                continue;
+            }
+            let time = entry.time;
+            f(entry, &line)?;
+            if let Some(time) = time {
+                if checkpoint.last_time.is_none_or(|last| time > last) {
+                    checkpoint.last_time = Some(time);
+                    checkpoint.seen_at_last_time.clear();
+                }
+                if Some(time) == checkpoint.last_time {
+                    checkpoint.seen_at_last_time.push(line);
+                }
+            }
+        }
+        //Nothing newer at the old last time means the entries seen there are still the ones to skip
+        if checkpoint.last_time == Some(from) {
+            checkpoint.seen_at_last_time.extend(seen);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use super::{LogEntry, ObjectType};
+use crate::public::UserId;
+
+/// Narrows the entries returned by get_entries_all.
+/// The audit api can only filter by user; everything else is checked as the entries arrive.
+#[derive(Debug, Default, Clone)]
+pub struct EntryFilter {
+    /// Only entries for this user
+    pub user_id: Option<UserId>,
+
+    /// Only entries about these kinds of object. Empty allows every kind.
+    pub object_types: Vec<ObjectType>,
+
+    /// Only entries whose event text contains this, ignoring case
+    pub event_text: Option<String>,
+
+    /// Only entries from these addresses. Empty allows every address.
+    pub ip_ranges: Vec<IpRange>,
+}
+
+impl EntryFilter {
+    /// True when the entry passes the client side parts of the filter
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if !self.object_types.is_empty()
+            && !entry
+                .object_type
+                .as_ref()
+                .is_some_and(|t| self.object_types.contains(t))
+        {
+            return false;
+        }
+        if let Some(text) = &self.event_text {
+            let text = text.to_lowercase();
+            if !entry
+                .event_text
+                .as_ref()
+                .is_some_and(|e| e.to_lowercase().contains(&text))
+            {
+                return false;
+            }
+        }
+        if !self.ip_ranges.is_empty() {
+            let ip = entry
+                .ip_address
+                .as_deref()
+                .and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+            if !ip.is_some_and(|ip| self.ip_ranges.iter().any(|r| r.contains(ip))) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// An address block in CIDR form, e.g. 10.0.0.0/8, or a single address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    pub addr: IpAddr,
+    pub prefix: u8,
+}
+
+impl IpRange {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct IpRangeParseError(String);
+
+impl std::error::Error for IpRangeParseError {}
+impl std::fmt::Display for IpRangeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid ip range {}", self.0)
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = IpRangeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || IpRangeParseError(String::from(s));
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((a, p)) => (a, Some(p)),
+            None => (s.trim(), None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| err())?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p.parse().map_err(|_| err())?,
+            None => max,
+        };
+        if prefix > max {
+            return Err(err());
+        }
+        Ok(IpRange { addr, prefix })
+    }
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::{ActivityCheckpoint, EntryFilter, LogEntry};
+use crate::public::Client;
+
+/// How many times a batch is retried before forward_entries gives up
+const FORWARD_RETRIES: u32 = 3;
+
+impl Client {
+    /// Posts the entries newer than the checkpoint to url as json arrays of up to batch_size entries,
+    /// retrying each batch with backoff. Returns how many entries were sent.
+    ///
+    /// The checkpoint only moves once every batch has been accepted, so a failed run is sent again in full
+    /// by the next one and the receiver may see an entry more than once.
+    pub async fn forward_entries(
+        &self,
+        url: &str,
+        checkpoint: &mut ActivityCheckpoint,
+        start: DateTime<Utc>,
+        filter: EntryFilter,
+        batch_size: usize,
+    ) -> Result<usize, Box<dyn Error + Send + Sync + 'static>> {
+        let mut next = checkpoint.clone();
+        let entries = self.get_new_entries(&mut next, start, filter).await?;
+        for batch in entries.chunks(batch_size.max(1)) {
+            post_batch(url, batch).await?;
+        }
+        *checkpoint = next;
+        Ok(entries.len())
+    }
+}
+
+async fn post_batch(
+    url: &str,
+    batch: &[LogEntry],
+) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    let mut attempt = 0;
+    loop {
+        let error = match surf::post(url).body(surf::Body::from_json(&batch)?).await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => format!("{} answered {}", url, response.status()),
+            Err(e) => e.to_string(),
+        };
+        if attempt == FORWARD_RETRIES {
+            return Err(error.into());
+        }
+        async_std::task::sleep(Duration::from_secs(1 << attempt)).await;
+        attempt += 1;
+    }
+}
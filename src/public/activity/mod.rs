@@ -1,8 +1,35 @@
 use std::error::Error;
+use std::ops::Range;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 
+use super::UserId;
+
+pub mod export;
+pub use export::ActivityCheckpoint;
+
+mod filter;
+pub use filter::{EntryFilter, IpRange, IpRangeParseError};
+
+pub mod report;
+pub use report::{EntryCount, GroupBy};
+
+pub mod dataset;
+pub use dataset::entry_schema;
+
+mod forward;
+
+/// The most entries get_entries returns at once
+const ENTRY_PAGE_SIZE: u32 = 1000;
+
+/// How much time get_entries_all asks for at once
+const ENTRY_WINDOW_HOURS: i64 = 24;
+
+/// How many times a page is retried when Domo answers 429 Too Many Requests
+const ENTRY_RATE_LIMIT_RETRIES: u32 = 5;
+
 /// Activity Log Entry Object
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default, rename_all = "camelCase")]
@@ -11,13 +38,13 @@ pub struct LogEntry {
     pub user_name: Option<String>,
 
     /// The id of the user
-    pub user_id: Option<String>,
+    pub user_id: Option<UserId>,
 
     /// Type of user
-    pub user_type: Option<String>,
+    pub user_type: Option<UserType>,
 
     /// Id of proxying user if applicable
-    pub actor_id: Option<u64>,
+    pub actor_id: Option<UserId>,
 
     /// Type of user performing the proxy if applicable
     pub actor_type: Option<String>,
@@ -29,7 +56,7 @@ pub struct LogEntry {
     pub object_id: Option<String>,
 
     /// The type of object affected
-    pub object_type: Option<String>,
+    pub object_type: Option<ObjectType>,
 
     /// Description of the log entry, with additional information if applicable
     pub additional_comment: Option<String>,
@@ -50,10 +77,37 @@ pub struct LogEntry {
     pub ip_address: Option<String>,
 }
 
+string_enum!(
+    /// The kind of object an entry is about
+    ObjectType {
+        Account => "ACCOUNT",
+        Alert => "ALERT",
+        App => "APP",
+        Card => "CARD",
+        DataFlow => "DATAFLOW",
+        DataSet => "DATASET",
+        DataSource => "DATA_SOURCE",
+        Group => "GROUP",
+        Page => "PAGE",
+        Role => "ROLE",
+        User => "USER",
+    }
+);
+
+string_enum!(
+    /// The kind of user that acted
+    UserType {
+        User => "USER",
+        Client => "CLIENT",
+        DeveloperToken => "DEVELOPER_TOKEN",
+        System => "SYSTEM",
+    }
+);
+
 #[derive(Serialize)]
 struct ListParams {
     #[serde(rename = "user")]
-    pub user_id: Option<u64>,
+    pub user_id: Option<UserId>,
     pub start: u64,
     pub end: Option<u64>,
     pub limit: Option<u32>,
@@ -62,7 +116,7 @@ struct ListParams {
 /// Activity Log API methods
 /// Uses the form method_object
 impl super::Client {
-    /// Retrieves activity log entries
+    /// Retrieves activity log entries, waiting for the client's rate limit if one is set
     ///
     /// Params
     /// * user_id: The Id of the user
@@ -72,12 +126,13 @@ impl super::Client {
     /// * offset: The offset location of events you retrieve(default is 0)
     pub async fn get_entries(
         &self,
-        user_id: Option<u64>,
+        user_id: Option<UserId>,
         start: u64,
         end: Option<u64>,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<LogEntry>, Box<dyn Error + Send + Sync + 'static>> {
+        self.wait_turn().await;
         let at = self.get_access_token("audit").await?;
         let q = ListParams {
             user_id,
@@ -96,4 +151,125 @@ impl super::Client {
         }
         Ok(response.body_json().await?)
     }
+
+    /// Returns every entry in the range as a stream, asking for a day at a time and paging within each day.
+    /// Entries come a window at a time, earliest window first, in the order the api gives them within a window.
+    pub fn get_entries_all(
+        &self,
+        range: Range<DateTime<Utc>>,
+        filter: EntryFilter,
+    ) -> impl Stream<Item = Result<LogEntry, Box<dyn Error + Send + Sync + 'static>>> + '_ {
+        let window = Duration::hours(ENTRY_WINDOW_HOURS);
+        let end = range.end;
+        stream::try_unfold(Some((range.start, 0)), move |state| {
+            let filter = filter.clone();
+            async move {
+                let (start, offset) = match state {
+                    Some(s) if s.0 < end => s,
+                    _ => return Ok::<_, Box<dyn Error + Send + Sync + 'static>>(None),
+                };
+                let window_end = std::cmp::min(start + window, end);
+                let (page, full) = self
+                    .get_entries_window(start..window_end, &filter, offset)
+                    .await?;
+                let next = if full {
+                    Some((start, offset + ENTRY_PAGE_SIZE))
+                } else {
+                    Some((window_end, 0))
+                };
+                Ok(Some((stream::iter(page.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Returns every entry in the range as a stream, fetching up to concurrency days at once.
+    /// Entries come in time order. Each day is read in full before it is passed on,
+    /// so at most concurrency days of entries are held in memory.
+    pub fn get_entries_parallel(
+        &self,
+        range: Range<DateTime<Utc>>,
+        filter: EntryFilter,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<LogEntry, Box<dyn Error + Send + Sync + 'static>>> + '_ {
+        let window = Duration::hours(ENTRY_WINDOW_HOURS);
+        let mut windows = Vec::new();
+        let mut start = range.start;
+        while start < range.end {
+            let end = std::cmp::min(start + window, range.end);
+            windows.push(start..end);
+            start = end;
+        }
+        stream::iter(windows)
+            .map(move |w| {
+                let filter = filter.clone();
+                async move { self.get_window_entries(w, &filter).await }
+            })
+            .buffered(concurrency.max(1))
+            .map_ok(|entries| stream::iter(entries.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Every entry in a window, in time order
+    async fn get_window_entries(
+        &self,
+        window: Range<DateTime<Utc>>,
+        filter: &EntryFilter,
+    ) -> Result<Vec<LogEntry>, Box<dyn Error + Send + Sync + 'static>> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        loop {
+            let (page, full) = self
+                .get_entries_window(window.clone(), filter, offset)
+                .await?;
+            entries.extend(page);
+            if !full {
+                break;
+            }
+            offset += ENTRY_PAGE_SIZE;
+        }
+        entries.sort_by_key(|e| e.time);
+        Ok(entries)
+    }
+
+    /// One page of a time window, and whether the page was full so there may be more.
+    /// Entries outside the window are dropped, so neighbouring windows never both return an entry
+    /// whether or not the api treats the end time as inclusive.
+    async fn get_entries_window(
+        &self,
+        window: Range<DateTime<Utc>>,
+        filter: &EntryFilter,
+        offset: u32,
+    ) -> Result<(Vec<LogEntry>, bool), Box<dyn Error + Send + Sync + 'static>> {
+        let mut attempt = 0;
+        let page = loop {
+            let r = self
+                .get_entries(
+                    filter.user_id,
+                    window.start.timestamp_millis() as u64,
+                    Some(window.end.timestamp_millis() as u64),
+                    Some(ENTRY_PAGE_SIZE),
+                    Some(offset),
+                )
+                .await;
+            match r {
+                Err(e)
+                    if attempt < ENTRY_RATE_LIMIT_RETRIES
+                        && e.downcast_ref::<super::PubAPIError>()
+                            .is_some_and(|e| e.status == 429) =>
+                {
+                    //Back off 1, 2, 4... seconds so parallel fetches stay under the rate limit
+                    async_std::task::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+                    attempt += 1;
+                }
+                r => break r?,
+            }
+        };
+        let full = page.len() as u32 == ENTRY_PAGE_SIZE;
+        let page = page
+            .into_iter()
+            .filter(|e| e.time.is_none_or(|t| window.contains(&t)) && filter.matches(e))
+            .collect();
+        Ok((page, full))
+    }
 }
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::ops::Range;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::{EntryFilter, LogEntry};
+use crate::public::Client;
+
+/// What get_entry_counts counts entries by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    User,
+    ObjectType,
+    Event,
+}
+
+impl GroupBy {
+    fn key(&self, entry: &LogEntry) -> String {
+        let key = match self {
+            GroupBy::User => entry
+                .user_name
+                .clone()
+                .or_else(|| entry.user_id.map(|id| id.to_string())),
+            GroupBy::ObjectType => entry.object_type.as_ref().map(|t| t.to_string()),
+            GroupBy::Event => entry.event_text.clone(),
+        };
+        key.unwrap_or_else(|| String::from("(none)"))
+    }
+}
+
+#[derive(Debug)]
+pub struct GroupByParseError(String);
+
+impl std::error::Error for GroupByParseError {}
+impl std::fmt::Display for GroupByParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "can not group by {}, use user, object-type or event",
+            self.0
+        )
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = GroupByParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "user" => Ok(GroupBy::User),
+            "object-type" | "type" => Ok(GroupBy::ObjectType),
+            "event" => Ok(GroupBy::Event),
+            _ => Err(GroupByParseError(String::from(s))),
+        }
+    }
+}
+
+/// How many entries share a user, object type, or event
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EntryCount {
+    pub key: String,
+    pub count: u64,
+}
+
+impl Client {
+    /// Counts the entries in the range by user, object type, or event, most common first.
+    /// Entries are counted as they stream in, so a long range does not need to fit in memory.
+    pub async fn get_entry_counts(
+        &self,
+        range: Range<DateTime<Utc>>,
+        filter: EntryFilter,
+        group_by: GroupBy,
+    ) -> Result<Vec<EntryCount>, Box<dyn Error + Send + Sync + 'static>> {
+        let counts = self
+            .get_entries_all(range, filter)
+            .try_fold(HashMap::new(), |mut counts, entry| async move {
+                *counts.entry(group_by.key(&entry)).or_insert(0) += 1;
+                Ok(counts)
+            })
+            .await?;
+        let mut counts: Vec<EntryCount> = counts
+            .into_iter()
+            .map(|(key, count)| EntryCount { key, count })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        Ok(counts)
+    }
+}
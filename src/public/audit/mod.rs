@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+
+use super::page::Page;
+
+/// How many pages find_orphans fetches at once
+const PAGE_FETCH_CONCURRENCY: usize = 8;
+
+/// A piece of content that looks unused, as found by find_orphans
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Orphan {
+    /// page, dataset, or group
+    pub kind: String,
+    pub id: String,
+    pub name: Option<String>,
+    /// Why it was flagged
+    pub reason: String,
+}
+
+/// Cross api audit methods
+/// Uses the form method_object
+impl super::Client {
+    /// Finds content that is probably safe to clean up: pages without cards, datasets with no stream whose data
+    /// has not changed within stale_after, and groups without members.
+    ///
+    /// Nothing is changed. This reads every page, dataset, stream, and group, so it can take a while on large instances.
+    pub async fn find_orphans(
+        &self,
+        stale_after: Duration,
+    ) -> Result<Vec<Orphan>, Box<dyn Error + Send + Sync + 'static>> {
+        let mut orphans = Vec::new();
+
+        //The page list only has ids and children, so each page is fetched to see its cards
+        let mut ids = Vec::new();
+        let mut pending: Vec<Page> = self.get_pages_all().await?;
+        while let Some(p) = pending.pop() {
+            pending.extend(p.children.unwrap_or_default());
+            ids.extend(p.id);
+        }
+        ids.sort_unstable();
+        let pages: Vec<Page> = stream::iter(ids)
+            .map(|id| self.get_page(id))
+            .buffered(PAGE_FETCH_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        for p in pages {
+            if p.card_ids.as_ref().is_none_or(|c| c.is_empty()) {
+                orphans.push(Orphan {
+                    kind: String::from("page"),
+                    id: p.id.map(|id| id.to_string()).unwrap_or_default(),
+                    name: p.name,
+                    reason: String::from("no cards"),
+                });
+            }
+        }
+
+        let streamed: HashSet<_> = self
+            .get_streams_all()
+            .await?
+            .into_iter()
+            .filter_map(|s| s.dataset?.id)
+            .collect();
+        let stale_before = Utc::now() - chrono::Duration::from_std(stale_after)?;
+        for d in self.get_datasets_all().await? {
+            let id = match d.id {
+                Some(id) => id,
+                None => continue,
+            };
+            if streamed.contains(&id) {
+                continue;
+            }
+            match d.data_current_at {
+                Some(at) if at >= stale_before => {}
+                Some(at) => orphans.push(Orphan {
+                    kind: String::from("dataset"),
+                    id: id.to_string(),
+                    name: d.name,
+                    reason: format!("no stream, data last updated {}", at.format("%Y-%m-%d")),
+                }),
+                None => orphans.push(Orphan {
+                    kind: String::from("dataset"),
+                    id: id.to_string(),
+                    name: d.name,
+                    reason: String::from("no stream, never updated"),
+                }),
+            }
+        }
+
+        for g in self.get_groups_all().await? {
+            if g.member_count() == Some(0) {
+                orphans.push(Orphan {
+                    kind: String::from("group"),
+                    id: g.id().map(|id| id.to_string()).unwrap_or_default(),
+                    name: g.name().map(String::from),
+                    reason: String::from("no members"),
+                });
+            }
+        }
+        Ok(orphans)
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use super::{Channel, Event, EventServer, EventType, User};
+use crate::webhook::buzz::Message;
+
+type CommandResult = Result<Option<Message>, Box<dyn Error + Send + Sync + 'static>>;
+type CommandHandler =
+    Arc<dyn Fn(SlashCommand) -> Pin<Box<dyn Future<Output = CommandResult> + Send>> + Send + Sync>;
+
+/// A slash command a user invoked, split into its name and arguments
+#[derive(Debug, Clone)]
+pub struct SlashCommand {
+    /// The command name without the leading slash, lower case
+    pub name: String,
+
+    /// Everything after the command name, as typed
+    pub text: String,
+
+    /// The text split on whitespace
+    pub args: Vec<String>,
+
+    /// Who invoked the command
+    pub author: Option<User>,
+
+    /// The channel the command was invoked in
+    pub channel: Option<Channel>,
+
+    /// The thread the command was invoked in, if any
+    pub thread: Option<Channel>,
+
+    /// The event the command came from
+    pub event: Event,
+}
+
+impl SlashCommand {
+    /// Reads the command from a SLASH_COMMAND event's message text, e.g. "/deploy web --force"
+    pub fn parse(event: Event) -> Option<Self> {
+        let text = event.message.as_ref()?.text.as_deref()?.trim();
+        let text = text.strip_prefix('/').unwrap_or(text);
+        let (name, rest) = match text.split_once(char::is_whitespace) {
+            Some((name, rest)) => (name, rest.trim()),
+            None => (text, ""),
+        };
+        if name.is_empty() {
+            return None;
+        }
+        Some(SlashCommand {
+            name: name.to_lowercase(),
+            text: String::from(rest),
+            args: rest.split_whitespace().map(String::from).collect(),
+            author: event.author.clone(),
+            channel: event.channel.clone(),
+            thread: event.thread.clone(),
+            event,
+        })
+    }
+}
+
+/// Answers slash commands with the handler registered for the command's name.
+///
+/// A handler's message is posted back through the event's callback; None posts nothing.
+/// Commands without a handler are answered with the list of known commands. Needs the tide feature.
+#[derive(Default)]
+pub struct SlashCommandBot {
+    headers: Vec<(String, String)>,
+    commands: HashMap<String, CommandHandler>,
+}
+
+impl SlashCommandBot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accept requests carrying this header, as for EventServer::header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((String::from(name), String::from(value)));
+        self
+    }
+
+    /// Calls handler whenever the command is invoked. The name may be given with or without its slash.
+    pub fn command<F, Fut>(mut self, name: &str, handler: F) -> Self
+    where
+        F: Fn(SlashCommand) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = CommandResult> + Send + 'static,
+    {
+        let name = name.trim_start_matches('/').to_lowercase();
+        self.commands
+            .insert(name, Arc::new(move |c| Box::pin(handler(c))));
+        self
+    }
+
+    /// Serves on an address such as 0.0.0.0:8080 until the process stops
+    pub async fn listen(self, addr: &str) -> std::io::Result<()> {
+        let mut server = EventServer::new();
+        for (name, value) in &self.headers {
+            server = server.header(name, value);
+        }
+        let bot = Arc::new(self);
+        server
+            .on(EventType::SlashCommand, move |event| {
+                let bot = bot.clone();
+                async move { bot.answer(event).await }
+            })
+            .listen(addr)
+            .await
+    }
+
+    async fn answer(&self, event: Event) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let command = match SlashCommand::parse(event.clone()) {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let reply = match self.commands.get(&command.name) {
+            Some(handler) => handler(command).await?,
+            None => {
+                let mut known: Vec<String> =
+                    self.commands.keys().map(|k| format!("/{}", k)).collect();
+                known.sort();
+                Some(Message {
+                    title: None,
+                    text: format!(
+                        "Unknown command /{}. Try one of: {}",
+                        command.name,
+                        known.join(", ")
+                    ),
+                })
+            }
+        };
+        if let Some(message) = reply {
+            crate::webhook::Client::new()
+                .reply_to_event(&event, message)
+                .await?;
+        }
+        Ok(())
+    }
+}
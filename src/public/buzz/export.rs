@@ -0,0 +1,168 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Header, Integration, Scope, Subscription};
+use crate::public::{Client, PropertyValue};
+
+/// Buzz integrations and their subscriptions as written by `domo buzz export`.
+/// Ids are left out so the file can be imported into another instance.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct BuzzExport {
+    pub integrations: Vec<IntegrationExport>,
+}
+
+/// One integration without its id. Header values are secrets, so they are exported as
+/// environment variable references, e.g. `value: { env: BUZZ_ALERTS_X_MY_API_KEY }`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IntegrationExport {
+    pub name: String,
+    pub description: Option<String>,
+    pub scope: Option<Scope>,
+    pub channel_ids: Option<Vec<String>>,
+    pub headers: Vec<HeaderExport>,
+    pub subscriptions: Vec<Subscription>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HeaderExport {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+/// What import_integrations did
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BuzzImport {
+    /// The integrations created, with their new ids
+    pub created: Vec<Integration>,
+
+    /// Names of integrations that already existed and were left alone
+    pub skipped: Vec<String>,
+}
+
+/// The variable a header value is exported as, e.g. BUZZ_ALERTS_X_MY_API_KEY
+fn header_env(integration: &str, header: &str) -> String {
+    format!("BUZZ_{}_{}", integration, header)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl Client {
+    /// Reads every integration with its subscriptions, leaving out ids and header values
+    pub async fn export_integrations(
+        &self,
+    ) -> Result<BuzzExport, Box<dyn Error + Send + Sync + 'static>> {
+        let mut integrations = Vec::new();
+        for i in self.get_integrations().await? {
+            let id = i.id.ok_or("integration without an id")?;
+            let name = i.name.unwrap_or_default();
+            let headers = i
+                .headers
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|h| h.name)
+                .map(|h| HeaderExport {
+                    value: PropertyValue::Env {
+                        env: header_env(&name, &h),
+                    },
+                    name: h,
+                })
+                .collect();
+            let subscriptions = self
+                .get_integration_subscriptions(&id)
+                .await?
+                .into_iter()
+                .map(|s| Subscription { id: None, ..s })
+                .collect();
+            integrations.push(IntegrationExport {
+                name,
+                description: i.description,
+                scope: i.scope,
+                channel_ids: i.channel_ids,
+                headers,
+                subscriptions,
+            });
+        }
+        Ok(BuzzExport { integrations })
+    }
+
+    /// Creates the exported integrations and their subscriptions.
+    ///
+    /// Header values given as env references are read from the environment first, and any unset
+    /// variables are reported together before anything is created. Integrations are matched by name,
+    /// and one that already exists is skipped rather than changed.
+    pub async fn import_integrations(
+        &self,
+        export: BuzzExport,
+    ) -> Result<BuzzImport, Box<dyn Error + Send + Sync + 'static>> {
+        let mut unset = Vec::new();
+        let mut planned = Vec::new();
+        for i in export.integrations {
+            let mut headers = Vec::new();
+            for h in i.headers {
+                let value = match h.value.resolve() {
+                    Ok(s) => s,
+                    Err(env) => {
+                        unset.push(env.to_string());
+                        continue;
+                    }
+                };
+                headers.push(Header {
+                    name: Some(h.name),
+                    value: Some(value),
+                });
+            }
+            let integration = Integration {
+                id: None,
+                name: Some(i.name),
+                description: i.description,
+                scope: i.scope,
+                channel_ids: i.channel_ids,
+                headers: Some(headers),
+            };
+            integration.validate()?;
+            for s in &i.subscriptions {
+                s.validate()?;
+            }
+            planned.push((integration, i.subscriptions));
+        }
+        if !unset.is_empty() {
+            return Err(format!("environment variables not set: {}", unset.join(", ")).into());
+        }
+
+        let existing: Vec<String> = self
+            .get_integrations()
+            .await?
+            .into_iter()
+            .filter_map(|i| i.name)
+            .collect();
+        let mut report = BuzzImport::default();
+        for (integration, subscriptions) in planned {
+            let name = integration.name.clone().unwrap_or_default();
+            if existing.contains(&name) {
+                report.skipped.push(name);
+                continue;
+            }
+            let created = self.post_integration(integration).await?;
+            let id = created
+                .id
+                .as_deref()
+                .ok_or("created integration without an id")?;
+            for s in subscriptions {
+                self.post_integration_subscription(id, s).await?;
+            }
+            report.created.push(created);
+        }
+        Ok(report)
+    }
+}
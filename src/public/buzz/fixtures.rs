@@ -0,0 +1,66 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use super::{Event, EventRouter};
+
+/// Writes a received request body to dir as a json fixture named by time and event type,
+/// e.g. 20240131T120000.123456789Z-SLASH_COMMAND.json, and returns its path
+pub fn save_fixture(dir: &Path, body: &str) -> std::io::Result<PathBuf> {
+    let event_type = serde_json::from_str::<Event>(body)
+        .ok()
+        .and_then(|e| e.event)
+        .and_then(|e| e.event_type)
+        .map(String::from)
+        .unwrap_or_else(|| String::from("UNKNOWN"));
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "{}-{}.json",
+        Utc::now().format("%Y%m%dT%H%M%S%.9fZ"),
+        event_type
+    ));
+    fs::write(&path, body)?;
+    Ok(path)
+}
+
+/// Reads the events in a fixture file, or in every .json file of a directory in name order,
+/// which is the order save_fixture captured them in
+pub fn load_fixtures(path: &Path) -> Result<Vec<Event>, Box<dyn Error + Send + Sync + 'static>> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let p = entry?.path();
+            if p.extension().is_some_and(|e| e == "json") {
+                files.push(p);
+            }
+        }
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+    let mut events = Vec::new();
+    for f in files {
+        let event: Event = serde_json::from_str(&fs::read_to_string(&f)?)
+            .map_err(|e| format!("{}: {}", f.display(), e))?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Sends captured events through a router again, as if Buzz had just posted them.
+/// Their callbacks will usually have expired, so replies fail unless the handlers are being tried out offline.
+/// Returns how many events were replayed.
+pub async fn replay_fixtures(
+    router: Arc<EventRouter>,
+    path: &Path,
+) -> Result<usize, Box<dyn Error + Send + Sync + 'static>> {
+    let events = load_fixtures(path)?;
+    let count = events.len();
+    for event in events {
+        router.clone().handle(event).await?;
+    }
+    Ok(count)
+}
@@ -0,0 +1,97 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use super::{Event, Middleware};
+
+/// Prints each event's type, channel and author to stderr, and any error the handlers return
+pub fn logging() -> Middleware {
+    Arc::new(|event: Event, next| {
+        Box::pin(async move {
+            let event_type = event
+                .event
+                .as_ref()
+                .and_then(|e| e.event_type.clone())
+                .map(String::from)
+                .unwrap_or_else(|| String::from("UNKNOWN"));
+            let channel = event
+                .channel
+                .as_ref()
+                .and_then(|c| c.title.clone().or_else(|| c.id.clone()))
+                .unwrap_or_default();
+            let author = event
+                .author
+                .as_ref()
+                .and_then(|a| a.display_name.clone())
+                .unwrap_or_default();
+            eprintln!("{} in {} from {}", event_type, channel, author);
+            let result = next.run(event).await;
+            if let Err(e) = &result {
+                eprintln!("{} failed: {}", event_type, e);
+            }
+            result
+        })
+    })
+}
+
+/// Drops the events allowed returns false for, e.g. ones from outside an organization or by unknown users
+pub fn authorize<F>(allowed: F) -> Middleware
+where
+    F: Fn(&Event) -> bool + Send + Sync + 'static,
+{
+    let allowed = Arc::new(allowed);
+    Arc::new(move |event, next| {
+        let allowed = allowed.clone();
+        Box::pin(async move {
+            if !allowed(&event) {
+                return Ok(());
+            }
+            next.run(event).await
+        })
+    })
+}
+
+/// Drops events already seen among the last capacity events, as Buzz may deliver an event again
+/// when a handler is slow to answer.
+///
+/// Events carry no id of their own, so an event is keyed by its type and message id, or,
+/// for events without a message, by its content without the callback.
+pub fn dedup(capacity: usize) -> Middleware {
+    let seen = Arc::new(Mutex::new((VecDeque::new(), HashSet::new())));
+    Arc::new(move |event, next| {
+        let seen = seen.clone();
+        Box::pin(async move {
+            let key = event_key(&event);
+            {
+                let mut seen = seen.lock().unwrap();
+                let (order, keys) = &mut *seen;
+                if !keys.insert(key.clone()) {
+                    return Ok(());
+                }
+                order.push_back(key);
+                while order.len() > capacity {
+                    if let Some(old) = order.pop_front() {
+                        keys.remove(&old);
+                    }
+                }
+            }
+            next.run(event).await
+        })
+    })
+}
+
+fn event_key(event: &Event) -> String {
+    let event_type = event
+        .event
+        .as_ref()
+        .and_then(|e| e.event_type.clone())
+        .map(String::from)
+        .unwrap_or_default();
+    match event.message.as_ref().and_then(|m| m.id.as_deref()) {
+        Some(id) => format!("{}:{}", event_type, id),
+        None => {
+            let mut event = event.clone();
+            event.callback = None;
+            serde_json::to_string(&event).unwrap_or_default()
+        }
+    }
+}
@@ -1,6 +1,28 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, error::Error};
 
+pub mod export;
+pub use export::{BuzzExport, BuzzImport};
+
+pub mod verify;
+pub use verify::{verify_bot_token, verify_headers, UnverifiedRequest};
+
+mod fixtures;
+pub use fixtures::{load_fixtures, replay_fixtures, save_fixture};
+
+pub mod middleware;
+mod router;
+pub use router::{EventRouter, HandlerFuture, HandlerResult, Middleware, Next};
+
+#[cfg(feature = "tide")]
+mod bot;
+#[cfg(feature = "tide")]
+mod server;
+#[cfg(feature = "tide")]
+pub use bot::{SlashCommand, SlashCommandBot};
+#[cfg(feature = "tide")]
+pub use server::EventServer;
+
 /// A Buzz integration is a service hosted outside of Domo’s infrastructure that can receive events from Buzz, and can post messages to Buzz. To use this feature, invoke this API to register an integration, then create one or more event subscriptions for the integration. When a corresponding event occur, Buzz will POST an HTTP request using the configured URL and headers.
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default, rename_all = "camelCase")]
@@ -18,7 +40,7 @@ pub struct Integration {
     /// * PUBLIC_CHANNELS All public channels
     /// * OWNER_ACCESS All channels accessible by the user that creates the integration
     /// * CHANNEL_LIST All channels specified in the channelIds list
-    pub scope: Option<String>,
+    pub scope: Option<Scope>,
 
     /// Must be provided if and only if scope is CHANNEL_LIST
     pub channel_ids: Option<Vec<String>>,
@@ -44,9 +66,9 @@ impl Integration {
             id: Some(String::from("UUID")),
             name: Some(String::from("Integration Name")),
             description: Some(String::from("Integration Description")),
-            scope: Some(String::from(
+            scope: Some(Scope::Other(String::from(
                 "PUBLIC_CHANNELS | OWNER_ACCESS | CHANNEL_LIST",
-            )),
+            ))),
             channel_ids: Some(vec![
                 String::from("CHANNEL-A ID for CHANNEL_LIST scope"),
                 String::from("CHANNEL-B ID for CHANNEL_LIST scope"),
@@ -64,6 +86,44 @@ impl Integration {
             ]),
         }
     }
+
+    /// Checks that channel_ids is given if and only if the scope is CHANNEL_LIST
+    pub fn validate(&self) -> Result<(), InvalidBuzzRequest> {
+        let has_channels = self.channel_ids.as_ref().is_some_and(|c| !c.is_empty());
+        match (&self.scope, has_channels) {
+            (Some(Scope::ChannelList), false) => Err(InvalidBuzzRequest(String::from(
+                "channelIds must be given when scope is CHANNEL_LIST",
+            ))),
+            (Some(Scope::ChannelList), true) => Ok(()),
+            (_, true) => Err(InvalidBuzzRequest(String::from(
+                "channelIds can only be given when scope is CHANNEL_LIST",
+            ))),
+            (_, false) => Ok(()),
+        }
+    }
+}
+
+string_enum!(
+    /// Which channels an integration receives events from
+    Scope {
+        /// All public channels
+        PublicChannels => "PUBLIC_CHANNELS",
+        /// All channels accessible by the user that creates the integration
+        OwnerAccess => "OWNER_ACCESS",
+        /// All channels specified in the channelIds list
+        ChannelList => "CHANNEL_LIST",
+    }
+);
+
+/// An integration or subscription that Buzz would reject
+#[derive(Debug)]
+pub struct InvalidBuzzRequest(String);
+
+impl std::error::Error for InvalidBuzzRequest {}
+impl std::fmt::Display for InvalidBuzzRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid buzz request: {}", self.0)
+    }
 }
 
 /// The headers to include on integration calls
@@ -96,7 +156,7 @@ pub struct Subscription {
     /// * THREAD_CREATED
     /// * USERS_JOINED_CHANNEL
     /// * USERS_LEFT_CHANNEL
-    pub event_type: Option<String>,
+    pub event_type: Option<EventType>,
 
     /// The integration will post to this URL when an event occurs
     pub url: Option<String>,
@@ -118,14 +178,29 @@ impl Subscription {
     pub fn template() -> Self {
         Subscription{
             id: Some(String::from("UUID")),
-            event_type: Some(String::from("MESSAGE_POSTED | SLASH_COMMAND | THREAD_CREATED | USERS_JOINED_CHANNEL | USERS_LEFT_CHANNEL")),
+            event_type: Some(EventType::Other(String::from("MESSAGE_POSTED | SLASH_COMMAND | THREAD_CREATED | USERS_JOINED_CHANNEL | USERS_LEFT_CHANNEL"))),
             url: Some(String::from("The integration will post to this URL when an event occurs")),
             slash_command: Some(String::from("Required if and only if eventType is SLASH_COMMAND")),
         }
     }
+
+    /// Checks that slash_command is given if and only if the event type is SLASH_COMMAND
+    pub fn validate(&self) -> Result<(), InvalidBuzzRequest> {
+        let has_command = self.slash_command.as_ref().is_some_and(|c| !c.is_empty());
+        match (&self.event_type, has_command) {
+            (Some(EventType::SlashCommand), false) => Err(InvalidBuzzRequest(String::from(
+                "slashCommand must be given when eventType is SLASH_COMMAND",
+            ))),
+            (Some(EventType::SlashCommand), true) => Ok(()),
+            (_, true) => Err(InvalidBuzzRequest(String::from(
+                "slashCommand can only be given when eventType is SLASH_COMMAND",
+            ))),
+            (_, false) => Ok(()),
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Event {
     /// Included in events associated with a specific message, this object contains information about the author of the message.
@@ -156,22 +231,38 @@ pub struct Event {
     pub callback: Option<Callback>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct EventT {
     /// One of the subscription types
     #[serde(rename = "type")]
-    pub event_type: Option<String>,
+    pub event_type: Option<EventType>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+string_enum!(
+    /// The kinds of event Buzz sends to an integration
+    EventType {
+        /// A user posted a message
+        MessagePosted => "MESSAGE_POSTED",
+        /// A user invoked a specific slash command
+        SlashCommand => "SLASH_COMMAND",
+        /// A thread was created on the parent channel
+        ThreadCreated => "THREAD_CREATED",
+        /// One or more users joined the channel
+        UsersJoinedChannel => "USERS_JOINED_CHANNEL",
+        /// One or more users left the channel
+        UsersLeftChannel => "USERS_LEFT_CHANNEL",
+    }
+);
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Organization {
     pub domain: Option<String>,
 }
 
 /// A buzz user object. Simple user
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct User {
     /// A user Id
@@ -185,7 +276,7 @@ pub struct User {
 }
 
 /// A buzz message
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Message {
     /// The id of the buzz message
@@ -196,7 +287,7 @@ pub struct Message {
 }
 
 /// A buzz channel
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Channel {
     pub id: Option<String>,
@@ -205,7 +296,7 @@ pub struct Channel {
 }
 
 /// Callback information for posting a response message
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Callback {
     pub url: Option<String>,
@@ -242,6 +333,7 @@ impl super::Client {
         &self,
         integration: Integration,
     ) -> Result<Integration, Box<dyn Error + Send + Sync + 'static>> {
+        integration.validate()?;
         let at = self.get_access_token("buzz").await?;
         let mut response = surf::post(&format!("{}{}", self.host, "/v1/buzz/integrations"))
             .header("Authorization", at)
@@ -270,6 +362,25 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Updates an integration in place, keeping its id and subscriptions
+    pub async fn put_integration(
+        &self,
+        id: &str,
+        integration: Integration,
+    ) -> Result<Integration, Box<dyn Error + Send + Sync + 'static>> {
+        integration.validate()?;
+        let at = self.get_access_token("buzz").await?;
+        let mut response = surf::put(format!("{}{}{}", self.host, "/v1/buzz/integrations/", id))
+            .header("Authorization", at)
+            .body(surf::Body::from_json(&integration)?)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
     /// Permanently deletes a user from your Domo instance
     /// This is destructive and cannot be reversed.
     pub async fn delete_integration(
@@ -326,6 +437,7 @@ impl super::Client {
         id: &str,
         subscription: Subscription,
     ) -> Result<Subscription, Box<dyn Error + Send + Sync + 'static>> {
+        subscription.validate()?;
         let at = self.get_access_token("buzz").await?;
         let mut response = surf::post(&format!(
             "{}{}{}{}",
@@ -0,0 +1,142 @@
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use super::{Event, EventType};
+
+pub type HandlerResult = Result<(), Box<dyn Error + Send + Sync + 'static>>;
+pub type HandlerFuture = Pin<Box<dyn Future<Output = HandlerResult> + Send>>;
+type Handler = Arc<dyn Fn(Event) -> HandlerFuture + Send + Sync>;
+
+/// Runs around every event before it reaches the handlers. Call next.run(event) to pass the event on,
+/// or return without calling it to drop the event.
+pub type Middleware = Arc<dyn Fn(Event, Next) -> HandlerFuture + Send + Sync>;
+
+struct Route {
+    event_type: Option<EventType>,
+    channel_id: Option<String>,
+    handler: Handler,
+}
+
+impl Route {
+    fn matches(&self, event: &Event) -> bool {
+        let event_type = event.event.as_ref().and_then(|e| e.event_type.as_ref());
+        let channel_id = event.channel.as_ref().and_then(|c| c.id.as_deref());
+        self.event_type
+            .as_ref()
+            .is_none_or(|t| Some(t) == event_type)
+            && self
+                .channel_id
+                .as_deref()
+                .is_none_or(|c| Some(c) == channel_id)
+    }
+}
+
+/// Sends events through a middleware chain and on to every handler whose event type and channel match,
+/// in the order they were added, so a large bot can be composed from small handlers.
+///
+/// EventServer dispatches with one, and it can be used with other http frameworks through handle.
+#[derive(Default)]
+pub struct EventRouter {
+    middleware: Vec<Middleware>,
+    routes: Vec<Route>,
+}
+
+impl EventRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a middleware. Middleware runs in the order it was added, before any handler.
+    pub fn with<F, Fut>(self, middleware: F) -> Self
+    where
+        F: Fn(Event, Next) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        self.with_middleware(Arc::new(move |e, n| Box::pin(middleware(e, n))))
+    }
+
+    /// Adds a middleware built elsewhere, such as one from the middleware module
+    pub fn with_middleware(mut self, middleware: Middleware) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Calls handler for events of the type, from the channel if one is given.
+    /// Without an event type the handler gets every event from the channel, or every event at all.
+    pub fn route<F, Fut>(
+        mut self,
+        event_type: Option<EventType>,
+        channel_id: Option<&str>,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        self.routes.push(Route {
+            event_type,
+            channel_id: channel_id.map(String::from),
+            handler: Arc::new(move |e| Box::pin(handler(e))),
+        });
+        self
+    }
+
+    /// Calls handler for every event of this type
+    pub fn on<F, Fut>(self, event_type: EventType, handler: F) -> Self
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        self.route(Some(event_type), None, handler)
+    }
+
+    /// Calls handler for every event, whatever its type
+    pub fn on_any<F, Fut>(self, handler: F) -> Self
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        self.route(None, None, handler)
+    }
+
+    /// Runs the event through the middleware and then the matching handlers, stopping at the first error
+    pub async fn handle(self: Arc<Self>, event: Event) -> HandlerResult {
+        Next {
+            router: self,
+            index: 0,
+        }
+        .run(event)
+        .await
+    }
+
+    async fn dispatch(&self, event: Event) -> HandlerResult {
+        for route in self.routes.iter().filter(|r| r.matches(&event)) {
+            (route.handler)(event.clone()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The rest of the middleware chain and the handlers after it
+pub struct Next {
+    router: Arc<EventRouter>,
+    index: usize,
+}
+
+impl Next {
+    /// Passes the event on to the next middleware, or to the handlers after the last one
+    pub async fn run(self, event: Event) -> HandlerResult {
+        match self.router.middleware.get(self.index).cloned() {
+            Some(middleware) => {
+                let next = Next {
+                    router: self.router.clone(),
+                    index: self.index + 1,
+                };
+                middleware(event, next).await
+            }
+            None => self.router.dispatch(event).await,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{
+    save_fixture, verify_headers, Event, EventRouter, EventType, HandlerResult, Middleware, Next,
+};
+
+/// Receives the events Buzz posts to an integration's subscription urls and passes them to an EventRouter.
+///
+/// Requests missing any of the configured headers are refused with 401, and bodies that aren't events with 400.
+/// Handlers run before the response is sent, and a handler error answers 500, so keep them quick
+/// or spawn the slow part. Needs the tide feature.
+#[derive(Default)]
+pub struct EventServer {
+    headers: Vec<(String, String)>,
+    router: EventRouter,
+    capture: Option<PathBuf>,
+}
+
+impl EventServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accept requests carrying this header, e.g. one of the headers configured on the integration
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((String::from(name), String::from(value)));
+        self
+    }
+
+    /// Saves the body of every verified request to the directory as a json fixture before handling it,
+    /// so rare events can be replayed later with replay_fixtures
+    pub fn capture(mut self, dir: PathBuf) -> Self {
+        self.capture = Some(dir);
+        self
+    }
+
+    /// Dispatches with a router built elsewhere, replacing the handlers and middleware added so far
+    pub fn router(mut self, router: EventRouter) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Adds a middleware, as for EventRouter::with
+    pub fn with<F, Fut>(mut self, middleware: F) -> Self
+    where
+        F: Fn(Event, Next) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        self.router = self.router.with(middleware);
+        self
+    }
+
+    /// Adds a middleware built elsewhere, such as one from the middleware module
+    pub fn with_middleware(mut self, middleware: Middleware) -> Self {
+        self.router = self.router.with_middleware(middleware);
+        self
+    }
+
+    /// Calls handler for every event of this type
+    pub fn on<F, Fut>(mut self, event_type: EventType, handler: F) -> Self
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        self.router = self.router.on(event_type, handler);
+        self
+    }
+
+    /// Calls handler for every event, whatever its type
+    pub fn on_any<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        self.router = self.router.on_any(handler);
+        self
+    }
+
+    /// Serves on an address such as 0.0.0.0:8080 until the process stops. Events are accepted on any path.
+    pub async fn listen(self, addr: &str) -> std::io::Result<()> {
+        let state = State {
+            headers: self.headers,
+            router: Arc::new(self.router),
+            capture: self.capture,
+        };
+        let mut app = tide::with_state(Arc::new(state));
+        app.at("/").post(receive);
+        app.at("/*").post(receive);
+        app.listen(String::from(addr)).await
+    }
+}
+
+struct State {
+    headers: Vec<(String, String)>,
+    router: Arc<EventRouter>,
+    capture: Option<PathBuf>,
+}
+
+async fn receive(mut req: tide::Request<Arc<State>>) -> tide::Result {
+    let state = req.state().clone();
+    if verify_headers(&state.headers, |n| req.header(n).map(|v| v.as_str())).is_err() {
+        return Ok(tide::Response::new(tide::StatusCode::Unauthorized));
+    }
+    let body = req.body_string().await?;
+    if let Some(dir) = &state.capture {
+        save_fixture(dir, &body)?;
+    }
+    let event: Event = match serde_json::from_str(&body) {
+        Ok(e) => e,
+        Err(_) => return Ok(tide::Response::new(tide::StatusCode::BadRequest)),
+    };
+    match state.router.clone().handle(event).await {
+        Ok(()) => Ok(tide::Response::new(tide::StatusCode::Ok)),
+        Err(e) => Err(tide::Error::from_str(
+            tide::StatusCode::InternalServerError,
+            e.to_string(),
+        )),
+    }
+}
@@ -0,0 +1,54 @@
+/// The header Buzz bots authenticate with
+pub const BOT_TOKEN_HEADER: &str = "x-buzz-bot-token";
+
+/// An incoming request was missing a configured header or had the wrong value for it
+#[derive(Debug)]
+pub struct UnverifiedRequest {
+    pub header: String,
+}
+
+impl std::error::Error for UnverifiedRequest {}
+impl std::fmt::Display for UnverifiedRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "missing or wrong {} header", self.header)
+    }
+}
+
+/// Compares two secrets in time that depends only on their lengths, not on where they differ
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Checks that a request carries every expected header with its value, such as the headers configured
+/// on an integration. header looks a header up by name in whatever http framework received the request.
+pub fn verify_headers<'a, F>(
+    expected: &[(String, String)],
+    header: F,
+) -> Result<(), UnverifiedRequest>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    for (name, value) in expected {
+        let ok = header(name).is_some_and(|v| constant_time_eq(v.as_bytes(), value.as_bytes()));
+        if !ok {
+            return Err(UnverifiedRequest {
+                header: name.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks the x-buzz-bot-token header against the token
+pub fn verify_bot_token<'a, F>(token: &str, header: F) -> Result<(), UnverifiedRequest>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    verify_headers(
+        &[(String::from(BOT_TOKEN_HEADER), String::from(token))],
+        header,
+    )
+}
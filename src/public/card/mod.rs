@@ -0,0 +1,127 @@
+use std::error::Error;
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use super::{CardId, PageId, UserId};
+
+/// How many cards get_page_cards fetches at once
+const CARD_FETCH_CONCURRENCY: usize = 8;
+
+/// A card is a single visualization or KPI, placed on one or more pages.
+/// The Card API is read only; cards are placed on pages through the Page API.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Card {
+    /// The id of the card
+    pub id: Option<CardId>,
+
+    /// The card's urn, usually the id as a string
+    pub urn: Option<String>,
+
+    /// The title shown on the card
+    pub title: Option<String>,
+
+    /// The card description
+    pub description: Option<String>,
+
+    /// The kind of card, e.g. kpi, doc or notebook
+    #[serde(rename = "type")]
+    pub card_type: Option<String>,
+
+    /// The chart type of a kpi card, e.g. badge_vert_bar
+    pub chart_type: Option<String>,
+
+    /// The ID of the card owner
+    pub owner_id: Option<UserId>,
+
+    /// The name of the card owner
+    pub owner_name: Option<String>,
+
+    /// The IDs of the pages the card appears on
+    #[serde(rename = "pages")]
+    pub page_ids: Option<Vec<PageId>>,
+}
+
+/// Card API methods
+/// Uses the form method_object
+impl super::Client {
+    /// Get a list of the cards in your Domo instance.
+    pub async fn get_cards(
+        &self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Card>, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("dashboard").await?;
+        #[derive(Serialize)]
+        struct QueryParams {
+            pub limit: Option<u32>,
+            pub offset: Option<u32>,
+        }
+        let q = QueryParams { limit, offset };
+        let mut response = surf::get(format!("{}{}", self.host, "/v1/cards"))
+            .query(&q)?
+            .header("Authorization", at)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Returns every card, paging through get_cards
+    pub async fn get_cards_all(&self) -> Result<Vec<Card>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(500, |limit, offset| {
+            self.get_cards(Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
+    /// Retrieves the metadata of a card.
+    pub async fn get_card(
+        &self,
+        id: CardId,
+    ) -> Result<Card, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("dashboard").await?;
+        let mut response = surf::get(format!("{}{}{}", self.host, "/v1/cards/", id))
+            .header("Authorization", at)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Returns the cards on a page, in the order of the page's card_ids.
+    /// The API has no direct listing, so each card is fetched on its own.
+    pub async fn get_page_cards(
+        &self,
+        page_id: PageId,
+    ) -> Result<Vec<Card>, Box<dyn Error + Send + Sync + 'static>> {
+        let ids = self.get_page(page_id).await?.card_ids.unwrap_or_default();
+        stream::iter(ids)
+            .map(|id| self.get_card(id))
+            .buffered(CARD_FETCH_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns the cards owned by a user.
+    /// The API has no direct lookup, so this reads every card.
+    pub async fn get_user_cards(
+        &self,
+        user_id: UserId,
+    ) -> Result<Vec<Card>, Box<dyn Error + Send + Sync + 'static>> {
+        Ok(self
+            .get_cards_all()
+            .await?
+            .into_iter()
+            .filter(|c| c.owner_id == Some(user_id))
+            .collect())
+    }
+}
@@ -1,8 +1,13 @@
+use async_std::io::{BufRead as AsyncBufRead, BufReader, ReadExt};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use serde_json::Value;
-use std::{error::Error, path::Path};
+use serde_json::{Map, Value};
+use std::{error::Error, fs, path::Path, time::Duration};
+
+use crate::public::stream::Stream;
+use crate::public::{write_atomic, Progress, ProgressReader};
+use crate::public::{DataSetId, GroupId, UserId};
 
 /// The DataSet object allows you to create, import, export and manage DataSets and manage data permissions for DataSets within Domo.
 ///
@@ -11,7 +16,7 @@ use std::{error::Error, path::Path};
 #[serde(default, rename_all = "camelCase")]
 pub struct DataSet {
     /// ID of the DataSet
-    pub id: Option<String>,
+    pub id: Option<DataSetId>,
 
     /// Name of the DataSet
     pub name: Option<String>,
@@ -66,11 +71,11 @@ impl DataSet {
     }
     pub fn template() -> Self {
         DataSet {
-            id: Some(String::from("UUID")),
+            id: Some(DataSetId::from("UUID")),
             name: Some(String::from("DataSet Name")),
             description: Some(String::from("DataSet Description")),
             owner: Some(Owner {
-                id: 1234,
+                id: UserId(1234),
                 name: Some(String::from("DataSet Owner's Name")),
             }),
             created_at: Some(Utc::now()),
@@ -97,13 +102,13 @@ impl DataSet {
 #[serde(default)]
 pub struct Owner {
     /// ID of the owner
-    pub id: u32,
+    pub id: UserId,
     /// Name of the owner
     pub name: Option<String>,
 }
 
 /// Defines a schema of a dataset
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default)]
 pub struct Schema {
     /// Array of columns in the DataSet
@@ -111,7 +116,7 @@ pub struct Schema {
 }
 
 /// A single column schema definition for a dataset
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default)]
 pub struct Column {
     /// Column name in the DataSet schema
@@ -122,6 +127,143 @@ pub struct Column {
     pub column_type: Option<String>,
 }
 
+/// A single column difference between two schemas.
+/// Columns are compared by position, as that is how Domo lines up CSV data with the schema.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum ColumnChange {
+    /// A column exists in the desired schema but not the current one
+    Added { index: usize, column: Column },
+
+    /// A column exists in the current schema but not the desired one
+    Removed { index: usize, column: Column },
+
+    /// The column at this position has a new name
+    Renamed {
+        index: usize,
+        from: Option<String>,
+        to: Option<String>,
+    },
+
+    /// The column at this position has a new type
+    Retyped {
+        index: usize,
+        name: Option<String>,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+impl std::fmt::Display for ColumnChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnChange::Added { index, column } => write!(
+                f,
+                "+ [{}] {} ({})",
+                index,
+                column.name.as_deref().unwrap_or(""),
+                column.column_type.as_deref().unwrap_or("")
+            ),
+            ColumnChange::Removed { index, column } => write!(
+                f,
+                "- [{}] {} ({})",
+                index,
+                column.name.as_deref().unwrap_or(""),
+                column.column_type.as_deref().unwrap_or("")
+            ),
+            ColumnChange::Renamed { index, from, to } => write!(
+                f,
+                "~ [{}] renamed {} -> {}",
+                index,
+                from.as_deref().unwrap_or(""),
+                to.as_deref().unwrap_or("")
+            ),
+            ColumnChange::Retyped {
+                index,
+                name,
+                from,
+                to,
+            } => write!(
+                f,
+                "~ [{}] {} retyped {} -> {}",
+                index,
+                name.as_deref().unwrap_or(""),
+                from.as_deref().unwrap_or(""),
+                to.as_deref().unwrap_or("")
+            ),
+        }
+    }
+}
+
+/// The set of changes needed to turn one schema into another
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct SchemaDiff {
+    pub changes: Vec<ColumnChange>,
+}
+
+impl SchemaDiff {
+    /// True when the schemas are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// True when any column would be removed, which drops its data
+    pub fn has_removals(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|c| matches!(c, ColumnChange::Removed { .. }))
+    }
+}
+
+impl std::fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "No schema changes");
+        }
+        let lines: Vec<String> = self.changes.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Compares two schemas column by column and returns what would change going from current to desired
+pub fn diff_schema(current: &Schema, desired: &Schema) -> SchemaDiff {
+    let empty = Vec::new();
+    let cur = current.columns.as_ref().unwrap_or(&empty);
+    let des = desired.columns.as_ref().unwrap_or(&empty);
+    let mut changes = Vec::new();
+    for index in 0..cur.len().max(des.len()) {
+        match (cur.get(index), des.get(index)) {
+            (Some(c), Some(d)) => {
+                if c.name != d.name {
+                    changes.push(ColumnChange::Renamed {
+                        index,
+                        from: c.name.clone(),
+                        to: d.name.clone(),
+                    });
+                }
+                if c.column_type != d.column_type {
+                    changes.push(ColumnChange::Retyped {
+                        index,
+                        name: d.name.clone(),
+                        from: c.column_type.clone(),
+                        to: d.column_type.clone(),
+                    });
+                }
+            }
+            (None, Some(d)) => changes.push(ColumnChange::Added {
+                index,
+                column: d.clone(),
+            }),
+            (Some(c), None) => changes.push(ColumnChange::Removed {
+                index,
+                column: c.clone(),
+            }),
+            (None, None) => {}
+        }
+    }
+    SchemaDiff { changes }
+}
+
 /// A PDP Policy definition for a dataset
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default)]
@@ -140,13 +282,13 @@ pub struct Policy {
     pub filters: Option<Vec<Filter>>,
 
     /// List of user IDs the policy applies to
-    pub users: Option<Vec<u64>>,
+    pub users: Option<Vec<UserId>>,
 
     /// List of virtual Ids the policy applies to
     pub virtual_users: Option<Vec<String>>,
 
     /// List of group IDs the policy applies to
-    pub groups: Option<Vec<String>>,
+    pub groups: Option<Vec<GroupId>>,
 }
 
 impl Policy {
@@ -173,9 +315,9 @@ impl Policy {
                 operator: Some(String::from("EQUALS")),
                 values: vec![String::from("values in this column that match will apply")],
             }]),
-            users: Some(vec![27]),
+            users: Some(vec![UserId(27)]),
             virtual_users: Some(vec![String::from("vu:324ds")]),
-            groups: Some(vec![String::from("15")]),
+            groups: Some(vec![GroupId(15)]),
         }
     }
 }
@@ -249,6 +391,57 @@ pub struct QueryMetadata {
     pub aggregated: Option<bool>,
 }
 
+/// Tracks the progress of a resumable export so an interrupted run can pick up where it left off
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExportCheckpoint {
+    /// The dataset being exported
+    pub dataset_id: DataSetId,
+
+    /// The number of rows requested per part
+    pub chunk_rows: u32,
+
+    /// The row offset of the next part to fetch
+    pub next_offset: u64,
+
+    /// File names of the parts written so far, in order
+    pub parts: Vec<String>,
+
+    /// Whether every row has been exported
+    pub complete: bool,
+}
+
+/// Everything the public API can show referencing a DataSet, useful to review before deleting it.
+/// Cards and the pages showing them are not included, so an empty report does not mean the DataSet is unused.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSetUsage {
+    /// The DataSet itself
+    pub dataset: DataSet,
+
+    /// Streams that import into this DataSet
+    pub streams: Vec<Stream>,
+
+    /// PDP policies restricting this DataSet
+    pub policies: Vec<Policy>,
+
+    /// Whether cards and pages built on this DataSet were looked for. Always false,
+    /// as the public API does not say which DataSet a card uses.
+    pub pages_checked: bool,
+}
+
+/// The file written alongside the exported parts to record progress
+pub const EXPORT_CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// A json value as a csv or table cell: strings without quotes, null as empty, anything else as json
+pub fn value_cell(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
 /// DataSet API methods
 /// Uses the form method_object
 impl super::Client {
@@ -281,6 +474,17 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns every dataset, paging through get_datasets
+    pub async fn get_datasets_all(
+        &self,
+    ) -> Result<Vec<DataSet>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(50, |limit, offset| {
+            self.get_datasets(Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
     /// Creates a new DataSet in your Domo instance. Once the DataSet has been created, data can then be imported into the DataSet.
     pub async fn post_dataset(
         &self,
@@ -301,7 +505,7 @@ impl super::Client {
     /// Retrieves the details of an existing DataSet.
     pub async fn get_dataset(
         &self,
-        id: &str,
+        id: &DataSetId,
     ) -> Result<DataSet, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         let mut response = surf::get(&format!("{}{}{}", self.host, "/v1/datasets/", id))
@@ -317,7 +521,7 @@ impl super::Client {
     /// Updates the specified DataSet’s metadata by providing values to parameters passed.
     pub async fn put_dataset(
         &self,
-        id: &str,
+        id: &DataSetId,
         ds: DataSet,
     ) -> Result<DataSet, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -332,12 +536,111 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Updates only the name and/or description of a DataSet.
+    ///
+    /// Unlike put_dataset, fields left as None are not sent at all, so the schema and any other metadata are left untouched.
+    pub async fn update_dataset_metadata(
+        &self,
+        id: &DataSetId,
+        name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<DataSet, Box<dyn Error + Send + Sync + 'static>> {
+        let mut payload = Map::new();
+        if let Some(name) = name {
+            payload.insert(String::from("name"), json!(name));
+        }
+        if let Some(description) = description {
+            payload.insert(String::from("description"), json!(description));
+        }
+        let at = self.get_access_token("data").await?;
+        let mut response = surf::put(format!("{}{}{}", self.host, "/v1/datasets/", id))
+            .header("Authorization", at)
+            .body(surf::Body::from_json(&Value::Object(payload))?)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Transfers ownership of a DataSet to another user without touching any other field
+    pub async fn set_dataset_owner(
+        &self,
+        id: &DataSetId,
+        owner_id: UserId,
+    ) -> Result<DataSet, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("data").await?;
+        let mut response = surf::put(format!("{}{}{}", self.host, "/v1/datasets/", id))
+            .header("Authorization", at)
+            .body(surf::Body::from_json(
+                &json!({ "owner": { "id": owner_id } }),
+            )?)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Polls a DataSet until its data_current_at (or updated_at when the former is missing) is later than since.
+    ///
+    /// Useful after committing a stream execution to know when the new data is actually live.
+    /// Returns the refreshed DataSet, or a TimeoutError if it has not updated within the timeout.
+    pub async fn wait_for_dataset_update(
+        &self,
+        id: &DataSetId,
+        since: DateTime<Utc>,
+        timeout: Duration,
+    ) -> Result<DataSet, Box<dyn Error + Send + Sync + 'static>> {
+        super::poll_until(
+            timeout,
+            &format!("waiting for dataset {} to update", id),
+            || async {
+                let ds = self.get_dataset(id).await?;
+                match ds.data_current_at.or(ds.updated_at) {
+                    Some(t) if t > since => Ok(Some(ds)),
+                    _ => Ok(None),
+                }
+            },
+        )
+        .await
+    }
+
+    /// Updates the schema of a DataSet to match desired, but only if it actually differs from the current schema.
+    ///
+    /// The current DataSet is re-fetched and sent back through put_dataset with just the schema replaced.
+    /// Removing columns drops their data, so a diff that removes any is refused unless allow_removals is set.
+    /// Returns the differences that were applied, which will be empty if nothing needed to change.
+    pub async fn apply_schema(
+        &self,
+        id: &DataSetId,
+        desired: Schema,
+        allow_removals: bool,
+    ) -> Result<SchemaDiff, Box<dyn Error + Send + Sync + 'static>> {
+        let mut ds = self.get_dataset(id).await?;
+        let diff = diff_schema(&ds.schema.take().unwrap_or_default(), &desired);
+        if diff.has_removals() && !allow_removals {
+            return Err(format!(
+                "the schema change removes columns, which drops their data:\n{}",
+                diff
+            )
+            .into());
+        }
+        if !diff.is_empty() {
+            ds.schema = Some(desired);
+            self.put_dataset(id, ds).await?;
+        }
+        Ok(diff)
+    }
+
     /// Permanently deletes a DataSet from your Domo instance. This can be done for all DataSets, not just those created through the API.
     ///
     /// This is destructive and cannot be reversed.
     pub async fn delete_dataset(
         &self,
-        id: &str,
+        id: &DataSetId,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         let mut response = surf::delete(&format!("{}{}{}", self.host, "/v1/datasets/", id))
@@ -350,6 +653,21 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Gathers the streams and PDP policies attached to a DataSet into a single report.
+    ///
+    /// The public API does not expose which cards are built on a DataSet, so pages that display it cannot be included.
+    pub async fn get_dataset_usage(
+        &self,
+        id: &DataSetId,
+    ) -> Result<DataSetUsage, Box<dyn Error + Send + Sync + 'static>> {
+        Ok(DataSetUsage {
+            dataset: self.get_dataset(id).await?,
+            streams: self.get_stream_search_dataset_id(id).await?,
+            policies: self.get_dataset_policies(id).await?,
+            pages_checked: false,
+        })
+    }
+
     /// Export data from a DataSet in your Domo instance.
     ///
     /// Data types will be exported as they are currently stored in the dataset. In addition, the only supported export type is CSV.
@@ -357,7 +675,7 @@ impl super::Client {
     /// TODO Parameters includeHeader and fileName
     pub async fn get_dataset_data(
         &self,
-        id: &str,
+        id: &DataSetId,
     ) -> Result<String, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         #[derive(Serialize)]
@@ -382,6 +700,120 @@ impl super::Client {
         Ok(response.body_string().await?)
     }
 
+    /// Export a large DataSet in windows of chunk_rows using repeated SQL queries, writing each window to its own csv part in dir.
+    ///
+    /// Progress is recorded in dir/checkpoint.json after every part, so calling this again with the same dir resumes from the last completed part.
+    /// Only the first part includes the header row, so the parts can be concatenated in order to produce the full export.
+    /// Rows are paged with LIMIT/OFFSET ordered by every column, so the dataset should not change while an export is in progress.
+    /// A resumed export must use the chunk_rows it was started with.
+    pub async fn export_dataset_resumable(
+        &self,
+        id: &DataSetId,
+        dir: impl AsRef<Path>,
+        chunk_rows: u32,
+    ) -> Result<ExportCheckpoint, Box<dyn Error + Send + Sync + 'static>> {
+        if chunk_rows == 0 {
+            return Err("chunk_rows must be at least 1".into());
+        }
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let checkpoint_path = dir.join(EXPORT_CHECKPOINT_FILE);
+        let mut checkpoint = if checkpoint_path.exists() {
+            let cp: ExportCheckpoint =
+                serde_json::from_str(&fs::read_to_string(&checkpoint_path)?)?;
+            if cp.dataset_id != *id {
+                return Err(format!(
+                    "checkpoint in {} belongs to dataset {}",
+                    dir.display(),
+                    cp.dataset_id
+                )
+                .into());
+            }
+            if cp.chunk_rows != chunk_rows {
+                return Err(format!(
+                    "checkpoint in {} was started with chunk_rows {}, not {}",
+                    dir.display(),
+                    cp.chunk_rows,
+                    chunk_rows
+                )
+                .into());
+            }
+            cp
+        } else {
+            ExportCheckpoint {
+                dataset_id: id.clone(),
+                chunk_rows,
+                ..Default::default()
+            }
+        };
+
+        //Without an order the windows may overlap or skip rows, so sort by every column
+        let order_by = self
+            .get_dataset(id)
+            .await?
+            .schema
+            .and_then(|s| s.columns)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|c| c.name)
+            .map(|name| format!("`{}`", name.replace('`', "``")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if order_by.is_empty() {
+            return Err(format!("dataset {} has no columns to order the export by", id).into());
+        }
+
+        while !checkpoint.complete {
+            let sql = format!(
+                "SELECT * FROM table ORDER BY {} LIMIT {} OFFSET {}",
+                order_by, checkpoint.chunk_rows, checkpoint.next_offset
+            );
+            let r = self.post_dataset_query(id, &sql).await?;
+            let rows = r.rows.unwrap_or_default();
+
+            let name = format!("part-{:05}.csv", checkpoint.parts.len());
+            let mut w = csv::Writer::from_path(dir.join(&name))?;
+            if checkpoint.parts.is_empty() {
+                w.write_record(r.columns.unwrap_or_default())?;
+            }
+            for row in &rows {
+                w.write_record(row.iter().map(value_cell))?;
+            }
+            w.flush()?;
+
+            checkpoint.parts.push(name);
+            checkpoint.next_offset += rows.len() as u64;
+            checkpoint.complete = (rows.len() as u64) < u64::from(checkpoint.chunk_rows);
+
+            write_atomic(&checkpoint_path, serde_json::to_string_pretty(&checkpoint)?)?;
+        }
+        Ok(checkpoint)
+    }
+
+    /// Export data from a DataSet, reporting download progress as the csv is received.
+    pub async fn get_dataset_data_with_progress(
+        &self,
+        id: &DataSetId,
+        progress: Progress,
+    ) -> Result<String, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("data").await?;
+        let mut response = surf::get(format!("{}{}{}{}", self.host, "/v1/datasets/", id, "/data"))
+            .query(&json!({ "includeHeader": true }))?
+            .header("Authorization", at)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        let body = response.take_body();
+        let total = body.len().map(|l| l as u64);
+        let mut ret = String::new();
+        ProgressReader::new(body, total, progress)
+            .read_to_string(&mut ret)
+            .await?;
+        Ok(ret)
+    }
+
     /// Import data into a DataSet in your Domo instance. This request will replace the data currently in the DataSet.
     ///
     /// The only supported content type is currently CSV format.
@@ -389,7 +821,7 @@ impl super::Client {
     /// To upload data in CSV format, the Domo specification used for representing data grids in CSV format closely follows the RFC standard for CSV (RFC-4180).
     pub async fn put_dataset_data(
         &self,
-        id: &str,
+        id: &DataSetId,
         csv: impl AsRef<Path>,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -398,7 +830,6 @@ impl super::Client {
             self.host, "/v1/datasets/", id, "/data"
         ))
         .header("Authorization", at)
-        //TODO Have the csv data passed in as an async_std::io::Read. <- Should just need to change the below to Body::from_reader
         .body(surf::Body::from_file(csv).await?)
         .header("Content-Type", "text/csv")
         .await?;
@@ -409,10 +840,92 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Import a csv file into a DataSet, reporting upload progress as the file is sent. This request will replace the data currently in the DataSet.
+    pub async fn put_dataset_data_with_progress(
+        &self,
+        id: &DataSetId,
+        csv: impl AsRef<Path>,
+        progress: Progress,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let file = async_std::fs::File::open(csv.as_ref()).await?;
+        let len = file.metadata().await?.len();
+        let reader = ProgressReader::new(BufReader::new(file), Some(len), progress);
+        let at = self.get_access_token("data").await?;
+        let mut response = surf::put(format!("{}{}{}{}", self.host, "/v1/datasets/", id, "/data"))
+            .header("Authorization", at)
+            .body(surf::Body::from_reader(reader, Some(len as usize)))
+            .header("Content-Type", "text/csv")
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Append the rows of a csv file to a DataSet without going through the Stream API.
+    ///
+    /// The DataSet API only supports replacing data, so the existing rows are downloaded, the new rows are added to the end, and the result is imported.
+    /// Like put_dataset_data, the csv file should not contain a header row.
+    /// This is meant for small incremental loads; large or frequently updated DataSets should use a Stream with the APPEND update method.
+    pub async fn append_dataset_data(
+        &self,
+        id: &DataSetId,
+        csv: impl AsRef<Path>,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("data").await?;
+        let url = format!("{}{}{}{}", self.host, "/v1/datasets/", id, "/data");
+        let mut response = surf::get(&url)
+            .query(&json!({ "includeHeader": false }))?
+            .header("Authorization", at.as_str())
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        let mut data = response.body_string().await?;
+        if !data.is_empty() && !data.ends_with('\n') {
+            data.push('\n');
+        }
+        data.push_str(&async_std::fs::read_to_string(csv.as_ref()).await?);
+
+        let mut response = surf::put(&url)
+            .header("Authorization", at)
+            .body(surf::Body::from_string(data))
+            .header("Content-Type", "text/csv")
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Import csv data read from any async reader (such as stdin) into a DataSet. This request will replace the data currently in the DataSet.
+    ///
+    /// The data is streamed to Domo as it is read, so it never needs to be written to a temporary file.
+    pub async fn put_dataset_data_reader(
+        &self,
+        id: &DataSetId,
+        csv: impl AsyncBufRead + Unpin + Send + Sync + 'static,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("data").await?;
+        let mut response = surf::put(format!("{}{}{}{}", self.host, "/v1/datasets/", id, "/data"))
+            .header("Authorization", at)
+            .body(surf::Body::from_reader(csv, None))
+            .header("Content-Type", "text/csv")
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
     /// Returns data from the DataSet based on your SQL query.
     pub async fn post_dataset_query(
         &self,
-        id: &str,
+        id: &DataSetId,
         query: &str,
     ) -> Result<QueryResult, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -433,7 +946,7 @@ impl super::Client {
     /// List the Personalized Data Permission (PDP) policies for a specified DataSet.
     pub async fn get_dataset_policies(
         &self,
-        id: &str,
+        id: &DataSetId,
     ) -> Result<Vec<Policy>, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         let mut response = surf::get(&format!(
@@ -453,7 +966,7 @@ impl super::Client {
     /// Users and groups must exist before creating PDP policy.
     pub async fn post_dataset_policy(
         &self,
-        id: &str,
+        id: &DataSetId,
         policy: Policy,
     ) -> Result<Policy, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -475,7 +988,7 @@ impl super::Client {
     /// A DataSet is required for a PDP policy to exist.
     pub async fn get_dataset_policy(
         &self,
-        id: &str,
+        id: &DataSetId,
         policy_id: u32,
     ) -> Result<Policy, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -495,7 +1008,7 @@ impl super::Client {
     /// Update the specific PDP policy for a DataSet by providing values to parameters passed.
     pub async fn put_dataset_policy(
         &self,
-        id: &str,
+        id: &DataSetId,
         policy_id: u32,
         policy: Policy,
     ) -> Result<Policy, Box<dyn Error + Send + Sync + 'static>> {
@@ -519,7 +1032,7 @@ impl super::Client {
     /// This is destructive and cannot be reversed.
     pub async fn delete_dataset_policy(
         &self,
-        id: &str,
+        id: &DataSetId,
         policy_id: u32,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
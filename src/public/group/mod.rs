@@ -2,15 +2,22 @@ use std::error::Error;
 
 use serde::{Deserialize, Serialize};
 
+use super::{bulk, GroupId, UserId};
+
+/// How many membership changes the bulk group methods send at once
+const GROUP_USER_CONCURRENCY: usize = 8;
+
 /// Group objects allow you to manage a group and users associated to a group.
 /// Groups allow you to set access rights, send Buzz messages, or share content that stays consistent even when the group members may change.
 /// The API allows you to create, delete, retrieve a user or a list of users, and update user information.
-#[derive(Serialize, Deserialize, Debug, Default)]
+///
+/// Only name, active, and default can be written; post_group and put_group leave the other fields out of the request.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Group {
     /// The ID of the group
     #[serde(skip_serializing_if = "Option::is_none")]
-    id: Option<u64>,
+    id: Option<GroupId>,
 
     /// The name of the group
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -25,16 +32,54 @@ pub struct Group {
     active: Option<bool>,
 
     /// The ID of the user who created the group
-    /// TODO This should be a u64 reference for consistency
     #[serde(skip_serializing_if = "Option::is_none")]
-    creator_id: Option<String>,
+    creator_id: Option<UserId>,
 
     /// The member count of the group
     #[serde(skip_serializing_if = "Option::is_none")]
     member_count: Option<u32>,
+
+    /// True if the group has been deleted, only returned when retrieving a deleted group
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted: Option<bool>,
 }
 
 impl Group {
+    /// The ID of the group
+    pub fn id(&self) -> Option<GroupId> {
+        self.id
+    }
+
+    /// The name of the group
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Whether the group is the default group
+    pub fn is_default(&self) -> Option<bool> {
+        self.default
+    }
+
+    /// The active status of the group
+    pub fn active(&self) -> Option<bool> {
+        self.active
+    }
+
+    /// The ID of the user who created the group
+    pub fn creator_id(&self) -> Option<UserId> {
+        self.creator_id
+    }
+
+    /// The member count of the group
+    pub fn member_count(&self) -> Option<u32> {
+        self.member_count
+    }
+
+    /// True if the group has been deleted
+    pub fn deleted(&self) -> Option<bool> {
+        self.deleted
+    }
+
     pub fn new() -> Self {
         Group {
             id: None,
@@ -43,21 +88,67 @@ impl Group {
             active: None,
             creator_id: None,
             member_count: None,
+            deleted: None,
+        }
+    }
+
+    /// Starts a group to create or update, e.g. Group::builder().name("Finance").active(true).build()
+    pub fn builder() -> GroupBuilder {
+        GroupBuilder {
+            group: Group::new(),
         }
     }
 
     pub fn template() -> Self {
         Group {
-            id: Some(0),
+            id: Some(GroupId(0)),
             name: Some(String::from("Group Name")),
             default: Some(false),
             active: Some(true),
-            creator_id: Some(String::from("0")),
+            creator_id: Some(UserId(0)),
             member_count: Some(0),
+            deleted: None,
+        }
+    }
+
+    /// The fields the api accepts on create and update
+    fn writable(&self) -> Group {
+        Group {
+            name: self.name.clone(),
+            default: self.default,
+            active: self.active,
+            ..Group::new()
         }
     }
 }
 
+/// Builds a Group with the writable fields set
+#[derive(Debug, Clone)]
+pub struct GroupBuilder {
+    group: Group,
+}
+
+impl GroupBuilder {
+    pub fn name(mut self, name: &str) -> Self {
+        self.group.name = Some(String::from(name));
+        self
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.group.active = Some(active);
+        self
+    }
+
+    pub fn is_default(mut self, default: bool) -> Self {
+        self.group.default = Some(default);
+        self
+    }
+
+    pub fn build(self) -> Group {
+        self.group
+    }
+}
+
 /// Group API methods
 /// Uses the form method_object
 impl super::Client {
@@ -86,6 +177,17 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns every group, paging through get_groups
+    pub async fn get_groups_all(
+        &self,
+    ) -> Result<Vec<Group>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(500, |limit, offset| {
+            self.get_groups(Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
     /// Creates a new group in your Domo instance.
     /// Returns a group object when successful.
     /// The returned group will have user attributes based on the information that was provided when group was created.
@@ -96,7 +198,7 @@ impl super::Client {
         let at = self.get_access_token("user").await?;
         let mut response = surf::post(&format!("{}{}", self.host, "/v1/groups"))
             .header("Authorization", at)
-            .body(surf::Body::from_json(&group)?)
+            .body(surf::Body::from_json(&group.writable())?)
             .await?;
         if !response.status().is_success() {
             let e: Box<super::PubAPIError> = response.body_json().await?;
@@ -110,7 +212,7 @@ impl super::Client {
     /// When requesting, if the group ID is related to a customer that has been deleted, a subset of the group's information will be returned, including a deleted property, which will be true.
     pub async fn get_group(
         &self,
-        id: &str,
+        id: GroupId,
     ) -> Result<Group, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
         let mut response = surf::get(&format!("{}{}{}", self.host, "/v1/groups/", id))
@@ -128,13 +230,13 @@ impl super::Client {
     /// Returns the parameter of success or error based on the group ID being valid.
     pub async fn put_group(
         &self,
-        id: &str,
+        id: GroupId,
         group: Group,
     ) -> Result<Group, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
         let mut response = surf::put(&format!("{}{}{}", self.host, "/v1/groups/", id))
             .header("Authorization", at)
-            .body(surf::Body::from_json(&group)?)
+            .body(surf::Body::from_json(&group.writable())?)
             .await?;
         if !response.status().is_success() {
             let e: Box<super::PubAPIError> = response.body_json().await?;
@@ -147,7 +249,7 @@ impl super::Client {
     /// This is destructive and cannot be reversed.
     pub async fn delete_group(
         &self,
-        id: &str,
+        id: GroupId,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
         let mut response = surf::delete(&format!("{}{}{}", self.host, "/v1/groups/", id))
@@ -162,12 +264,24 @@ impl super::Client {
 
     /// List the users in a group in your Domo instance.
     /// Returns IDs of users that are a part of the requested group.
+    ///
+    /// Limit: The amount of users to return in the list. The default is 50 and the maximum is 500
+    /// Offset: The offset of the user ID to begin list of users within the response
     pub async fn get_group_users(
         &self,
-        id: &str,
-    ) -> Result<Vec<u64>, Box<dyn Error + Send + Sync + 'static>> {
+        id: GroupId,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<UserId>, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
-        let mut response = surf::get(&format!("{}{}{}{}", self.host, "/v1/groups/", id, "/users"))
+        #[derive(Serialize)]
+        struct ListParams {
+            pub limit: Option<u32>,
+            pub offset: Option<u32>,
+        }
+        let q = ListParams { limit, offset };
+        let mut response = surf::get(format!("{}{}{}{}", self.host, "/v1/groups/", id, "/users"))
+            .query(&q)?
             .header("Authorization", at)
             .await?;
         if !response.status().is_success() {
@@ -177,11 +291,23 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns the IDs of every user in a group, paging through get_group_users
+    pub async fn get_group_users_all(
+        &self,
+        id: GroupId,
+    ) -> Result<Vec<UserId>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(500, |limit, offset| {
+            self.get_group_users(id, Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
     /// Add user to a group in your Domo instance.
     pub async fn put_group_user(
         &self,
-        group_id: &str,
-        user_id: &str,
+        group_id: GroupId,
+        user_id: UserId,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
         let mut response = surf::put(&format!(
@@ -200,8 +326,8 @@ impl super::Client {
     /// Remove a user from a group in your Domo instance.
     pub async fn delete_group_user(
         &self,
-        group_id: &str,
-        user_id: &str,
+        group_id: GroupId,
+        user_id: UserId,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
         let mut response = surf::delete(&format!(
@@ -216,4 +342,64 @@ impl super::Client {
         }
         Ok(response.body_json().await?)
     }
+
+    /// Returns the groups a user is a member of.
+    /// The API has no direct lookup, so this checks the members of every group.
+    pub async fn get_user_groups(
+        &self,
+        user_id: UserId,
+    ) -> Result<Vec<Group>, Box<dyn Error + Send + Sync + 'static>> {
+        let groups = self.get_groups_all().await?;
+        let mut r = Vec::new();
+        for g in groups {
+            if let Some(id) = g.id {
+                if self.get_group_users_all(id).await?.contains(&user_id) {
+                    r.push(g);
+                }
+            }
+        }
+        Ok(r)
+    }
+
+    /// Adds many users to a group, several at a time.
+    /// Every user is attempted; if any fail a BulkError lists them.
+    pub async fn add_group_users(
+        &self,
+        group_id: GroupId,
+        user_ids: &[UserId],
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        self.change_group_users(group_id, user_ids, true).await
+    }
+
+    /// Removes many users from a group, several at a time.
+    /// Every user is attempted; if any fail a BulkError lists them.
+    pub async fn remove_group_users(
+        &self,
+        group_id: GroupId,
+        user_ids: &[UserId],
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        self.change_group_users(group_id, user_ids, false).await
+    }
+
+    async fn change_group_users(
+        &self,
+        group_id: GroupId,
+        user_ids: &[UserId],
+        add: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let action = format!("membership changes for group {}", group_id);
+        bulk(
+            user_ids,
+            GROUP_USER_CONCURRENCY,
+            &action,
+            |user_id| async move {
+                if add {
+                    self.put_group_user(group_id, user_id).await
+                } else {
+                    self.delete_group_user(group_id, user_id).await
+                }
+            },
+        )
+        .await
+    }
 }
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// Some endpoints send numeric ids as strings, so both forms are accepted
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(u64),
+    String(String),
+}
+
+fn deserialize_numeric_id<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+    match NumberOrString::deserialize(d)? {
+        NumberOrString::Number(n) => Ok(n),
+        NumberOrString::String(s) => s.trim().parse().map_err(de::Error::custom),
+    }
+}
+
+/// Declares a numeric id newtype that serializes as the bare number
+macro_rules! numeric_id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(
+            Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord,
+        )]
+        #[serde(transparent)]
+        pub struct $name(#[serde(deserialize_with = "deserialize_numeric_id")] pub u64);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok($name(s.trim().parse()?))
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                $name(id)
+            }
+        }
+    };
+}
+
+numeric_id!(
+    /// The id of a Domo user
+    UserId
+);
+numeric_id!(
+    /// The id of a Domo group
+    GroupId
+);
+numeric_id!(
+    /// The id of a page
+    PageId
+);
+numeric_id!(
+    /// The id of a stream
+    StreamId
+);
+numeric_id!(
+    /// The id of a card
+    CardId
+);
+
+/// The id of a DataSet, a uuid
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct DataSetId(pub String);
+
+impl fmt::Display for DataSetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for DataSetId {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(DataSetId(String::from(s.trim())))
+    }
+}
+
+impl From<&str> for DataSetId {
+    fn from(id: &str) -> Self {
+        DataSetId(String::from(id))
+    }
+}
+
+impl From<String> for DataSetId {
+    fn from(id: String) -> Self {
+        DataSetId(id)
+    }
+}
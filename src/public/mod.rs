@@ -1,14 +1,81 @@
+/// Declares a string enum with an Other fallback. Values from the api are kept as sent, while parsing
+/// with FromStr ignores case and surrounding space and takes `-` as `_`.
+macro_rules! string_enum {
+    ($(#[$doc:meta])* $name:ident { $($(#[$variant_doc:meta])* $variant:ident => $value:literal,)* }) => {
+        $(#[$doc])*
+        #[derive(
+            serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+        )]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $($(#[$variant_doc])* $variant,)*
+            /// Any value this sdk does not know about
+            Other(String),
+        }
+
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                match s.as_str() {
+                    $($value => $name::$variant,)*
+                    _ => $name::Other(s),
+                }
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(v: $name) -> Self {
+                match v {
+                    $($name::$variant => String::from($value),)*
+                    $name::Other(s) => s,
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", String::from(self.clone()))
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok($name::from(s.trim().to_uppercase().replace('-', "_")))
+            }
+        }
+    };
+}
+
 pub mod account;
 pub mod activity;
+pub mod audit;
 pub mod buzz;
+pub mod card;
 pub mod dataset;
 pub mod group;
+pub mod ids;
 pub mod page;
+pub mod property;
 pub mod stream;
 pub mod user;
 pub mod workflow;
 
+pub use ids::{CardId, DataSetId, GroupId, PageId, StreamId, UserId};
+pub use property::PropertyValue;
+
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::hash::Hash;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use async_std::io::{BufRead as AsyncBufRead, Read as AsyncRead};
+use futures::stream::StreamExt;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -37,12 +104,84 @@ impl std::fmt::Display for PubAPIError {
     }
 }
 
+/// Returned by the polling helpers when the awaited condition is not met before the timeout
+#[derive(Debug)]
+pub struct TimeoutError {
+    pub message: String,
+}
+
+impl std::error::Error for TimeoutError {}
+impl std::fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timed out: {}", self.message)
+    }
+}
+
+/// Progress callback used by the transfer methods.
+/// Called with the number of bytes transferred so far and, when it is known, the total number of bytes.
+pub type Progress = Arc<dyn Fn(u64, Option<u64>) + Send + Sync>;
+
+/// Wraps a reader and reports every byte read through it to a Progress callback
+pub struct ProgressReader<R> {
+    inner: R,
+    done: u64,
+    total: Option<u64>,
+    progress: Progress,
+}
+
+impl<R> ProgressReader<R> {
+    pub fn new(inner: R, total: Option<u64>, progress: Progress) -> Self {
+        Self {
+            inner,
+            done: 0,
+            total,
+            progress,
+        }
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.done += n as u64;
+        (self.progress)(self.done, self.total);
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ProgressReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let r = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            this.advance(n);
+        }
+        r
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncBufRead for ProgressReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<&[u8]>> {
+        Pin::new(&mut self.get_mut().inner).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        Pin::new(&mut this.inner).consume(amt);
+        this.advance(amt);
+    }
+}
+
 /// The public API client.
 /// All methods will be attached to this struct
 pub struct Client {
     host: String,
     client_id: String,
     client_secret: String,
+    /// When each stream was last committed through this client, used to respect the stream commit interval
+    stream_commits: Mutex<HashMap<StreamId, Instant>>,
+    /// Spaces activity log requests, shared by every concurrent fetch
+    rate_limit: Option<RateLimiter>,
 }
 
 /// Client initialization and helper methods
@@ -53,6 +192,22 @@ impl Client {
             host: String::from(host),
             client_id: String::from(client_id),
             client_secret: String::from(client_secret),
+            stream_commits: Mutex::new(HashMap::new()),
+            rate_limit: None,
+        }
+    }
+
+    /// The most activity log requests per second to send, shared by every concurrent fetch through this client.
+    /// A rate under one request an hour, or one that is not a positive number, allows one request an hour.
+    pub fn rate_limit(mut self, per_second: f64) -> Self {
+        self.rate_limit = Some(RateLimiter::per_second(per_second));
+        self
+    }
+
+    /// Sleeps until the rate limit, if any, allows another request
+    async fn wait_turn(&self) {
+        if let Some(limit) = &self.rate_limit {
+            limit.wait(()).await;
         }
     }
 
@@ -87,3 +242,177 @@ struct TokenQuery<'a> {
     pub grant_type: &'a str,
     pub scope: &'a str,
 }
+
+/// Walks a limit/offset paginated list endpoint one page at a time.
+///
+/// fetch is called with the page size and the offset of the next page. Paging stops at the first page shorter than the page size.
+/// A page size of 0 is taken as 1, as it could never be shorter.
+pub struct Paginator<F> {
+    fetch: F,
+    page_size: u32,
+    offset: u32,
+    done: bool,
+}
+
+impl<F> Paginator<F> {
+    pub fn new(page_size: u32, fetch: F) -> Self {
+        Paginator {
+            fetch,
+            page_size: page_size.max(1),
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Fetches the next page, or returns None once every page has been read
+    pub async fn next_page<T, Fut>(
+        &mut self,
+    ) -> Result<Option<Vec<T>>, Box<dyn Error + Send + Sync + 'static>>
+    where
+        F: FnMut(u32, u32) -> Fut,
+        Fut: Future<Output = Result<Vec<T>, Box<dyn Error + Send + Sync + 'static>>>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        let page = (self.fetch)(self.page_size, self.offset).await?;
+        self.offset += page.len() as u32;
+        self.done = (page.len() as u32) < self.page_size;
+        Ok(Some(page))
+    }
+
+    /// Reads every remaining page and returns all of the items
+    pub async fn all<T, Fut>(mut self) -> Result<Vec<T>, Box<dyn Error + Send + Sync + 'static>>
+    where
+        F: FnMut(u32, u32) -> Fut,
+        Fut: Future<Output = Result<Vec<T>, Box<dyn Error + Send + Sync + 'static>>>,
+    {
+        let mut r = Vec::new();
+        while let Some(mut page) = self.next_page().await? {
+            r.append(&mut page);
+        }
+        Ok(r)
+    }
+}
+
+/// Repeatedly calls check until it yields a value, sleeping with exponential backoff between attempts.
+/// The delay starts at one second and doubles up to thirty seconds, never sleeping past the timeout.
+pub(crate) async fn poll_until<T, F, Fut>(
+    timeout: Duration,
+    what: &str,
+    mut check: F,
+) -> Result<T, Box<dyn Error + Send + Sync + 'static>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>, Box<dyn Error + Send + Sync + 'static>>>,
+{
+    let deadline = Instant::now() + timeout;
+    let mut delay = Duration::from_secs(1);
+    loop {
+        if let Some(r) = check().await? {
+            return Ok(r);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(Box::new(TimeoutError {
+                message: format!("{} after {:?}", what, timeout),
+            }));
+        }
+        async_std::task::sleep(delay.min(deadline - now)).await;
+        delay = (delay * 2).min(Duration::from_secs(30));
+    }
+}
+
+/// Writes a file through a temp file and a rename, so a crash never leaves it half written
+pub(crate) fn write_atomic(path: &Path, bytes: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Returned when part of a bulk operation failed. Every id not listed succeeded.
+#[derive(Debug)]
+pub struct BulkError<Id> {
+    /// What was being done, e.g. "user deletes"
+    pub action: String,
+    /// The ids that failed and why
+    pub failures: Vec<(Id, String)>,
+}
+
+impl<Id: fmt::Debug + fmt::Display> std::error::Error for BulkError<Id> {}
+impl<Id: fmt::Display> fmt::Display for BulkError<Id> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} failed", self.failures.len(), self.action)?;
+        for (id, e) in &self.failures {
+            write!(f, "\n  {}: {}", id, e)?;
+        }
+        Ok(())
+    }
+}
+
+/// Calls f for every id, at most concurrency at a time, attempting them all.
+/// If any fail, a BulkError lists them in id order.
+pub(crate) async fn bulk<Id, F, Fut>(
+    ids: &[Id],
+    concurrency: usize,
+    action: &str,
+    f: F,
+) -> Result<(), Box<dyn Error + Send + Sync + 'static>>
+where
+    Id: Copy + Ord + fmt::Debug + fmt::Display + Send + Sync + 'static,
+    F: Fn(Id) -> Fut,
+    Fut: Future<Output = Result<(), Box<dyn Error + Send + Sync + 'static>>>,
+{
+    let f = &f;
+    let mut failures: Vec<(Id, String)> = futures::stream::iter(ids.iter().copied())
+        .map(|id| async move { f(id).await.err().map(|e| (id, e.to_string())) })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|r| async move { r })
+        .collect()
+        .await;
+    if failures.is_empty() {
+        return Ok(());
+    }
+    failures.sort_by_key(|(id, _)| *id);
+    Err(Box::new(BulkError {
+        action: String::from(action),
+        failures,
+    }))
+}
+
+/// Spaces requests at least an interval apart, separately for each key.
+/// A slot is reserved before sleeping, so concurrent callers queue up rather than all waking at once.
+pub(crate) struct RateLimiter<K = ()> {
+    interval: Duration,
+    /// When the next request for each key may be sent
+    next: Mutex<HashMap<K, Instant>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    /// The longest requests are spaced, which is also what zero, negative or NaN rates get
+    const MAX_INTERVAL: Duration = Duration::from_secs(3600);
+
+    pub(crate) fn per_second(per_second: f64) -> Self {
+        let interval = Duration::try_from_secs_f64(1.0 / per_second).unwrap_or(Self::MAX_INTERVAL);
+        Self {
+            interval: interval.min(Self::MAX_INTERVAL),
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sleeps until a request for key is allowed
+    pub(crate) async fn wait(&self, key: K) {
+        let at = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            let at = next.get(&key).map_or(now, |t| (*t).max(now));
+            next.insert(key, at + self.interval);
+            at
+        };
+        let wait = at.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            async_std::task::sleep(wait).await;
+        }
+    }
+}
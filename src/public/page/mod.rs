@@ -1,6 +1,10 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{CardId, GroupId, PageId, UserId};
 
 /// The page object is a screen where you can view a “collection” of data, which is typically displayed in cards.
 /// You use a page to organize, manage, and share content to other users in Domo.
@@ -10,16 +14,16 @@ use serde::{Deserialize, Serialize};
 #[serde(default, rename_all = "camelCase")]
 pub struct Page {
     /// The id of the page
-    pub id: Option<u64>,
+    pub id: Option<PageId>,
 
     /// The name of the page
     pub name: Option<String>,
 
     /// The ID of the page that is higher in organizational hierarchy
-    pub parent_id: Option<u64>,
+    pub parent_id: Option<PageId>,
 
     /// The ID of the page owner
-    pub owner_id: Option<u64>,
+    pub owner_id: Option<UserId>,
 
     /// Determines whether users (besides the page owner) can make updates to page or its content - the default value is false
     pub locked: Option<bool>,
@@ -28,7 +32,7 @@ pub struct Page {
     pub collection_ids: Option<Vec<u64>>,
 
     /// The ID of all cards contained within the page
-    pub card_ids: Option<Vec<u64>>,
+    pub card_ids: Option<Vec<CardId>>,
 
     /// All pages that are considered "sub pages" in organizational hierarchy
     pub children: Option<Vec<Page>>,
@@ -42,10 +46,10 @@ pub struct Page {
 #[serde(default, rename_all = "camelCase")]
 pub struct Visibility {
     /// IDs provided will share page with associated users
-    pub user_ids: Option<Vec<u64>>,
+    pub user_ids: Option<Vec<UserId>>,
 
     /// IDs provided will share page with associated groups
-    pub group_ids: Option<Vec<u64>>,
+    pub group_ids: Option<Vec<GroupId>>,
 }
 
 impl Page {
@@ -65,17 +69,17 @@ impl Page {
 
     pub fn template() -> Self {
         Self {
-            id: Some(0),
+            id: Some(PageId(0)),
             name: Some(String::from("Page Name")),
-            parent_id: Some(0),
-            owner_id: Some(0),
+            parent_id: Some(PageId(0)),
+            owner_id: Some(UserId(0)),
             locked: Some(false),
             collection_ids: Some(vec![1, 2, 3]),
-            card_ids: Some(vec![1, 2, 3]),
+            card_ids: Some(vec![CardId(1), CardId(2), CardId(3)]),
             children: Some(vec![]),
             visibility: Some(Visibility {
-                user_ids: Some(vec![1, 2, 3]),
-                group_ids: Some(vec![1, 2, 3]),
+                user_ids: Some(vec![UserId(1), UserId(2), UserId(3)]),
+                group_ids: Some(vec![GroupId(1), GroupId(2), GroupId(3)]),
             }),
         }
     }
@@ -95,7 +99,7 @@ pub struct Collection {
     pub description: Option<String>,
 
     /// IDs provided will add or remove cards that are not a part of a page collection
-    pub card_ids: Option<Vec<u64>>,
+    pub card_ids: Option<Vec<CardId>>,
 }
 
 impl Collection {
@@ -113,11 +117,127 @@ impl Collection {
             id: Some(0),
             title: Some(String::from("Collection Title")),
             description: Some(String::from("Collection Description")),
-            card_ids: Some(vec![1, 2, 3]),
+            card_ids: Some(vec![CardId(1), CardId(2), CardId(3)]),
         }
     }
 }
 
+/// A page and its collections, as saved by export_page and recreated by import_page
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PageBundle {
+    pub page: Page,
+    pub collections: Vec<Collection>,
+}
+
+/// What moving a page will change, built by plan_page_move.
+///
+/// Everyone the page is shared with is also given access to the new parent. Sub pages inherit the sharing
+/// of the pages above them, so the moved page and its sub pages stop being visible through the old parent's
+/// chain and become visible through the new one. Users and groups shared on a moved page directly keep their access.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PageMoveReport {
+    pub page_id: PageId,
+    pub old_parent: Option<PageId>,
+    pub new_parent: PageId,
+
+    /// Users who will gain access to the new parent
+    pub parent_users: Vec<UserId>,
+
+    /// Groups that will gain access to the new parent
+    pub parent_groups: Vec<GroupId>,
+
+    /// Users who will see the page or one of its sub pages through the new parent and could not before
+    pub gained_users: Vec<UserId>,
+
+    /// Groups that will see the page or one of its sub pages through the new parent and could not before
+    pub gained_groups: Vec<GroupId>,
+
+    /// Users who saw the page or one of its sub pages only through the old parent
+    pub lost_users: Vec<UserId>,
+
+    /// Groups that saw the page or one of its sub pages only through the old parent
+    pub lost_groups: Vec<GroupId>,
+}
+
+impl std::fmt::Display for PageMoveReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.old_parent {
+            Some(p) => write!(
+                f,
+                "Moving page {} from {} to {}",
+                self.page_id, p, self.new_parent
+            )?,
+            None => write!(f, "Moving page {} to {}", self.page_id, self.new_parent)?,
+        }
+        if self.parent_users.is_empty() && self.parent_groups.is_empty() {
+            write!(f, "\n  nobody gains access to page {}", self.new_parent)?;
+        }
+        for id in &self.parent_users {
+            write!(
+                f,
+                "\n  + user {} gains access to page {}",
+                id, self.new_parent
+            )?;
+        }
+        for id in &self.parent_groups {
+            write!(
+                f,
+                "\n  + group {} gains access to page {}",
+                id, self.new_parent
+            )?;
+        }
+        for id in &self.gained_users {
+            write!(f, "\n  + user {} gains access to the moved pages", id)?;
+        }
+        for id in &self.gained_groups {
+            write!(f, "\n  + group {} gains access to the moved pages", id)?;
+        }
+        for id in &self.lost_users {
+            write!(f, "\n  - user {} loses access to the moved pages", id)?;
+        }
+        for id in &self.lost_groups {
+            write!(f, "\n  - group {} loses access to the moved pages", id)?;
+        }
+        Ok(())
+    }
+}
+
+/// The users and groups a page is shared with, directly or through the pages above it
+#[derive(Debug, Default, Clone)]
+struct Audience {
+    users: BTreeSet<UserId>,
+    groups: BTreeSet<GroupId>,
+}
+
+impl Audience {
+    fn add(&mut self, visibility: &Option<Visibility>) {
+        if let Some(v) = visibility {
+            self.users.extend(v.user_ids.iter().flatten().copied());
+            self.groups.extend(v.group_ids.iter().flatten().copied());
+        }
+    }
+}
+
+/// One user who can see a page, and why, built by get_page_access
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PageAccess {
+    pub user_id: UserId,
+    pub name: Option<String>,
+    pub email: Option<String>,
+
+    /// The user owns the page
+    pub owner: bool,
+
+    /// The page is shared with the user directly
+    pub direct: bool,
+
+    /// The groups the page is shared with that the user belongs to
+    pub groups: Vec<GroupId>,
+}
+
 /// Page API methods
 /// Uses the form method_object
 impl super::Client {
@@ -145,6 +265,15 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns every top level page, with sub pages nested in children, paging through get_pages
+    pub async fn get_pages_all(&self) -> Result<Vec<Page>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(50, |limit, offset| {
+            self.get_pages(Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
     /// Creates a new page in your Domo instance.
     pub async fn post_page(
         &self,
@@ -163,7 +292,10 @@ impl super::Client {
     }
 
     /// Retrieves the details of an existing page.
-    pub async fn get_page(&self, id: u64) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+    pub async fn get_page(
+        &self,
+        id: PageId,
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("dashboard").await?;
         let mut response = surf::get(&format!("{}{}{}", self.host, "/v1/pages/", id))
             .header("Authorization", at)
@@ -183,7 +315,7 @@ impl super::Client {
     /// Moving a page by updating the parentId will also cause everyone with access to the page to have access to the new parent page.
     pub async fn put_page(
         &self,
-        id: u64,
+        id: PageId,
         page: Page,
     ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("dashboard").await?;
@@ -198,9 +330,342 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Locks or unlocks a page, sending only the locked flag so nothing else on the page changes
+    pub async fn set_page_locked(
+        &self,
+        id: PageId,
+        locked: bool,
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        self.put_page_fields(id, json!({ "locked": locked })).await
+    }
+
+    /// Gives a page to a new owner, and with recursive its sub pages at every level too.
+    /// Returns the IDs of the pages that changed hands.
+    pub async fn set_page_owner(
+        &self,
+        id: PageId,
+        owner_id: UserId,
+        recursive: bool,
+    ) -> Result<Vec<PageId>, Box<dyn Error + Send + Sync + 'static>> {
+        let mut changed = Vec::new();
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            self.put_page_fields(id, json!({ "ownerId": owner_id }))
+                .await?;
+            changed.push(id);
+            if recursive {
+                //Nested children aren't always filled in, so each sub page is read for its own children
+                let children = self.get_page(id).await?.children;
+                pending.extend(children.into_iter().flatten().filter_map(|c| c.id));
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Updates a page with a partial payload
+    async fn put_page_fields(
+        &self,
+        id: PageId,
+        fields: serde_json::Value,
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("dashboard").await?;
+        let mut response = surf::put(format!("{}{}{}", self.host, "/v1/pages/", id))
+            .header("Authorization", at)
+            .body(surf::Body::from_json(&fields)?)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Shares a page with more users and groups, keeping everyone it is already shared with
+    pub async fn share_page_with(
+        &self,
+        id: PageId,
+        users: &[UserId],
+        groups: &[GroupId],
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        self.modify_page(id, |page| {
+            let v = page.visibility.get_or_insert_with(Visibility::default);
+            let user_ids = v.user_ids.get_or_insert_with(Vec::new);
+            for u in users {
+                if !user_ids.contains(u) {
+                    user_ids.push(*u);
+                }
+            }
+            let group_ids = v.group_ids.get_or_insert_with(Vec::new);
+            for g in groups {
+                if !group_ids.contains(g) {
+                    group_ids.push(*g);
+                }
+            }
+        })
+        .await
+    }
+
+    /// Stops sharing a page with the given users and groups, leaving the rest of its sharing alone
+    pub async fn unshare_page(
+        &self,
+        id: PageId,
+        users: &[UserId],
+        groups: &[GroupId],
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        self.modify_page(id, |page| {
+            let v = page.visibility.get_or_insert_with(Visibility::default);
+            if let Some(user_ids) = v.user_ids.as_mut() {
+                user_ids.retain(|u| !users.contains(u));
+            }
+            if let Some(group_ids) = v.group_ids.as_mut() {
+                group_ids.retain(|g| !groups.contains(g));
+            }
+        })
+        .await
+    }
+
+    /// Adds cards to a page, after the cards already on it. Cards already on the page are skipped.
+    pub async fn add_cards_to_page(
+        &self,
+        id: PageId,
+        cards: &[CardId],
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        self.modify_page(id, |page| {
+            let card_ids = page.card_ids.get_or_insert_with(Vec::new);
+            for c in cards {
+                if !card_ids.contains(c) {
+                    card_ids.push(*c);
+                }
+            }
+        })
+        .await
+    }
+
+    /// Removes cards from a page, leaving the other cards in place
+    pub async fn remove_cards_from_page(
+        &self,
+        id: PageId,
+        cards: &[CardId],
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        self.modify_page(id, |page| {
+            if let Some(card_ids) = page.card_ids.as_mut() {
+                card_ids.retain(|c| !cards.contains(c));
+            }
+        })
+        .await
+    }
+
+    /// Works out who gains access to the new parent, and who gains or loses sight of the page and its sub pages,
+    /// if the page is moved there, without moving it.
+    /// Returns an error if the new parent is the page itself or one of its sub pages.
+    pub async fn plan_page_move(
+        &self,
+        id: PageId,
+        new_parent: PageId,
+    ) -> Result<PageMoveReport, Box<dyn Error + Send + Sync + 'static>> {
+        let page = self.get_page(id).await?;
+        let mut pending = vec![&page];
+        while let Some(p) = pending.pop() {
+            if p.id == Some(new_parent) {
+                return Err(format!("page {} cannot be moved under itself", id).into());
+            }
+            pending.extend(p.children.iter().flatten());
+        }
+        let parent = self.get_page(new_parent).await?;
+        let old_inherited = self.page_chain_audience(page.parent_id).await?;
+        let mut new_inherited = self.page_chain_audience(parent.parent_id).await?;
+        new_inherited.add(&parent.visibility);
+
+        //Each moved page keeps what is shared on it and on the moved pages above it, only the inherited part changes
+        let mut gained = Audience::default();
+        let mut lost = Audience::default();
+        let mut pending = vec![(id, Audience::default())];
+        while let Some((page_id, mut own)) = pending.pop() {
+            let p = self.get_page(page_id).await?;
+            own.add(&p.visibility);
+            let visible = |a: &Audience, u: &UserId| a.users.contains(u) || own.users.contains(u);
+            let visible_group =
+                |a: &Audience, g: &GroupId| a.groups.contains(g) || own.groups.contains(g);
+            gained.users.extend(
+                new_inherited
+                    .users
+                    .iter()
+                    .filter(|u| !visible(&old_inherited, u)),
+            );
+            gained.groups.extend(
+                new_inherited
+                    .groups
+                    .iter()
+                    .filter(|g| !visible_group(&old_inherited, g)),
+            );
+            lost.users.extend(
+                old_inherited
+                    .users
+                    .iter()
+                    .filter(|u| !visible(&new_inherited, u)),
+            );
+            lost.groups.extend(
+                old_inherited
+                    .groups
+                    .iter()
+                    .filter(|g| !visible_group(&new_inherited, g)),
+            );
+            for child in p.children.iter().flatten().filter_map(|c| c.id) {
+                pending.push((child, own.clone()));
+            }
+        }
+
+        let shared = page.visibility.unwrap_or_default();
+        let parent_shared = parent.visibility.unwrap_or_default();
+        let parent_users = parent_shared.user_ids.unwrap_or_default();
+        let parent_groups = parent_shared.group_ids.unwrap_or_default();
+        Ok(PageMoveReport {
+            page_id: id,
+            old_parent: page.parent_id,
+            new_parent,
+            parent_users: shared
+                .user_ids
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|u| !parent_users.contains(u))
+                .collect(),
+            parent_groups: shared
+                .group_ids
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|g| !parent_groups.contains(g))
+                .collect(),
+            gained_users: gained.users.into_iter().collect(),
+            gained_groups: gained.groups.into_iter().collect(),
+            lost_users: lost.users.into_iter().collect(),
+            lost_groups: lost.groups.into_iter().collect(),
+        })
+    }
+
+    /// Everyone a page is shared with along the chain of pages from first up to the top level
+    async fn page_chain_audience(
+        &self,
+        first: Option<PageId>,
+    ) -> Result<Audience, Box<dyn Error + Send + Sync + 'static>> {
+        let mut audience = Audience::default();
+        let mut seen = BTreeSet::new();
+        let mut next = first;
+        while let Some(id) = next.filter(|id| seen.insert(*id)) {
+            let p = self.get_page(id).await?;
+            audience.add(&p.visibility);
+            next = p.parent_id;
+        }
+        Ok(audience)
+    }
+
+    /// Moves a page, with its sub pages, under a new parent and returns who gained or lost access
+    pub async fn move_page(
+        &self,
+        id: PageId,
+        new_parent: PageId,
+    ) -> Result<PageMoveReport, Box<dyn Error + Send + Sync + 'static>> {
+        let report = self.plan_page_move(id, new_parent).await?;
+        self.modify_page(id, |page| page.parent_id = Some(new_parent))
+            .await?;
+        Ok(report)
+    }
+
+    /// Puts a page's collections in the given order.
+    /// Collections can only be reordered this way, so ordered_ids must list exactly the page's collections.
+    pub async fn reorder_collections(
+        &self,
+        id: PageId,
+        ordered_ids: &[u64],
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        let page = self.get_page(id).await?;
+        let mut current = page.collection_ids.unwrap_or_default();
+        let mut wanted = ordered_ids.to_vec();
+        current.sort_unstable();
+        wanted.sort_unstable();
+        if current != wanted {
+            return Err(format!(
+                "the new order must list exactly the collections on page {}: {:?}",
+                id, current
+            )
+            .into());
+        }
+        self.modify_page(id, |page| page.collection_ids = Some(ordered_ids.to_vec()))
+            .await
+    }
+
+    /// Replaces the cards in a collection, keeping its title and description
+    pub async fn set_collection_cards(
+        &self,
+        id: PageId,
+        collection_id: u64,
+        card_ids: &[CardId],
+    ) -> Result<Collection, Box<dyn Error + Send + Sync + 'static>> {
+        let mut collection = self
+            .get_page_collections(id)
+            .await?
+            .into_iter()
+            .find(|c| c.id == Some(collection_id))
+            .ok_or_else(|| format!("page {} has no collection {}", id, collection_id))?;
+        collection.card_ids = Some(card_ids.to_vec());
+        self.put_page_collection(id, collection_id, collection)
+            .await
+    }
+
+    /// Lists everyone who can see a page: its owner, the users it is shared with, and the members of the groups it is shared with.
+    /// Group members come from the Group API, so access through a parent page or admin rights is not included.
+    pub async fn get_page_access(
+        &self,
+        id: PageId,
+    ) -> Result<Vec<PageAccess>, Box<dyn Error + Send + Sync + 'static>> {
+        let page = self.get_page(id).await?;
+        let shared = page.visibility.unwrap_or_default();
+        let mut access: BTreeMap<UserId, PageAccess> = BTreeMap::new();
+        fn entry(access: &mut BTreeMap<UserId, PageAccess>, user_id: UserId) -> &mut PageAccess {
+            access.entry(user_id).or_insert_with(|| PageAccess {
+                user_id,
+                ..Default::default()
+            })
+        }
+        if let Some(owner) = page.owner_id {
+            entry(&mut access, owner).owner = true;
+        }
+        for u in shared.user_ids.unwrap_or_default() {
+            entry(&mut access, u).direct = true;
+        }
+        for g in shared.group_ids.unwrap_or_default() {
+            for u in self.get_group_users_all(g).await? {
+                entry(&mut access, u).groups.push(g);
+            }
+        }
+        for u in self.get_users_all().await? {
+            if let Some(a) = u.id.and_then(|id| access.get_mut(&id)) {
+                a.name = u.name;
+                a.email = u.email;
+            }
+        }
+        Ok(access.into_values().collect())
+    }
+
+    /// Fetches a page, applies the change, and PUTs it back.
+    /// put_page takes the whole page, so this saves callers from losing fields on a partial write.
+    async fn modify_page<F: FnOnce(&mut Page)>(
+        &self,
+        id: PageId,
+        change: F,
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        let mut page = self.get_page(id).await?;
+        //Sub pages are returned on read but are not part of the update
+        page.children = None;
+        change(&mut page);
+        self.put_page(id, page).await
+    }
+
     /// Permanently deletes a page from your Domo instance.
     /// This is destructive and cannot be reversed.
-    pub async fn delete_page(&self, id: u64) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    pub async fn delete_page(
+        &self,
+        id: PageId,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("dashboard").await?;
         let mut response = surf::delete(&format!("{}{}{}", self.host, "/v1/pages/", id))
             .header("Authorization", at)
@@ -214,7 +679,7 @@ impl super::Client {
 
     pub async fn get_page_collections(
         &self,
-        id: u64,
+        id: PageId,
     ) -> Result<Vec<Collection>, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("dashboard").await?;
         let mut response = surf::get(&format!(
@@ -232,7 +697,7 @@ impl super::Client {
 
     pub async fn post_page_collection(
         &self,
-        id: u64,
+        id: PageId,
         collection: Collection,
     ) -> Result<Collection, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("dashboard").await?;
@@ -252,10 +717,10 @@ impl super::Client {
 
     pub async fn put_page_collection(
         &self,
-        id: u64,
+        id: PageId,
         collection_id: u64,
         collection: Collection,
-    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    ) -> Result<Collection, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("dashboard").await?;
         let mut response = surf::put(&format!(
             "{}{}{}{}{}",
@@ -273,7 +738,7 @@ impl super::Client {
 
     pub async fn delete_page_collection(
         &self,
-        id: u64,
+        id: PageId,
         collection_id: u64,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("dashboard").await?;
@@ -289,4 +754,81 @@ impl super::Client {
         }
         Ok(response.body_json().await?)
     }
+
+    /// Copies a page with its cards and collections under a new name.
+    /// The copy goes under new_parent, or next to the source page if that is None.
+    ///
+    /// Sub pages and sharing are not copied; the copy is only visible to its owner until shared.
+    pub async fn clone_page(
+        &self,
+        source_id: PageId,
+        new_name: &str,
+        new_parent: Option<PageId>,
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        let source = self.get_page(source_id).await?;
+        let page = Page {
+            name: Some(String::from(new_name)),
+            parent_id: new_parent.or(source.parent_id),
+            locked: source.locked,
+            card_ids: source.card_ids,
+            ..Page::new()
+        };
+        let collections = self.get_page_collections(source_id).await?;
+        self.create_page_with_collections(page, collections).await
+    }
+
+    /// Reads a page with its sharing and collections so it can be saved and later recreated with import_page
+    pub async fn export_page(
+        &self,
+        id: PageId,
+    ) -> Result<PageBundle, Box<dyn Error + Send + Sync + 'static>> {
+        let mut page = self.get_page(id).await?;
+        //Sub pages are separate pages and exported on their own
+        page.children = None;
+        Ok(PageBundle {
+            page,
+            collections: self.get_page_collections(id).await?,
+        })
+    }
+
+    /// Creates a new page from an exported bundle, under parent if given or else the bundle's parent.
+    ///
+    /// Card, user, and group ids are used as they are, so when importing into another instance
+    /// the cards must exist there with the same ids (edit the bundle to map them first).
+    pub async fn import_page(
+        &self,
+        bundle: PageBundle,
+        parent: Option<PageId>,
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        let page = Page {
+            name: bundle.page.name,
+            parent_id: parent.or(bundle.page.parent_id),
+            locked: bundle.page.locked,
+            card_ids: bundle.page.card_ids,
+            visibility: bundle.page.visibility,
+            ..Page::new()
+        };
+        self.create_page_with_collections(page, bundle.collections)
+            .await
+    }
+
+    /// Posts a page then each collection on it, returning the finished page
+    async fn create_page_with_collections(
+        &self,
+        page: Page,
+        collections: Vec<Collection>,
+    ) -> Result<Page, Box<dyn Error + Send + Sync + 'static>> {
+        let page = self.post_page(page).await?;
+        let id = page.id.ok_or("created page has no id")?;
+        for c in collections {
+            let collection = Collection {
+                title: c.title,
+                description: c.description,
+                card_ids: c.card_ids,
+                ..Collection::new()
+            };
+            self.post_page_collection(id, collection).await?;
+        }
+        self.get_page(id).await
+    }
 }
@@ -0,0 +1,34 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// Where a configured value comes from. Secrets should come from the environment
+/// so they stay out of files written to disk, e.g. `password: { env: SNOWFLAKE_PASSWORD }`.
+/// Literals may be any scalar, so `port: 5432` and `ssl: true` are read as "5432" and "true".
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Env { env: String },
+    Literal(#[serde(deserialize_with = "scalar_string")] String),
+}
+
+impl PropertyValue {
+    /// The literal, or the value of the environment variable. An unset variable is returned as the error.
+    pub fn resolve(&self) -> Result<String, &str> {
+        match self {
+            PropertyValue::Literal(s) => Ok(s.clone()),
+            PropertyValue::Env { env } => std::env::var(env).map_err(|_| env.as_str()),
+        }
+    }
+}
+
+/// Reads a string, number or boolean as a string
+fn scalar_string<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    match serde_yaml::Value::deserialize(d)? {
+        serde_yaml::Value::String(s) => Ok(s),
+        serde_yaml::Value::Number(n) => Ok(n.to_string()),
+        serde_yaml::Value::Bool(b) => Ok(b.to_string()),
+        other => Err(de::Error::custom(format!(
+            "expected a string, number or boolean, got {:?}",
+            other
+        ))),
+    }
+}
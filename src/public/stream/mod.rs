@@ -1,9 +1,22 @@
+use async_std::io::{BufRead as AsyncBufRead, BufReader};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::{error::Error, path::Path};
+use std::{
+    error::Error,
+    path::Path,
+    time::{Duration, Instant},
+};
 
 use crate::public::dataset::DataSet;
+use crate::public::{DataSetId, StreamId, UserId};
+use crate::public::{Progress, ProgressReader};
+
+pub mod uploader;
+pub use uploader::{
+    ManifestPart, StreamUploader, StreamUploaderOptions, UploadManifest, UploadObserver,
+    UploadReport,
+};
 
 /// The Stream API allows you to automate the creation of new DataSets in your Domo Warehouse, featuring an accelerated upload Stream. A Domo Stream expedites uploads by dividing your data into parts, and uploading all of these parts simultaneously.
 ///
@@ -12,7 +25,7 @@ use crate::public::dataset::DataSet;
 #[serde(default, rename_all = "camelCase")]
 pub struct Stream {
     /// ID of the Stream
-    pub id: Option<u32>,
+    pub id: Option<StreamId>,
 
     /// An ISO-8601 representation of the create date of the Stream
     pub created_at: Option<DateTime<Utc>>,
@@ -50,7 +63,7 @@ impl Stream {
 
     pub fn template() -> Self {
         Stream {
-            id: Some(0),
+            id: Some(StreamId(0)),
             created_at: Some(Utc::now()),
             modified_at: Some(Utc::now()),
             update_method: Some(String::from("APPEND | REPLACE | UPSERT")),
@@ -63,6 +76,47 @@ impl Stream {
     }
 }
 
+/// Normalizes an update method to the upper case name the API expects, rejecting unknown methods
+fn update_method_name(
+    update_method: &str,
+) -> Result<String, Box<dyn Error + Send + Sync + 'static>> {
+    let update_method = update_method.to_uppercase();
+    if !["APPEND", "REPLACE", "UPSERT"].contains(&update_method.as_str()) {
+        return Err(format!(
+            "invalid update method {}, expected APPEND, REPLACE, or UPSERT",
+            update_method
+        )
+        .into());
+    }
+    Ok(update_method)
+}
+
+/// Domo only allows a stream to be committed once per interval
+pub const STREAM_COMMIT_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// How many times put_stream_execution_commit_when_allowed tries to commit before giving up
+pub const STREAM_COMMIT_ATTEMPTS: u32 = 5;
+
+/// Returned when a stream commit is refused because the stream was committed too recently
+#[derive(Debug)]
+pub struct CommitThrottled {
+    pub stream_id: StreamId,
+    /// How long until the stream can be committed again
+    pub remaining: Duration,
+}
+
+impl std::error::Error for CommitThrottled {}
+impl std::fmt::Display for CommitThrottled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Stream {} can not be committed for another {}s",
+            self.stream_id,
+            self.remaining.as_secs()
+        )
+    }
+}
+
 /// Defines a stream execution
 /// Each new set of data will be a new execution.
 /// When committed will trigger Domo to pull in the data from the stream and process it into the
@@ -86,6 +140,132 @@ pub struct Execution {
     pub modified_at: Option<DateTime<Utc>>,
 }
 
+/// The lifecycle states of a stream execution
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum ExecutionState {
+    Active,
+    Uploading,
+    Committing,
+    Success,
+    Error,
+    Aborted,
+    /// Any state this sdk does not know about yet
+    Other(String),
+}
+
+impl ExecutionState {
+    /// True once the execution has finished and will not change state again
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ExecutionState::Success | ExecutionState::Error | ExecutionState::Aborted
+        )
+    }
+}
+
+impl From<String> for ExecutionState {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "ACTIVE" => ExecutionState::Active,
+            "UPLOADING" => ExecutionState::Uploading,
+            "COMMITTING" => ExecutionState::Committing,
+            "SUCCESS" => ExecutionState::Success,
+            "ERROR" => ExecutionState::Error,
+            "ABORTED" => ExecutionState::Aborted,
+            _ => ExecutionState::Other(s),
+        }
+    }
+}
+
+impl From<ExecutionState> for String {
+    fn from(s: ExecutionState) -> Self {
+        match s {
+            ExecutionState::Active => String::from("ACTIVE"),
+            ExecutionState::Uploading => String::from("UPLOADING"),
+            ExecutionState::Committing => String::from("COMMITTING"),
+            ExecutionState::Success => String::from("SUCCESS"),
+            ExecutionState::Error => String::from("ERROR"),
+            ExecutionState::Aborted => String::from("ABORTED"),
+            ExecutionState::Other(s) => s,
+        }
+    }
+}
+
+impl Execution {
+    /// The current state as a typed value
+    pub fn state(&self) -> Option<ExecutionState> {
+        self.current_state.clone().map(ExecutionState::from)
+    }
+}
+
+/// A query for the stream search endpoint.
+/// Field criteria match a stream field against a value and can be combined with and/or.
+#[derive(Debug, Clone)]
+pub enum StreamSearchQuery {
+    /// Matches streams whose field has the given value
+    Field { name: String, value: String },
+
+    /// Matches streams matching both queries
+    And(Box<StreamSearchQuery>, Box<StreamSearchQuery>),
+
+    /// Matches streams matching either query
+    Or(Box<StreamSearchQuery>, Box<StreamSearchQuery>),
+}
+
+impl StreamSearchQuery {
+    /// Matches an arbitrary stream field, for example dataSource.name
+    pub fn field(name: &str, value: &str) -> Self {
+        StreamSearchQuery::Field {
+            name: String::from(name),
+            value: String::from(value),
+        }
+    }
+
+    /// Matches streams importing into the given dataset
+    pub fn dataset_id(id: &DataSetId) -> Self {
+        Self::field("dataSource.id", &id.0)
+    }
+
+    /// Matches streams whose dataset is owned by the given user
+    pub fn dataset_owner_id(id: UserId) -> Self {
+        Self::field("dataSource.owner.id", &id.to_string())
+    }
+
+    pub fn and(self, other: StreamSearchQuery) -> Self {
+        StreamSearchQuery::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: StreamSearchQuery) -> Self {
+        StreamSearchQuery::Or(Box::new(self), Box::new(other))
+    }
+
+    fn fmt_operand(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamSearchQuery::Field { .. } => write!(f, "{}", self),
+            _ => write!(f, "({})", self),
+        }
+    }
+}
+
+impl std::fmt::Display for StreamSearchQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamSearchQuery::Field { name, value } => write!(f, "{}:{}", name, value),
+            StreamSearchQuery::And(a, b) => {
+                a.fmt_operand(f)?;
+                write!(f, " AND ")?;
+                b.fmt_operand(f)
+            }
+            StreamSearchQuery::Or(a, b) => {
+                a.fmt_operand(f)?;
+                write!(f, " OR ")?;
+                b.fmt_operand(f)
+            }
+        }
+    }
+}
+
 /// Stream API methods
 /// Uses the form method_object
 impl super::Client {
@@ -116,22 +296,32 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
-    /// Returns all Stream objects that meet argument criteria from original request.
-    /// This search will return all streams with a matching dataset.id
-    pub async fn get_stream_search_dataset_id(
+    /// Returns every stream, paging through get_streams
+    pub async fn get_streams_all(
+        &self,
+    ) -> Result<Vec<Stream>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(500, |limit, offset| {
+            self.get_streams(Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
+    /// Returns all Stream objects that match the search query.
+    pub async fn search_streams(
         &self,
-        dsid: &str,
+        query: StreamSearchQuery,
     ) -> Result<Vec<Stream>, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         #[derive(Serialize)]
         struct QueryParams {
             pub q: String,
         }
-        let query = QueryParams {
-            q: String::from("dataSource.id:") + dsid,
+        let q = QueryParams {
+            q: query.to_string(),
         };
-        let mut response = surf::get(&format!("{}{}", self.host, "/v1/streams/search"))
-            .query(&query)?
+        let mut response = surf::get(format!("{}{}", self.host, "/v1/streams/search"))
+            .query(&q)?
             .header("Authorization", at)
             .await?;
         if !response.status().is_success() {
@@ -141,29 +331,24 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns all Stream objects that meet argument criteria from original request.
+    /// This search will return all streams with a matching dataset.id
+    pub async fn get_stream_search_dataset_id(
+        &self,
+        dsid: &DataSetId,
+    ) -> Result<Vec<Stream>, Box<dyn Error + Send + Sync + 'static>> {
+        self.search_streams(StreamSearchQuery::dataset_id(dsid))
+            .await
+    }
+
     /// Returns all Stream objects that meet argument criteria from original request.
     /// This search will return all streams with a matching dataset.owner.id
     pub async fn get_stream_search_dataset_owner_id(
         &self,
-        dsoid: &str,
+        dsoid: UserId,
     ) -> Result<Vec<Stream>, Box<dyn Error + Send + Sync + 'static>> {
-        let at = self.get_access_token("data").await?;
-        #[derive(Serialize)]
-        struct QueryParams {
-            pub q: String,
-        }
-        let query = QueryParams {
-            q: String::from("dataSource.owner.id:") + dsoid,
-        };
-        let mut response = surf::get(&format!("{}{}", self.host, "/v1/streams/search"))
-            .query(&query)?
-            .header("Authorization", at)
-            .await?;
-        if !response.status().is_success() {
-            let e: Box<super::PubAPIError> = response.body_json().await?;
-            return Err(e);
-        }
-        Ok(response.body_json().await?)
+        self.search_streams(StreamSearchQuery::dataset_owner_id(dsoid))
+            .await
     }
 
     /// When creating a Stream, specify the DataSet properties (name and description) and as a convenience, the create Stream API will create a DataSet for you.
@@ -185,10 +370,39 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Creates a stream for an existing DataSet, copying its name, description, and schema into the stream payload.
+    ///
+    /// update_method is one of "APPEND", "REPLACE", or "UPSERT".
+    /// The DataSet is referenced by id in the payload. Where Domo doesn't allow a stream on that DataSet
+    /// (for example one created by a connector) it creates a new DataSet with the same schema instead,
+    /// so check the DataSet id on the returned stream.
+    /// Returns an error if the DataSet already has a stream.
+    pub async fn post_stream_for_dataset(
+        &self,
+        dataset_id: &DataSetId,
+        update_method: &str,
+    ) -> Result<Stream, Box<dyn Error + Send + Sync + 'static>> {
+        let update_method = update_method_name(update_method)?;
+        let existing = self.get_stream_search_dataset_id(dataset_id).await?;
+        if let Some(id) = existing.first().and_then(|s| s.id) {
+            return Err(format!("dataset {} already has stream {}", dataset_id, id).into());
+        }
+        let ds = self.get_dataset(dataset_id).await?;
+        let mut dataset = DataSet::new();
+        dataset.id = ds.id;
+        dataset.name = ds.name;
+        dataset.description = ds.description;
+        dataset.schema = ds.schema;
+        let mut stream = Stream::new();
+        stream.update_method = Some(update_method);
+        stream.dataset = Some(dataset);
+        self.post_stream(stream).await
+    }
+
     /// Retrieves the details of an existing stream
     pub async fn get_stream(
         &self,
-        id: &str,
+        id: StreamId,
     ) -> Result<Stream, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         let mut response = surf::get(&format!("{}{}{}", self.host, "/v1/streams/", id))
@@ -204,7 +418,7 @@ impl super::Client {
     /// Updates the specified Stream’s metadata by providing values to parameters passed.
     pub async fn patch_stream(
         &self,
-        id: &str,
+        id: StreamId,
         stream: Stream,
     ) -> Result<Stream, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -219,10 +433,107 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Checks that an UPSERT stream's key column exists in its dataset schema and, when a header is given, in the header.
+    ///
+    /// Domo only reports a bad key column once the execution is committed, so this fails fast before any data is sent.
+    /// Streams that don't use UPSERT always pass.
+    pub async fn check_stream_upsert_key(
+        &self,
+        id: StreamId,
+        header: Option<&[String]>,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let stream = self.get_stream(id).await?;
+        let upsert = stream
+            .update_method
+            .as_deref()
+            .is_some_and(|m| m.eq_ignore_ascii_case("UPSERT"));
+        if !upsert {
+            return Ok(());
+        }
+        let key = stream
+            .key_column_name
+            .filter(|k| !k.is_empty())
+            .ok_or_else(|| format!("stream {} uses UPSERT but has no key column name", id))?;
+        let dataset_id = stream
+            .dataset
+            .and_then(|d| d.id)
+            .ok_or_else(|| format!("stream {} has no dataset", id))?;
+        let columns: Vec<String> = self
+            .get_dataset(&dataset_id)
+            .await?
+            .schema
+            .and_then(|s| s.columns)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|c| c.name)
+            .collect();
+        if !columns.contains(&key) {
+            return Err(format!(
+                "UPSERT key column {} is not in the schema of dataset {} (columns: {})",
+                key,
+                dataset_id,
+                columns.join(", ")
+            )
+            .into());
+        }
+        if let Some(header) = header {
+            if !header.contains(&key) {
+                return Err(format!(
+                    "UPSERT key column {} is missing from the csv header (columns: {})",
+                    key,
+                    header.join(", ")
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes only the update method of a stream: "APPEND", "REPLACE", or "UPSERT".
+    ///
+    /// Unlike patch_stream this sends nothing but the update method, so dataset fields can't be changed by accident.
+    pub async fn set_stream_update_method(
+        &self,
+        id: StreamId,
+        update_method: &str,
+    ) -> Result<Stream, Box<dyn Error + Send + Sync + 'static>> {
+        let update_method = update_method_name(update_method)?;
+        self.patch_stream_fields(id, json!({ "updateMethod": update_method }))
+            .await
+    }
+
+    /// Changes only the key column a stream uses for UPSERT updates
+    pub async fn set_stream_key_column(
+        &self,
+        id: StreamId,
+        key_column_name: &str,
+    ) -> Result<Stream, Box<dyn Error + Send + Sync + 'static>> {
+        self.patch_stream_fields(id, json!({ "keyColumnName": key_column_name }))
+            .await
+    }
+
+    /// Patches a stream with a partial payload
+    async fn patch_stream_fields(
+        &self,
+        id: StreamId,
+        fields: serde_json::Value,
+    ) -> Result<Stream, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("data").await?;
+        let mut response = surf::patch(format!("{}{}{}", self.host, "/v1/streams/", id))
+            .header("Authorization", at)
+            .body(surf::Body::from_json(&fields)?)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
     /// Deletes a Stream from your Domo instance. This does not a delete the associated DataSet.
     pub async fn delete_stream(
         &self,
-        id: &str,
+        id: StreamId,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         let mut response = surf::delete(&format!("{}{}{}", self.host, "/v1/streams/", id))
@@ -238,7 +549,7 @@ impl super::Client {
     /// Import data into a DataSet in your Domo instance. This request will replace the data currently in the DataSet.
     pub async fn get_stream_execution(
         &self,
-        id: &str,
+        id: StreamId,
         execution_id: &str,
     ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -259,7 +570,7 @@ impl super::Client {
     /// Creating an Execution on a Stream will abort all other Executions on that Stream. Each Stream can only have one active Execution at a time.
     pub async fn post_stream_execution(
         &self,
-        id: &str,
+        id: StreamId,
     ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
         let mut response = surf::post(&format!(
@@ -282,7 +593,7 @@ impl super::Client {
     /// Offset: The offset of the Stream ID to begin list of users within the response
     pub async fn get_stream_executions(
         &self,
-        id: &str,
+        id: StreamId,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<Execution>, Box<dyn Error + Send + Sync + 'static>> {
@@ -313,19 +624,79 @@ impl super::Client {
     /// To upload data in CSV format, the Domo specification used for representing data grids in CSV format closely follows the RFC standard for CSV (RFC-4180)
     pub async fn put_stream_execution_part(
         &self,
-        id: &str,
+        id: StreamId,
         execution_id: &str,
         part_id: &str,
         csv: impl AsRef<Path>,
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        let body = surf::Body::from_file(csv).await?;
+        self.put_stream_execution_part_body(id, execution_id, part_id, body)
+            .await
+    }
+
+    /// Creates a data part within the Stream execution from csv data already in memory.
+    pub async fn put_stream_execution_part_bytes(
+        &self,
+        id: StreamId,
+        execution_id: &str,
+        part_id: &str,
+        csv: &[u8],
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        self.put_stream_execution_part_body(
+            id,
+            execution_id,
+            part_id,
+            surf::Body::from_bytes(csv.to_vec()),
+        )
+        .await
+    }
+
+    /// Creates a data part within the Stream execution from a csv string.
+    pub async fn put_stream_execution_part_string(
+        &self,
+        id: StreamId,
+        execution_id: &str,
+        part_id: &str,
+        csv: String,
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        self.put_stream_execution_part_body(id, execution_id, part_id, surf::Body::from_string(csv))
+            .await
+    }
+
+    /// Creates a data part within the Stream execution from csv read from any async reader.
+    ///
+    /// The data is streamed to Domo as it is read, so generated data never needs to be written to a temporary file.
+    pub async fn put_stream_execution_part_reader(
+        &self,
+        id: StreamId,
+        execution_id: &str,
+        part_id: &str,
+        csv: impl AsyncBufRead + Unpin + Send + Sync + 'static,
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        self.put_stream_execution_part_body(
+            id,
+            execution_id,
+            part_id,
+            surf::Body::from_reader(csv, None),
+        )
+        .await
+    }
+
+    /// Uploads an already built request body as a data part within the Stream execution.
+    pub(crate) async fn put_stream_execution_part_body(
+        &self,
+        id: StreamId,
+        execution_id: &str,
+        part_id: &str,
+        body: surf::Body,
     ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
-        let mut response = surf::put(&format!(
+        let mut response = surf::put(format!(
             "{}{}{}{}{}{}{}",
             self.host, "/v1/streams/", id, "/executions/", execution_id, "/part/", part_id
         ))
         .header("Authorization", at)
-        //TODO Have the csv data passed in as an async_std::io::Read. <- Should just need to change the below to Body::from_reader
-        .body(surf::Body::from_file(csv).await?)
+        .body(body)
         .header("Content-Type", "text/csv")
         .await?;
         if !response.status().is_success() {
@@ -335,13 +706,63 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Uploads a csv file as a data part within the Stream execution, reporting upload progress as the file is sent.
+    pub async fn put_stream_execution_part_with_progress(
+        &self,
+        id: StreamId,
+        execution_id: &str,
+        part_id: &str,
+        csv: impl AsRef<Path>,
+        progress: Progress,
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        let file = async_std::fs::File::open(csv.as_ref()).await?;
+        let len = file.metadata().await?.len();
+        let reader = ProgressReader::new(BufReader::new(file), Some(len), progress);
+        self.put_stream_execution_part_body(
+            id,
+            execution_id,
+            part_id,
+            surf::Body::from_reader(reader, Some(len as usize)),
+        )
+        .await
+    }
+
+    /// Serializes rows to csv on the fly and uploads them to a Stream as a new execution, chunking them into parts and committing at the end.
+    ///
+    /// Uses the default StreamUploader settings; build a StreamUploader directly to tune part sizes or concurrency.
+    pub async fn upload_rows<T, I>(
+        &self,
+        stream_id: StreamId,
+        rows: I,
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>>
+    where
+        T: Serialize + Send,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'static,
+    {
+        Ok(StreamUploader::new(self, stream_id)
+            .upload_rows(rows)
+            .await?
+            .execution)
+    }
+
     /// Commits stream execution to import combined set of data parts that have been successfully uploaded.
     /// The Stream API only supports the ability to execute a “commit” every 15 minutes.
+    ///
+    /// Returns a CommitThrottled error with the remaining wait if this client committed the stream too recently,
+    /// or if Domo rejects the commit with 429 Too Many Requests.
     pub async fn put_stream_execution_commit(
         &self,
-        id: &str,
+        id: StreamId,
         execution_id: &str,
     ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        let remaining = self.stream_commit_remaining(id);
+        if !remaining.is_zero() {
+            return Err(Box::new(CommitThrottled {
+                stream_id: id,
+                remaining,
+            }));
+        }
         let at = self.get_access_token("data").await?;
         let mut response = surf::put(&format!(
             "{}{}{}{}{}{}",
@@ -349,18 +770,123 @@ impl super::Client {
         ))
         .header("Authorization", at)
         .await?;
+        if response.status() == surf::StatusCode::TooManyRequests {
+            let remaining = response
+                .header("Retry-After")
+                .and_then(|v| v.as_str().parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(STREAM_COMMIT_INTERVAL);
+            return Err(Box::new(CommitThrottled {
+                stream_id: id,
+                remaining,
+            }));
+        }
         if !response.status().is_success() {
             let e: Box<super::PubAPIError> = response.body_json().await?;
             return Err(e);
         }
+        self.stream_commits
+            .lock()
+            .unwrap()
+            .insert(id, Instant::now());
         Ok(response.body_json().await?)
     }
 
+    /// Commits a stream execution, first sleeping for as long as the commit interval requires.
+    ///
+    /// A throttled commit is tried up to STREAM_COMMIT_ATTEMPTS times. If the commit fails for good
+    /// the execution is aborted so it does not block later uploads, and the commit error is returned.
+    pub async fn put_stream_execution_commit_when_allowed(
+        &self,
+        id: StreamId,
+        execution_id: &str,
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        let mut attempt = 1;
+        let e = loop {
+            match self.put_stream_execution_commit(id, execution_id).await {
+                Err(e) => match e.downcast_ref::<CommitThrottled>() {
+                    Some(t) if attempt < STREAM_COMMIT_ATTEMPTS => {
+                        async_std::task::sleep(t.remaining).await;
+                        attempt += 1;
+                    }
+                    _ => break e,
+                },
+                r => return r,
+            }
+        };
+        //The commit error is more useful than any failure to abort
+        let _ = self.put_stream_execution_abort(id, execution_id).await;
+        Err(e)
+    }
+
+    /// How long until this client may commit the stream again, zero if it can commit now
+    pub fn stream_commit_remaining(&self, id: StreamId) -> Duration {
+        match self.stream_commits.lock().unwrap().get(&id) {
+            Some(last) => STREAM_COMMIT_INTERVAL.saturating_sub(last.elapsed()),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Polls a stream execution until it reaches a terminal state (SUCCESS, ERROR, or ABORTED) and returns it.
+    ///
+    /// An execution that ends in ERROR or ABORTED is still returned as Ok, so check state() on the result.
+    /// Returns a TimeoutError if the execution is still running once the timeout has passed.
+    pub async fn wait_for_execution(
+        &self,
+        id: StreamId,
+        execution_id: &str,
+        timeout: Duration,
+    ) -> Result<Execution, Box<dyn Error + Send + Sync + 'static>> {
+        super::poll_until(
+            timeout,
+            &format!("waiting for stream {} execution {}", id, execution_id),
+            || async {
+                let e = self.get_stream_execution(id, execution_id).await?;
+                match e.state() {
+                    Some(s) if s.is_terminal() => Ok(Some(e)),
+                    _ => Ok(None),
+                }
+            },
+        )
+        .await
+    }
+
+    /// Aborts executions that are still ACTIVE or UPLOADING and were started longer ago than older_than.
+    ///
+    /// A stuck execution blocks new ones from being created, which usually happens when an uploader crashes before committing.
+    /// Returns the executions that were aborted.
+    pub async fn abort_stale_executions(
+        &self,
+        id: StreamId,
+        older_than: Duration,
+    ) -> Result<Vec<Execution>, Box<dyn Error + Send + Sync + 'static>> {
+        let cutoff = Utc::now() - chrono::Duration::from_std(older_than)?;
+        let mut stale = Vec::new();
+        let mut pages = super::Paginator::new(500, |limit, offset| {
+            self.get_stream_executions(id, Some(limit), Some(offset))
+        });
+        while let Some(page) = pages.next_page().await? {
+            stale.extend(page.into_iter().filter(|e| {
+                matches!(
+                    e.state(),
+                    Some(ExecutionState::Active) | Some(ExecutionState::Uploading)
+                ) && e.started_at.or(e.created_at).is_some_and(|t| t < cutoff)
+            }));
+        }
+        for e in &stale {
+            if let Some(execution_id) = e.id {
+                self.put_stream_execution_abort(id, &execution_id.to_string())
+                    .await?;
+            }
+        }
+        Ok(stale)
+    }
+
     /// If needed during an execution, aborts an entire Stream execution.
     /// To abort the current stream execution within a Stream, simply identify the Stream’s ID within request.
     pub async fn put_stream_execution_abort(
         &self,
-        id: &str,
+        id: StreamId,
         execution_id: &str,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("data").await?;
@@ -0,0 +1,700 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Read;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use csv::{ReaderBuilder, Writer, WriterBuilder};
+use futures::future::{self, Either};
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+#[cfg(feature = "parquet")]
+use parquet::file::reader::{FileReader, SerializedFileReader};
+#[cfg(feature = "parquet")]
+use parquet::record::Field;
+use serde::{Deserialize, Serialize};
+
+use super::Execution;
+use crate::public::{write_atomic, Client, StreamId};
+
+/// Uploads csv data to a Stream, handling the whole execution lifecycle.
+///
+/// The data is split into parts by row count and size, a new execution is created, every part is uploaded, and the execution is committed.
+/// Parts are uploaded concurrently and each part is retried on its own before the upload is considered failed.
+/// UPSERT streams have their key column checked against the dataset schema and the input's header before an execution is created.
+/// If the stream was committed too recently the commit waits until it is allowed.
+/// If anything fails along the way the execution is aborted so it does not block later uploads,
+/// unless a manifest is being kept, in which case the execution is left open so the upload can be resumed.
+/// A commit that still fails after its retries always aborts the execution. The manifest is then kept,
+/// and resuming from it reports the aborted execution; it is only removed once a commit succeeds.
+pub struct StreamUploader<'a> {
+    client: &'a Client,
+    stream_id: StreamId,
+    options: StreamUploaderOptions,
+    manifest: Option<PathBuf>,
+    observer: Option<Arc<dyn UploadObserver>>,
+}
+
+/// Receives events from a StreamUploader as the upload runs.
+///
+/// Every method has an empty default so implementors only need to handle the events they care about.
+/// Parts upload concurrently, so methods may be called from several tasks at once.
+pub trait UploadObserver: Send + Sync {
+    /// The execution the parts are going into, called once before any parts are uploaded
+    fn started(&self, _execution_id: &str) {}
+
+    /// A part was uploaded successfully
+    fn part_uploaded(&self, _part: &ManifestPart) {}
+
+    /// A part was already uploaded according to the manifest and has been skipped
+    fn part_skipped(&self, _part: &ManifestPart) {}
+
+    /// A part failed to upload and is about to be retried
+    fn part_retry(&self, _part: &ManifestPart, _attempt: u32, _error: &(dyn Error + Send + Sync)) {}
+}
+
+/// Summarizes a committed upload
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadReport {
+    /// The execution as returned by the commit
+    pub execution: Execution,
+
+    /// Every part in the execution, including parts resumed from a manifest
+    pub parts: Vec<ManifestPart>,
+
+    /// How many of the parts were already uploaded by an earlier run
+    pub resumed_parts: u32,
+
+    /// Total rows across all parts
+    pub rows: u64,
+
+    /// Total bytes across all parts
+    pub bytes: u64,
+
+    /// Rows in the parts uploaded by this run, leaving out resumed parts
+    pub uploaded_rows: u64,
+
+    /// Bytes in the parts uploaded by this run, leaving out resumed parts
+    pub uploaded_bytes: u64,
+
+    /// How many part uploads were retried
+    pub retries: u32,
+
+    /// How long this run took, from creating or resuming the execution to the commit
+    pub duration: Duration,
+}
+
+impl UploadReport {
+    /// Rows uploaded per second over this run, not counting resumed parts
+    pub fn rows_per_second(&self) -> f64 {
+        self.uploaded_rows as f64 / self.duration.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Bytes uploaded per second over this run, not counting resumed parts
+    pub fn bytes_per_second(&self) -> f64 {
+        self.uploaded_bytes as f64 / self.duration.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Controls how a StreamUploader splits and uploads data.
+///
+/// A part is closed as soon as it reaches either max_rows or max_bytes, whichever comes first.
+/// Domo recommends parts of roughly 50-100MB once gzipped; parts are sent uncompressed, so the byte limit
+/// is measured on the uncompressed csv and the default sits at the top of that range.
+#[derive(Debug, Clone)]
+pub struct StreamUploaderOptions {
+    /// The most rows to put in a single part. Defaults to 1,000,000.
+    pub max_rows: usize,
+
+    /// The most bytes of uncompressed csv to put in a single part. Defaults to 100MiB.
+    pub max_bytes: usize,
+
+    /// Whether the input starts with a header row, which is skipped as stream parts must not contain one. Defaults to true.
+    pub has_header: bool,
+
+    /// How many parts may be uploading at the same time. Defaults to 4.
+    pub concurrency: usize,
+
+    /// How many times a failed part upload is retried before giving up. Defaults to 3.
+    /// Retries back off exponentially, waiting at most 64 seconds between attempts.
+    pub retries: u32,
+}
+
+impl StreamUploaderOptions {
+    pub const DEFAULT_MAX_ROWS: usize = 1_000_000;
+    pub const DEFAULT_MAX_BYTES: usize = 100 * 1024 * 1024;
+    pub const DEFAULT_CONCURRENCY: usize = 4;
+    pub const DEFAULT_RETRIES: u32 = 3;
+}
+
+impl Default for StreamUploaderOptions {
+    fn default() -> Self {
+        Self {
+            max_rows: Self::DEFAULT_MAX_ROWS,
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            has_header: true,
+            concurrency: Self::DEFAULT_CONCURRENCY,
+            retries: Self::DEFAULT_RETRIES,
+        }
+    }
+}
+
+/// Records the progress of an upload so a crashed upload can resume into the same execution
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct UploadManifest {
+    /// The stream being uploaded to
+    pub stream_id: StreamId,
+
+    /// The execution the parts are being uploaded into
+    pub execution_id: String,
+
+    /// The parts that have been uploaded successfully
+    pub parts: Vec<ManifestPart>,
+}
+
+/// A single uploaded part
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ManifestPart {
+    /// The part id used for the upload
+    pub part_id: u32,
+
+    /// The byte offset of this part within the csv being uploaded
+    pub offset: u64,
+
+    /// The size of this part in bytes
+    pub bytes: u64,
+
+    /// The number of rows in this part
+    pub rows: u64,
+}
+
+impl UploadManifest {
+    fn save(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        write_atomic(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl<'a> StreamUploader<'a> {
+    pub fn new(client: &'a Client, stream_id: StreamId) -> Self {
+        Self::with_options(client, stream_id, StreamUploaderOptions::default())
+    }
+
+    pub fn with_options(
+        client: &'a Client,
+        stream_id: StreamId,
+        options: StreamUploaderOptions,
+    ) -> Self {
+        Self {
+            client,
+            stream_id,
+            options,
+            manifest: None,
+            observer: None,
+        }
+    }
+
+    /// The most rows to put in a single part
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.options.max_rows = max_rows;
+        self
+    }
+
+    /// The most bytes of uncompressed csv to put in a single part
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.options.max_bytes = max_bytes;
+        self
+    }
+
+    /// Whether the input starts with a header row
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.options.has_header = has_header;
+        self
+    }
+
+    /// How many parts may be uploading at the same time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.options.concurrency = concurrency;
+        self
+    }
+
+    /// How many times a failed part upload is retried before giving up
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.options.retries = retries;
+        self
+    }
+
+    /// Keep a manifest of uploaded parts at this path.
+    ///
+    /// If the manifest already exists the upload resumes into its execution, skipping parts that were already uploaded.
+    /// The same input and part sizes must be used when resuming. The manifest is removed once the execution is committed.
+    pub fn manifest(mut self, path: impl AsRef<Path>) -> Self {
+        self.manifest = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Reports progress, retries, and skipped parts to the observer while uploading
+    pub fn observer(mut self, observer: Arc<dyn UploadObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Uploads a csv file as a new execution and commits it
+    pub async fn upload_csv_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>> {
+        self.upload_csv_reader(File::open(path)?).await
+    }
+
+    /// Uploads csv read from any reader as a new execution and commits it.
+    /// The reader is read on a blocking thread so parts keep uploading while the next one is read.
+    pub async fn upload_csv_reader(
+        &self,
+        reader: impl Read + Send + 'static,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>> {
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(self.options.has_header)
+            .flexible(true)
+            .from_reader(reader);
+        let header = if self.options.has_header {
+            Some(rdr.headers()?.iter().map(String::from).collect())
+        } else {
+            None
+        };
+        self.upload(header, rdr.into_byte_records(), |w, record| {
+            w.write_byte_record(&record?)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Uploads each csv file as its own part, in the order given, as a new execution and commits it.
+    ///
+    /// Files are not re-split, so each should already be a sensible part size. When has_header is set the first row of every file is dropped.
+    pub async fn upload_csv_files<P: AsRef<Path> + Send + 'static>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>> {
+        let has_header = self.options.has_header;
+        let paths: Vec<P> = paths.into_iter().collect();
+        let header = match paths.first() {
+            Some(first) if has_header => Some(
+                ReaderBuilder::new()
+                    .from_path(first)?
+                    .headers()?
+                    .iter()
+                    .map(String::from)
+                    .collect(),
+            ),
+            _ => None,
+        };
+        let chunks = paths.into_iter().map(move |path| {
+            let data = fs::read(path)?;
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(has_header)
+                .flexible(true)
+                .from_reader(&data[..]);
+            let start = if has_header {
+                rdr.byte_headers()?;
+                rdr.position().byte() as usize
+            } else {
+                0
+            };
+            let mut rows = 0;
+            for record in rdr.byte_records() {
+                record?;
+                rows += 1;
+            }
+            Ok(Chunk {
+                rows,
+                data: data[start..].to_vec(),
+            })
+        });
+        self.upload_chunks(header, chunks).await
+    }
+
+    /// Converts a parquet file to csv one row group at a time and uploads it as a new execution, then commits it.
+    ///
+    /// Only the row groups currently being converted are held in memory, never the whole file.
+    /// Columns are written in the order of the parquet schema, which must match the stream's dataset schema.
+    /// Dates are written as YYYY-MM-DD, timestamps as RFC 3339 in UTC, binary as base64, and nested values as json.
+    #[cfg(feature = "parquet")]
+    pub async fn upload_parquet(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>> {
+        let reader = SerializedFileReader::new(File::open(path)?)?;
+        let header = reader
+            .metadata()
+            .file_metadata()
+            .schema()
+            .get_fields()
+            .iter()
+            .map(|f| String::from(f.name()))
+            .collect();
+        self.upload(Some(header), reader, |w, row| {
+            w.write_record(row?.get_column_iter().map(|(_, f)| parquet_field(f)))?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Serializes rows to csv as they are pulled from the iterator and uploads them as a new execution, then commits it.
+    ///
+    /// Rows are never all held in memory at once, which makes this a good fit for piping a database cursor into Domo.
+    /// Field order must match the stream's dataset schema.
+    pub async fn upload_rows<T, I>(
+        &self,
+        rows: I,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>>
+    where
+        T: Serialize + Send,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'static,
+    {
+        self.upload(None, rows, |w, row| {
+            w.serialize(row)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Loads the manifest to resume from, or creates a new execution
+    async fn start(&self) -> Result<UploadManifest, Box<dyn Error + Send + Sync + 'static>> {
+        if let Some(path) = self.manifest.as_ref().filter(|p| p.exists()) {
+            let manifest: UploadManifest = serde_json::from_str(&fs::read_to_string(path)?)?;
+            if manifest.stream_id != self.stream_id {
+                return Err(format!(
+                    "manifest {} belongs to stream {}",
+                    path.display(),
+                    manifest.stream_id
+                )
+                .into());
+            }
+            let execution = self
+                .client
+                .get_stream_execution(self.stream_id, &manifest.execution_id)
+                .await?;
+            if let Some(state) = execution.state().filter(|s| s.is_terminal()) {
+                return Err(format!(
+                    "execution {} is already {:?}, remove {} to start a new upload",
+                    manifest.execution_id,
+                    state,
+                    path.display()
+                )
+                .into());
+            }
+            return Ok(manifest);
+        }
+
+        let execution = self.client.post_stream_execution(self.stream_id).await?;
+        let manifest = UploadManifest {
+            stream_id: self.stream_id,
+            execution_id: execution
+                .id
+                .ok_or("stream execution was created without an id")?
+                .to_string(),
+            parts: Vec::new(),
+        };
+        if let Some(path) = &self.manifest {
+            manifest.save(path)?;
+        }
+        Ok(manifest)
+    }
+
+    /// Runs the execution lifecycle around upload_parts
+    async fn upload<I, F>(
+        &self,
+        header: Option<Vec<String>>,
+        items: I,
+        write: F,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>>
+    where
+        I: IntoIterator,
+        I::IntoIter: Send + 'static,
+        I::Item: Send,
+        F: FnMut(
+                &mut Writer<Vec<u8>>,
+                I::Item,
+            ) -> Result<(), Box<dyn Error + Send + Sync + 'static>>
+            + Send
+            + 'static,
+    {
+        let chunks = CsvChunks {
+            items: items.into_iter().peekable(),
+            write,
+            max_rows: self.options.max_rows.max(1),
+            max_bytes: self.options.max_bytes,
+        };
+        self.upload_chunks(header, chunks).await
+    }
+
+    /// Uploads each chunk as a part of a new execution (or the manifest's execution) and commits it.
+    /// The header, when the input has one, is only used to check the UPSERT key column before anything is uploaded.
+    async fn upload_chunks(
+        &self,
+        header: Option<Vec<String>>,
+        chunks: impl Iterator<Item = Result<Chunk, Box<dyn Error + Send + Sync + 'static>>>
+            + Send
+            + 'static,
+    ) -> Result<UploadReport, Box<dyn Error + Send + Sync + 'static>> {
+        let started = Instant::now();
+        self.client
+            .check_stream_upsert_key(self.stream_id, header.as_deref())
+            .await?;
+        let mut manifest = self.start().await?;
+        let execution_id = manifest.execution_id.clone();
+        if let Some(o) = &self.observer {
+            o.started(&execution_id);
+        }
+        let resumed_parts = manifest.parts.len() as u32;
+        let resumed_rows: u64 = manifest.parts.iter().map(|p| p.rows).sum();
+        let resumed_bytes: u64 = manifest.parts.iter().map(|p| p.bytes).sum();
+        match self.upload_parts(&mut manifest, read_chunks(chunks)).await {
+            Ok(retries) => {
+                let execution = self
+                    .client
+                    .put_stream_execution_commit_when_allowed(self.stream_id, &execution_id)
+                    .await?;
+                //The data is committed, so a manifest that can not be removed must not turn this into an error
+                //that a caller might retry, uploading the data twice
+                if let Some(path) = &self.manifest {
+                    let _ = fs::remove_file(path);
+                }
+                let rows: u64 = manifest.parts.iter().map(|p| p.rows).sum();
+                let bytes: u64 = manifest.parts.iter().map(|p| p.bytes).sum();
+                Ok(UploadReport {
+                    execution,
+                    rows,
+                    bytes,
+                    uploaded_rows: rows - resumed_rows,
+                    uploaded_bytes: bytes - resumed_bytes,
+                    parts: manifest.parts,
+                    resumed_parts,
+                    retries,
+                    duration: started.elapsed(),
+                })
+            }
+            Err(e) => {
+                //Leave the execution open when it can be resumed from the manifest.
+                //Otherwise abort it, though the original error is more useful than any failure to abort.
+                if self.manifest.is_none() {
+                    let _ = self
+                        .client
+                        .put_stream_execution_abort(self.stream_id, &execution_id)
+                        .await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Numbers the chunks as parts and uploads any that are not already in the manifest, recording each part as it completes.
+    /// Uploads in flight keep being polled while the next chunk is read, and reading waits for a free slot so memory stays bounded.
+    /// Returns how many retries were needed.
+    async fn upload_parts(
+        &self,
+        manifest: &mut UploadManifest,
+        chunks: impl Stream<Item = Result<Chunk, Box<dyn Error + Send + Sync + 'static>>>,
+    ) -> Result<u32, Box<dyn Error + Send + Sync + 'static>> {
+        let execution_id = manifest.execution_id.clone();
+        let concurrency = self.options.concurrency.max(1);
+        let mut chunks = Box::pin(chunks);
+        let mut in_flight = FuturesUnordered::new();
+        let mut reading = true;
+        let mut part_id = 0;
+        let mut offset = 0;
+        let mut retries = 0;
+        while reading || !in_flight.is_empty() {
+            if !reading || in_flight.len() >= concurrency {
+                if let Some(r) = in_flight.next().await {
+                    retries += self.record(manifest, r?)?;
+                }
+                continue;
+            }
+            let next = if in_flight.is_empty() {
+                chunks.next().await
+            } else {
+                match future::select(chunks.next(), in_flight.next()).await {
+                    Either::Left((chunk, _)) => chunk,
+                    Either::Right((r, _)) => {
+                        if let Some(r) = r {
+                            retries += self.record(manifest, r?)?;
+                        }
+                        continue;
+                    }
+                }
+            };
+            let chunk = match next {
+                Some(chunk) => chunk?,
+                None => {
+                    reading = false;
+                    continue;
+                }
+            };
+            part_id += 1;
+            let part = ManifestPart {
+                part_id,
+                offset,
+                bytes: chunk.data.len() as u64,
+                rows: chunk.rows,
+            };
+            offset += part.bytes;
+            if let Some(done) = manifest.parts.iter().find(|p| p.part_id == part_id) {
+                if *done != part {
+                    return Err(format!(
+                        "part {} does not match the manifest, the input or part sizes have changed",
+                        part_id
+                    )
+                    .into());
+                }
+                if let Some(o) = &self.observer {
+                    o.part_skipped(&part);
+                }
+                continue;
+            }
+            in_flight.push(self.upload_part(&execution_id, part, chunk.data));
+        }
+        manifest.parts.sort_by_key(|p| p.part_id);
+        Ok(retries)
+    }
+
+    /// Adds an uploaded part to the manifest and passes its retry count through
+    fn record(
+        &self,
+        manifest: &mut UploadManifest,
+        (part, retries): (ManifestPart, u32),
+    ) -> Result<u32, Box<dyn Error + Send + Sync + 'static>> {
+        if let Some(o) = &self.observer {
+            o.part_uploaded(&part);
+        }
+        manifest.parts.push(part);
+        if let Some(path) = &self.manifest {
+            manifest.save(path)?;
+        }
+        Ok(retries)
+    }
+
+    /// Uploads a single part, retrying with backoff. Returns the part and how many retries it took once it has been uploaded.
+    async fn upload_part(
+        &self,
+        execution_id: &str,
+        part: ManifestPart,
+        data: Vec<u8>,
+    ) -> Result<(ManifestPart, u32), Box<dyn Error + Send + Sync + 'static>> {
+        let mut attempt = 0;
+        loop {
+            let r = self
+                .client
+                .put_stream_execution_part_body(
+                    self.stream_id,
+                    execution_id,
+                    &part.part_id.to_string(),
+                    surf::Body::from_bytes(data.clone()),
+                )
+                .await;
+            match r {
+                Ok(_) => return Ok((part, attempt)),
+                Err(e) if attempt < self.options.retries => {
+                    attempt += 1;
+                    if let Some(o) = &self.observer {
+                        o.part_retry(&part, attempt, e.as_ref());
+                    }
+                    async_std::task::sleep(Duration::from_secs(1 << attempt.min(6))).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Reads the chunks on a blocking thread, handing them over one at a time.
+/// Reading stops once the receiving side is dropped, e.g. after an upload has failed.
+fn read_chunks(
+    chunks: impl Iterator<Item = Result<Chunk, Box<dyn Error + Send + Sync + 'static>>> + Send + 'static,
+) -> impl Stream<Item = Result<Chunk, Box<dyn Error + Send + Sync + 'static>>> {
+    let (tx, rx) = async_std::channel::bounded(1);
+    async_std::task::spawn_blocking(move || {
+        for chunk in chunks {
+            if tx.send_blocking(chunk).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// The csv for a single part, before it has been given a part id
+struct Chunk {
+    rows: u64,
+    data: Vec<u8>,
+}
+
+/// Writes items into csv chunks of at most max_rows rows, starting a new chunk once max_bytes has been reached
+struct CsvChunks<I: Iterator, F> {
+    items: Peekable<I>,
+    write: F,
+    max_rows: usize,
+    max_bytes: usize,
+}
+
+impl<I, F> Iterator for CsvChunks<I, F>
+where
+    I: Iterator,
+    F: FnMut(&mut Writer<Vec<u8>>, I::Item) -> Result<(), Box<dyn Error + Send + Sync + 'static>>,
+{
+    type Item = Result<Chunk, Box<dyn Error + Send + Sync + 'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.peek()?;
+        //Stream parts never contain a header row
+        let mut w = WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(Vec::new());
+        let mut rows = 0;
+        for item in self.items.by_ref() {
+            if let Err(e) = (self.write)(&mut w, item) {
+                return Some(Err(e));
+            }
+            rows += 1;
+            if rows as usize >= self.max_rows || w.get_ref().len() >= self.max_bytes {
+                break;
+            }
+        }
+        Some(
+            w.into_inner()
+                .map(|data| Chunk { rows, data })
+                .map_err(|e| e.into()),
+        )
+    }
+}
+
+/// Formats a parquet value the way Domo expects it in csv
+#[cfg(feature = "parquet")]
+fn parquet_field(field: &Field) -> String {
+    use chrono::{NaiveDate, SecondsFormat, TimeZone, Utc};
+    let timestamp = |t: Option<chrono::DateTime<Utc>>| {
+        t.map(|t| t.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+            .unwrap_or_default()
+    };
+    match field {
+        Field::Null => String::new(),
+        Field::Str(s) => s.clone(),
+        Field::Bytes(b) => base64::encode(b.data()),
+        Field::Date(days) => NaiveDate::from_ymd_opt(1970, 1, 1)
+            .and_then(|epoch| epoch.checked_add_signed(chrono::Duration::days(*days as i64)))
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        Field::TimestampMillis(ms) => timestamp(Utc.timestamp_millis_opt(*ms).single()),
+        Field::TimestampMicros(us) => timestamp(Some(Utc.timestamp_nanos(us.saturating_mul(1000)))),
+        Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_) => {
+            field.to_json_value().to_string()
+        }
+        _ => field.to_string(),
+    }
+}
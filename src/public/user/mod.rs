@@ -1,13 +1,27 @@
 use std::error::Error;
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::public::page::Page;
+use crate::public::{bulk, DataSetId, GroupId, PageId, UserId};
+
+pub mod reconcile;
+pub use reconcile::ReconcilePlan;
+
+mod validate;
+pub use validate::{validate_user, InvalidUserField};
+
+/// How many deletes delete_users sends at once
+const USER_DELETE_CONCURRENCY: usize = 4;
 
 /// User objects allow you to manage a user and the user’s attributes such as a department, phone number, employee number, email, and username. The API allows you to create, delete, retrieve a user or a list of users, and update user information
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default, rename_all = "camelCase")]
 pub struct User {
     /// The domo user id
-    pub id: Option<u64>,
+    pub id: Option<UserId>,
 
     /// User's full name
     pub name: Option<String>,
@@ -44,9 +58,9 @@ pub struct User {
 
     /// The role of the user created (available roles are: 'Admin', 'Privileged', 'Participant')
     /// Deprecated in liu of custom roles and authorities
-    pub role: Option<String>,
+    pub role: Option<Role>,
 
-    /// The role id of the user created
+    /// The role id of the user created. Use this for custom roles.
     pub role_id: Option<u64>,
 
     /// If the user ID is related to a user that has been deleted, a subset of the user information will be returned, including a deleted property, which will be true.
@@ -75,7 +89,7 @@ impl User {
     }
     pub fn template() -> Self {
         User {
-            id: Some(0),
+            id: Some(UserId(0)),
             name: Some(String::from("First Last")),
             email: Some(String::from("First.Last@company.com")),
             alternate_email: Some(String::from("first.last@gmail.com")),
@@ -87,13 +101,279 @@ impl User {
             department: Some(String::from("department")),
             timezone: Some(String::from("America/Los_Angeles")),
             locale: Some(String::from("en-US")),
-            role: Some(String::from("Admin - Match roles defined in instance")),
+            role: Some(Role::Other(String::from(
+                "Admin | Privileged | Participant",
+            ))),
             role_id: Some(0),
             deleted: Some(false),
         }
     }
 }
 
+/// The standard Domo roles. Custom roles are assigned through role_id instead.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum Role {
+    Admin,
+    Privileged,
+    Participant,
+    /// Any role name this sdk does not know about
+    Other(String),
+}
+
+impl From<String> for Role {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "Admin" => Role::Admin,
+            "Privileged" => Role::Privileged,
+            "Participant" => Role::Participant,
+            _ => Role::Other(s),
+        }
+    }
+}
+
+impl From<Role> for String {
+    fn from(r: Role) -> Self {
+        match r {
+            Role::Admin => String::from("Admin"),
+            Role::Privileged => String::from("Privileged"),
+            Role::Participant => String::from("Participant"),
+            Role::Other(s) => s,
+        }
+    }
+}
+
+/// A role defined in the instance, either one of the standard roles or a custom role
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RoleDefinition {
+    /// The role id, as used in User.role_id
+    pub id: Option<u64>,
+
+    /// The role name
+    pub name: Option<String>,
+
+    /// The role description
+    pub description: Option<String>,
+
+    /// The number of authorities granted by the role
+    pub authority_count: Option<u32>,
+
+    /// The number of users with the role
+    pub user_count: Option<u32>,
+}
+
+/// Everything offboarding a user will touch, built by plan_user_offboard
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OffboardPlan {
+    pub user_id: UserId,
+
+    /// Who owned content is reassigned to, if anyone
+    pub reassign_to: Option<UserId>,
+
+    /// IDs of the datasets the user owns
+    pub datasets: Vec<DataSetId>,
+
+    /// IDs of the pages the user owns
+    pub pages: Vec<PageId>,
+
+    /// IDs of the groups the user is a member of
+    pub groups: Vec<GroupId>,
+}
+
+impl std::fmt::Display for OffboardPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let owner = match self.reassign_to {
+            Some(id) => format!("reassign to user {}", id),
+            None => String::from("keep owner"),
+        };
+        writeln!(f, "Offboarding user {}", self.user_id)?;
+        for id in &self.datasets {
+            writeln!(f, "  dataset {}: {}", id, owner)?;
+        }
+        for id in &self.pages {
+            writeln!(f, "  page {}: {}", id, owner)?;
+        }
+        for id in &self.groups {
+            writeln!(f, "  group {}: remove membership", id)?;
+        }
+        write!(f, "  delete user {}", self.user_id)
+    }
+}
+
+/// A single field that a bulk update will change
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFieldChange {
+    /// The user field, by its api name e.g. employeeNumber
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+impl std::fmt::Display for UserFieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "~ {}: {} -> {}", self.field, self.from, self.to)
+    }
+}
+
+/// The planned update for one user: the user with the new values merged in, and what changed
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserUpdatePlan {
+    pub id: UserId,
+    pub changes: Vec<UserFieldChange>,
+    pub user: User,
+}
+
+impl UserUpdatePlan {
+    /// True when the update would not change anything
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl std::fmt::Display for UserUpdatePlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "user {}: no changes", self.id);
+        }
+        write!(f, "user {}:", self.id)?;
+        for c in &self.changes {
+            write!(f, "\n  {}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// A partial user update, e.g. UserUpdate::new().title("Data Engineer").department("Data").
+/// put_user needs every field, so update_user fetches the user and merges these in first.
+#[derive(Debug, Default, Clone)]
+pub struct UserUpdate {
+    fields: Vec<(String, String)>,
+}
+
+impl UserUpdate {
+    pub fn new() -> Self {
+        UserUpdate { fields: Vec::new() }
+    }
+
+    /// Sets a field by its api name, e.g. employeeNumber. The value is checked when the update is applied.
+    pub fn set(mut self, field: &str, value: &str) -> Self {
+        self.fields.retain(|(f, _)| f != field);
+        self.fields.push((String::from(field), String::from(value)));
+        self
+    }
+
+    pub fn name(self, name: &str) -> Self {
+        self.set("name", name)
+    }
+
+    pub fn email(self, email: &str) -> Self {
+        self.set("email", email)
+    }
+
+    pub fn alternate_email(self, email: &str) -> Self {
+        self.set("alternateEmail", email)
+    }
+
+    pub fn employee_id(self, employee_id: &str) -> Self {
+        self.set("employeeId", employee_id)
+    }
+
+    pub fn employee_number(self, employee_number: u64) -> Self {
+        self.set("employeeNumber", &employee_number.to_string())
+    }
+
+    pub fn title(self, title: &str) -> Self {
+        self.set("title", title)
+    }
+
+    pub fn phone(self, phone: &str) -> Self {
+        self.set("phone", phone)
+    }
+
+    pub fn location(self, location: &str) -> Self {
+        self.set("location", location)
+    }
+
+    pub fn department(self, department: &str) -> Self {
+        self.set("department", department)
+    }
+
+    pub fn timezone(self, timezone: &str) -> Self {
+        self.set("timezone", timezone)
+    }
+
+    pub fn locale(self, locale: &str) -> Self {
+        self.set("locale", locale)
+    }
+
+    pub fn role(self, role: Role) -> Self {
+        self.set("role", &String::from(role))
+    }
+
+    pub fn role_id(self, role_id: u64) -> Self {
+        self.set("roleId", &role_id.to_string())
+    }
+
+    /// True when no fields have been set
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The user with this update merged in, and what changed
+    pub fn plan(
+        &self,
+        user: &User,
+    ) -> Result<(User, Vec<UserFieldChange>), Box<dyn Error + Send + Sync + 'static>> {
+        merge_user(user, &self.fields)
+    }
+}
+
+/// Merges only the given fields into a copy of the user and reports which of them changed.
+///
+/// Fields are named as in the api (e.g. employeeNumber). Values are taken as strings unless the field needs a number or bool.
+/// Empty values are skipped so a blank csv cell leaves the field alone.
+pub fn merge_user(
+    user: &User,
+    fields: &[(String, String)],
+) -> Result<(User, Vec<UserFieldChange>), Box<dyn Error + Send + Sync + 'static>> {
+    let mut map = match serde_json::to_value(user)? {
+        Value::Object(map) => map,
+        _ => unreachable!("a user always serializes to an object"),
+    };
+    let mut changes = Vec::new();
+    for (field, value) in fields {
+        if value.is_empty() {
+            continue;
+        }
+        let from = map
+            .get(field)
+            .cloned()
+            .ok_or_else(|| format!("unknown user field {}", field))?;
+        //Try the value as a string first, then as a json literal for numeric and bool fields
+        let mut to = Value::String(value.clone());
+        map.insert(field.clone(), to.clone());
+        if serde_json::from_value::<User>(Value::Object(map.clone())).is_err() {
+            to = serde_json::from_str(value)
+                .map_err(|_| format!("invalid value {} for user field {}", value, field))?;
+            map.insert(field.clone(), to.clone());
+            serde_json::from_value::<User>(Value::Object(map.clone()))
+                .map_err(|_| format!("invalid value {} for user field {}", value, field))?;
+        }
+        if from != to {
+            changes.push(UserFieldChange {
+                field: field.clone(),
+                from,
+                to,
+            });
+        }
+    }
+    Ok((serde_json::from_value(Value::Object(map))?, changes))
+}
+
 /// User API methods
 /// Uses the form method_object
 impl super::Client {
@@ -121,6 +401,15 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns every user, paging through get_users
+    pub async fn get_users_all(&self) -> Result<Vec<User>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(500, |limit, offset| {
+            self.get_users(Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
     /// Fetch users by email in bulk
     pub async fn post_bulk_user_emails(
         &self,
@@ -138,13 +427,56 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Looks up a single user by email, returning None if no user has that email
+    pub async fn get_user_by_email(
+        &self,
+        email: &str,
+    ) -> Result<Option<User>, Box<dyn Error + Send + Sync + 'static>> {
+        let users = self.post_bulk_user_emails(&[String::from(email)]).await?;
+        Ok(users.into_iter().find(|u| {
+            u.email
+                .as_deref()
+                .is_some_and(|e| e.eq_ignore_ascii_case(email))
+        }))
+    }
+
+    /// Lists the roles defined in the instance, including custom roles
+    pub async fn get_roles(
+        &self,
+    ) -> Result<Vec<RoleDefinition>, Box<dyn Error + Send + Sync + 'static>> {
+        let at = self.get_access_token("user").await?;
+        let mut response = surf::get(format!("{}{}", self.host, "/v1/roles"))
+            .header("Authorization", at)
+            .await?;
+        if !response.status().is_success() {
+            let e: Box<super::PubAPIError> = response.body_json().await?;
+            return Err(e);
+        }
+        Ok(response.body_json().await?)
+    }
+
+    /// Finds a role by name, ignoring case, so a role name can be turned into a role_id
+    pub async fn get_role_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<RoleDefinition>, Box<dyn Error + Send + Sync + 'static>> {
+        Ok(self.get_roles().await?.into_iter().find(|r| {
+            r.name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        }))
+    }
+
     /// Creates a new user in your Domo instance.
     ///
+    /// The timezone and locale are checked first, see validate_user.
+    ///
     /// TODO param sendInvite=true
     pub async fn post_user(
         &self,
         user: User,
     ) -> Result<User, Box<dyn Error + Send + Sync + 'static>> {
+        validate_user(&user)?;
         let at = self.get_access_token("user").await?;
         let mut response = surf::post(&format!("{}{}", self.host, "/v1/users"))
             .header("Authorization", at)
@@ -160,7 +492,10 @@ impl super::Client {
     /// Retrieves the details of an existing user.
     ///
     /// Returns a user object if valid user ID was provided. When requesting, if the user ID is related to a user that has been deleted, a subset of the user information will be returned, including a deleted property, which will be true.
-    pub async fn get_user(&self, id: &str) -> Result<User, Box<dyn Error + Send + Sync + 'static>> {
+    pub async fn get_user(
+        &self,
+        id: UserId,
+    ) -> Result<User, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
         let mut response = surf::get(&format!("{}{}{}", self.host, "/v1/users/", id))
             .header("Authorization", at)
@@ -174,11 +509,14 @@ impl super::Client {
 
     /// Updates the specified user by providing values to parameters passed. Any parameter left out of the request will cause the specific user’s attribute to remain unchanged
     /// Currently all user fields are required
+    ///
+    /// The timezone and locale are checked first, see validate_user.
     pub async fn put_user(
         &self,
-        id: &str,
+        id: UserId,
         user: User,
     ) -> Result<User, Box<dyn Error + Send + Sync + 'static>> {
+        validate_user(&user)?;
         let at = self.get_access_token("user").await?;
         let mut response = surf::put(&format!("{}{}{}", self.host, "/v1/users/", id))
             .header("Authorization", at)
@@ -191,11 +529,136 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Applies a partial update: fetches the user, merges in the fields set on the update, and PUTs the result.
+    /// Nothing is sent when the update does not change anything.
+    pub async fn update_user(
+        &self,
+        id: UserId,
+        update: &UserUpdate,
+    ) -> Result<User, Box<dyn Error + Send + Sync + 'static>> {
+        let current = self.get_user(id).await?;
+        let (user, changes) = update.plan(&current)?;
+        if changes.is_empty() {
+            return Ok(current);
+        }
+        self.put_user(id, user).await
+    }
+
+    /// Plans a bulk update from a csv file with an id column and one column per field to change.
+    ///
+    /// put_user replaces the whole user, so each user is fetched and only the supplied columns are merged in (see merge_user).
+    /// Nothing is written; pass the plans to apply_user_updates, or print them for a dry run.
+    pub async fn plan_user_updates(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<UserUpdatePlan>, Box<dyn Error + Send + Sync + 'static>> {
+        let mut rdr = csv::Reader::from_path(path)?;
+        let headers = rdr.headers()?.clone();
+        let id_col = headers
+            .iter()
+            .position(|h| h == "id")
+            .ok_or("the csv needs an id column")?;
+        let mut plans = Vec::new();
+        for record in rdr.records() {
+            let record = record?;
+            let id: UserId = record[id_col]
+                .parse()
+                .map_err(|_| format!("invalid user id {}", &record[id_col]))?;
+            let fields: Vec<(String, String)> = headers
+                .iter()
+                .zip(record.iter())
+                .filter(|(h, _)| *h != "id")
+                .map(|(h, v)| (String::from(h), String::from(v)))
+                .collect();
+            let current = self.get_user(id).await?;
+            let (user, changes) =
+                merge_user(&current, &fields).map_err(|e| format!("user {}: {}", id, e))?;
+            plans.push(UserUpdatePlan { id, changes, user });
+        }
+        Ok(plans)
+    }
+
+    /// PUTs every planned update that changes something and returns the updated users
+    pub async fn apply_user_updates(
+        &self,
+        plans: Vec<UserUpdatePlan>,
+    ) -> Result<Vec<User>, Box<dyn Error + Send + Sync + 'static>> {
+        let mut r = Vec::new();
+        for plan in plans.into_iter().filter(|p| !p.is_empty()) {
+            r.push(self.put_user(plan.id, plan.user).await?);
+        }
+        Ok(r)
+    }
+
+    /// Finds the datasets and pages a user owns and the groups they belong to, without changing anything.
+    ///
+    /// This reads every dataset, page, and group in the instance, so it can take a while on large instances.
+    pub async fn plan_user_offboard(
+        &self,
+        user_id: UserId,
+        reassign_to: Option<UserId>,
+    ) -> Result<OffboardPlan, Box<dyn Error + Send + Sync + 'static>> {
+        let datasets = self
+            .get_datasets_all()
+            .await?
+            .into_iter()
+            .filter(|d| d.owner.as_ref().is_some_and(|o| o.id == user_id))
+            .filter_map(|d| d.id)
+            .collect();
+
+        //The page list only has ids and children, so each page is fetched to check its owner
+        let mut pending: Vec<Page> = self.get_pages_all().await?;
+        let mut pages = Vec::new();
+        while let Some(p) = pending.pop() {
+            pending.extend(p.children.unwrap_or_default());
+            if let Some(id) = p.id {
+                if self.get_page(id).await?.owner_id == Some(user_id) {
+                    pages.push(id);
+                }
+            }
+        }
+        pages.sort_unstable();
+
+        let groups = self
+            .get_user_groups(user_id)
+            .await?
+            .iter()
+            .filter_map(|g| g.id())
+            .collect();
+        Ok(OffboardPlan {
+            user_id,
+            reassign_to,
+            datasets,
+            pages,
+            groups,
+        })
+    }
+
+    /// Carries out an offboarding plan: reassigns owned content when the plan says to, removes every group membership,
+    /// and only then deletes the user. Stops at the first failure so the user is never deleted half way.
+    pub async fn offboard_user(
+        &self,
+        plan: &OffboardPlan,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        if let Some(new_owner) = plan.reassign_to {
+            for id in &plan.datasets {
+                self.set_dataset_owner(id, new_owner).await?;
+            }
+            for id in &plan.pages {
+                self.set_page_owner(*id, new_owner, false).await?;
+            }
+        }
+        for id in &plan.groups {
+            self.delete_group_user(*id, plan.user_id).await?;
+        }
+        self.delete_user(plan.user_id).await
+    }
+
     /// Permanently deletes a user from your Domo instance
     /// This is destructive and cannot be reversed.
     pub async fn delete_user(
         &self,
-        id: &str,
+        id: UserId,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("user").await?;
         let mut response = surf::delete(&format!("{}{}{}", self.host, "/v1/users/", id))
@@ -207,4 +670,16 @@ impl super::Client {
         }
         Ok(response.body_json().await?)
     }
+
+    /// Permanently deletes many users, a few at a time.
+    /// Every user is attempted; if any fail a BulkError lists them.
+    pub async fn delete_users(
+        &self,
+        ids: &[UserId],
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        bulk(ids, USER_DELETE_CONCURRENCY, "user deletes", |id| {
+            self.delete_user(id)
+        })
+        .await
+    }
 }
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::{merge_user, User, UserUpdatePlan};
+use crate::public::Client;
+
+/// The changes needed to make the Domo user list match an HR roster, built by plan_reconcile
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcilePlan {
+    /// Roster entries with no Domo user
+    pub create: Vec<User>,
+
+    /// Domo users whose fields differ from the roster
+    pub update: Vec<UserUpdatePlan>,
+
+    /// Domo users that are not on the roster
+    pub deactivate: Vec<User>,
+}
+
+impl ReconcilePlan {
+    /// True when Domo already matches the roster
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.deactivate.is_empty()
+    }
+}
+
+impl std::fmt::Display for ReconcilePlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "Domo users match the roster");
+        }
+        let mut lines = Vec::new();
+        for u in &self.create {
+            lines.push(format!("+ create {}", u.email.as_deref().unwrap_or("")));
+        }
+        for p in &self.update {
+            lines.push(p.to_string());
+        }
+        for u in &self.deactivate {
+            lines.push(format!(
+                "- deactivate {} ({})",
+                u.email.as_deref().unwrap_or(""),
+                u.id.unwrap_or_default()
+            ));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Client {
+    /// Compares every Domo user with a roster csv keyed by email and plans what to create, update, and deactivate.
+    ///
+    /// The roster needs an email column; every other column is a user field named as in the api (e.g. title, department).
+    /// Blank cells leave the field alone. Emails are matched ignoring case. Nothing is changed.
+    pub async fn plan_reconcile(
+        &self,
+        roster: impl AsRef<Path>,
+    ) -> Result<ReconcilePlan, Box<dyn Error + Send + Sync + 'static>> {
+        let mut rdr = csv::Reader::from_path(roster)?;
+        let headers = rdr.headers()?.clone();
+        let email_col = headers
+            .iter()
+            .position(|h| h == "email")
+            .ok_or("the roster needs an email column")?;
+
+        let mut existing: HashMap<String, User> = self
+            .get_users_all()
+            .await?
+            .into_iter()
+            .filter_map(|u| Some((u.email.clone()?.to_lowercase(), u)))
+            .collect();
+
+        let mut plan = ReconcilePlan::default();
+        for record in rdr.records() {
+            let record = record?;
+            let email = record[email_col].trim().to_lowercase();
+            if email.is_empty() {
+                continue;
+            }
+            let fields: Vec<(String, String)> = headers
+                .iter()
+                .zip(record.iter())
+                .map(|(h, v)| (String::from(h), String::from(v.trim())))
+                .collect();
+            match existing.remove(&email) {
+                Some(current) => {
+                    //The email already matched, so a difference in case alone is not an update
+                    let fields: Vec<(String, String)> =
+                        fields.into_iter().filter(|(h, _)| h != "email").collect();
+                    let (user, changes) = merge_user(&current, &fields)
+                        .map_err(|e| format!("roster entry {}: {}", email, e))?;
+                    if !changes.is_empty() {
+                        let id = current.id.ok_or("user without an id")?;
+                        plan.update.push(UserUpdatePlan { id, changes, user });
+                    }
+                }
+                None => {
+                    let (user, _) = merge_user(&User::new(), &fields)
+                        .map_err(|e| format!("roster entry {}: {}", email, e))?;
+                    plan.create.push(user);
+                }
+            }
+        }
+        plan.deactivate = existing.into_values().collect();
+        plan.deactivate.sort_by_key(|u| u.id);
+        Ok(plan)
+    }
+
+    /// Carries out a reconcile plan: creates and updates users, then deactivates the ones missing from the roster.
+    ///
+    /// The public API has no way to deactivate a user, so deactivating deletes them, which can't be undone.
+    /// Deletions only happen when delete_missing is true; otherwise those users are left alone.
+    pub async fn apply_reconcile(
+        &self,
+        plan: ReconcilePlan,
+        delete_missing: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        for user in plan.create {
+            self.post_user(user).await?;
+        }
+        self.apply_user_updates(plan.update).await?;
+        if delete_missing {
+            for user in plan.deactivate {
+                if let Some(id) = user.id {
+                    self.delete_user(id).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use chrono_tz::{Tz, TZ_VARIANTS};
+use language_tags::LanguageTag;
+
+use super::User;
+
+/// Locales offered as near matches when a locale is not a valid BCP-47 tag
+const COMMON_LOCALES: &[&str] = &[
+    "en-US", "en-GB", "en-CA", "en-AU", "en-IN", "de-DE", "de-AT", "de-CH", "fr-FR", "fr-CA",
+    "es-ES", "es-MX", "es-US", "it-IT", "nl-NL", "pt-BR", "pt-PT", "sv-SE", "da-DK", "nb-NO",
+    "fi-FI", "pl-PL", "ru-RU", "tr-TR", "ja-JP", "ko-KR", "zh-CN", "zh-TW", "he-IL", "ar-SA",
+];
+
+/// A user field with a value Domo would reject or silently misread
+#[derive(Debug)]
+pub struct InvalidUserField {
+    pub field: String,
+    pub value: String,
+    /// Valid values close to the given one, best first
+    pub suggestions: Vec<String>,
+}
+
+impl std::error::Error for InvalidUserField {}
+impl std::fmt::Display for InvalidUserField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid {} {:?}", self.field, self.value)?;
+        if !self.suggestions.is_empty() {
+            write!(f, ", did you mean {}?", self.suggestions.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the timezone is an IANA tz database name and the locale a BCP-47 tag.
+/// Unset fields are not checked.
+pub fn validate_user(user: &User) -> Result<(), InvalidUserField> {
+    if let Some(tz) = user.timezone.as_deref() {
+        if tz.parse::<Tz>().is_err() {
+            return Err(InvalidUserField {
+                field: String::from("timezone"),
+                value: String::from(tz),
+                suggestions: near_matches(tz, TZ_VARIANTS.iter().map(|t| t.name())),
+            });
+        }
+    }
+    if let Some(locale) = user.locale.as_deref() {
+        if !LanguageTag::parse(locale).is_ok_and(|t| t.is_valid()) {
+            return Err(InvalidUserField {
+                field: String::from("locale"),
+                value: String::from(locale),
+                suggestions: near_matches(locale, COMMON_LOCALES.iter().copied()),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The closest few candidates, comparing case insensitively and treating spaces as underscores and
+/// underscores as dashes. A timezone also matches on its city, so "los angeles" finds America/Los_Angeles.
+fn near_matches<'a>(value: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let norm = |s: &str| s.to_lowercase().replace(' ', "_");
+    let value = norm(value);
+    let dashed = value.replace('_', "-");
+    let mut scored: Vec<(f64, &str)> = candidates
+        .map(|c| {
+            let n = norm(c);
+            let city = n.rsplit('/').next().unwrap_or(&n).to_string();
+            let score = [&n, &city]
+                .iter()
+                .flat_map(|t| {
+                    [
+                        strsim::jaro_winkler(&value, t),
+                        strsim::jaro_winkler(&dashed, t),
+                    ]
+                })
+                .fold(0.0, f64::max);
+            (score, c)
+        })
+        .filter(|(score, _)| *score >= 0.85)
+        .collect();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    scored
+        .into_iter()
+        .take(3)
+        .map(|(_, c)| String::from(c))
+        .collect()
+}
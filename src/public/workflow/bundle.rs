@@ -0,0 +1,212 @@
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{List, ListType, Project, Task};
+use crate::public::{Client, UserId};
+
+/// A project with its members, lists and tasks as written by `domo workflow export`.
+/// Ids are left out and users are written as emails, so the file can be imported into another instance.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProjectBundle {
+    pub name: String,
+    pub description: Option<String>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub public: Option<bool>,
+    pub members: Vec<String>,
+    pub lists: Vec<ListBundle>,
+}
+
+/// One list of a ProjectBundle with the tasks in it
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ListBundle {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub list_type: Option<ListType>,
+    pub index: u32,
+    pub tasks: Vec<TaskBundle>,
+}
+
+/// One task of a ListBundle, with its owner and contributors as emails
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TaskBundle {
+    pub task_name: Option<String>,
+    pub description: Option<String>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub priority: Option<u32>,
+    pub owner: Option<String>,
+    pub contributors: Vec<String>,
+    pub tags: Vec<String>,
+    pub archived: bool,
+}
+
+/// What import_project did
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectImport {
+    /// The project created, with its new id
+    pub project: Project,
+
+    /// How many tasks were created
+    pub tasks: usize,
+
+    /// Emails in the bundle with no user in this instance, left off the members, owners and contributors
+    pub unmatched_users: Vec<String>,
+}
+
+impl Client {
+    /// Reads a project with its lists and every task, writing users as their emails.
+    /// Users that can not be found, or have no email, are left out.
+    pub async fn export_project(
+        &self,
+        project_id: &str,
+    ) -> Result<ProjectBundle, Box<dyn Error + Send + Sync + 'static>> {
+        let emails: HashMap<UserId, String> = self
+            .get_users_all()
+            .await?
+            .into_iter()
+            .filter_map(|u| Some((u.id?, u.email?)))
+            .collect();
+        let email = |id: &UserId| emails.get(id).cloned();
+
+        let project = self.get_project(project_id).await?;
+        let mut lists = self.get_project_lists(project_id).await?;
+        lists.sort_by_key(|l| l.index);
+        let mut tasks = self.get_project_tasks_all(project_id).await?;
+        let lists = lists
+            .into_iter()
+            .map(|l| {
+                let (in_list, rest) = tasks
+                    .drain(..)
+                    .partition(|t| l.id.is_some() && t.project_list_id == l.id);
+                tasks = rest;
+                ListBundle {
+                    name: l.name,
+                    list_type: l.list_type,
+                    index: l.index,
+                    tasks: in_list
+                        .into_iter()
+                        .map(|t: Task| TaskBundle {
+                            task_name: t.task_name,
+                            description: t.description,
+                            due_date: t.due_date,
+                            priority: t.priority,
+                            owner: t.owned_by.as_ref().and_then(email),
+                            contributors: t
+                                .contributors
+                                .unwrap_or_default()
+                                .iter()
+                                .filter_map(email)
+                                .collect(),
+                            tags: t.tags.unwrap_or_default(),
+                            archived: t.archived,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        Ok(ProjectBundle {
+            name: project.name.unwrap_or_default(),
+            description: project.description,
+            due_date: project.due_date,
+            public: project.public,
+            members: project
+                .members
+                .unwrap_or_default()
+                .iter()
+                .filter_map(email)
+                .collect(),
+            lists,
+        })
+    }
+
+    /// Creates a project from a bundle, with its lists and tasks, matching users by email.
+    ///
+    /// Lists Domo creates with the new project are reused when their name and type match, and deleted
+    /// otherwise, as in clone_project. Archived tasks are created and then archived. Emails without a user
+    /// here are reported rather than failing the import. If any step after creating the project fails,
+    /// the new project is deleted before the error is returned.
+    pub async fn import_project(
+        &self,
+        bundle: ProjectBundle,
+    ) -> Result<ProjectImport, Box<dyn Error + Send + Sync + 'static>> {
+        let ids: HashMap<String, UserId> = self
+            .get_users_all()
+            .await?
+            .into_iter()
+            .filter_map(|u| Some((u.email?.to_lowercase(), u.id?)))
+            .collect();
+        let mut unmatched = BTreeSet::new();
+        let mut user_id = |email: &String| {
+            let id = ids.get(&email.to_lowercase()).copied();
+            if id.is_none() {
+                unmatched.insert(email.clone());
+            }
+            id
+        };
+
+        let mut project = Project::new();
+        project.name = Some(bundle.name);
+        project.description = bundle.description;
+        project.due_date = bundle.due_date;
+        project.public = bundle.public;
+        project.members = Some(bundle.members.iter().filter_map(&mut user_id).collect());
+        let project = self.post_project(project).await?;
+        let project_id = project.id.clone().ok_or("created project without an id")?;
+
+        let lists = bundle.lists;
+        let filled = async {
+            let mut existing = self.get_project_lists(&project_id).await?;
+            let mut created = 0;
+            for l in lists {
+                let mut wanted = List::new();
+                wanted.name = l.name;
+                wanted.list_type = l.list_type;
+                wanted.index = l.index;
+                let list = self
+                    .reuse_or_create_list(&project_id, &mut existing, &wanted)
+                    .await?;
+                let list_id = list.id.ok_or("list without an id")?.to_string();
+                for t in l.tasks {
+                    let mut task = Task::new();
+                    task.project_id = project_id.parse().ok();
+                    task.project_list_id = list.id;
+                    task.task_name = t.task_name;
+                    task.description = t.description;
+                    task.due_date = t.due_date;
+                    task.priority = t.priority;
+                    task.owned_by = t.owner.as_ref().and_then(&mut user_id);
+                    task.contributors =
+                        Some(t.contributors.iter().filter_map(&mut user_id).collect());
+                    task.tags = Some(t.tags);
+                    let task = self
+                        .post_project_list_task(&project_id, &list_id, task)
+                        .await?;
+                    if t.archived {
+                        let task_id = task.id.ok_or("created task without an id")?;
+                        self.archive_task(&project_id, &list_id, &task_id.to_string())
+                            .await?;
+                    }
+                    created += 1;
+                }
+            }
+            self.delete_unused_lists(&project_id, existing).await?;
+            Ok::<_, Box<dyn Error + Send + Sync + 'static>>(created)
+        }
+        .await;
+        let created = match filled {
+            Ok(created) => created,
+            Err(e) => return Err(self.discard_project(&project_id, e).await),
+        };
+        Ok(ProjectImport {
+            project,
+            tasks: created,
+            unmatched_users: unmatched.into_iter().collect(),
+        })
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use super::{List, ListType, Project, Task};
+use crate::public::Client;
+
+impl Client {
+    /// Creates a new project from an existing one, with the same description, members and lists
+    /// in the same order. With include_tasks, the open tasks are copied too, with their owners,
+    /// contributors, tags, priorities and due dates; archived tasks and tasks in COMPLETED lists are left behind.
+    ///
+    /// Lists Domo creates with the new project are reused when their name and type match a source list,
+    /// and deleted when they match none, so the clone has the source's lists and no others.
+    /// If any step after creating the project fails, the new project is deleted before the error is returned.
+    pub async fn clone_project(
+        &self,
+        source_id: &str,
+        new_name: &str,
+        include_tasks: bool,
+    ) -> Result<Project, Box<dyn Error + Send + Sync + 'static>> {
+        let source = self.get_project(source_id).await?;
+        let mut project = Project::new();
+        project.name = Some(String::from(new_name));
+        project.description = source.description;
+        project.public = source.public;
+        project.members = source.members;
+        let project = self.post_project(project).await?;
+        let project_id = project.id.clone().ok_or("created project without an id")?;
+        match self
+            .copy_project_contents(source_id, &project_id, include_tasks)
+            .await
+        {
+            Ok(()) => Ok(project),
+            Err(e) => Err(self.discard_project(&project_id, e).await),
+        }
+    }
+
+    /// Copies the lists, and optionally the open tasks, of source_id into the new project project_id
+    async fn copy_project_contents(
+        &self,
+        source_id: &str,
+        project_id: &str,
+        include_tasks: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let mut source_lists = self.get_project_lists(source_id).await?;
+        source_lists.sort_by_key(|l| l.index);
+        let mut existing = self.get_project_lists(project_id).await?;
+        // Source list id to the id of its copy
+        let mut list_ids: HashMap<u64, u64> = HashMap::new();
+        for source_list in &source_lists {
+            let source_list_id = match source_list.id {
+                Some(id) => id,
+                None => continue,
+            };
+            let copy = self
+                .reuse_or_create_list(project_id, &mut existing, source_list)
+                .await?;
+            list_ids.insert(source_list_id, copy.id.ok_or("list without an id")?);
+        }
+        self.delete_unused_lists(project_id, existing).await?;
+
+        if include_tasks {
+            let completed: Vec<u64> = source_lists
+                .iter()
+                .filter(|l| l.list_type == Some(ListType::Completed))
+                .filter_map(|l| l.id)
+                .collect();
+            for task in self.get_project_tasks_all(source_id).await? {
+                let list_id = match task.project_list_id {
+                    Some(id) if !task.archived && !completed.contains(&id) => id,
+                    _ => continue,
+                };
+                let new_list_id = match list_ids.get(&list_id) {
+                    Some(id) => *id,
+                    None => continue,
+                };
+                let mut copy = Task::new();
+                copy.project_id = project_id.parse().ok();
+                copy.project_list_id = Some(new_list_id);
+                copy.task_name = task.task_name;
+                copy.description = task.description;
+                copy.due_date = task.due_date;
+                copy.priority = task.priority;
+                copy.owned_by = task.owned_by;
+                copy.contributors = task.contributors;
+                copy.tags = task.tags;
+                self.post_project_list_task(project_id, &new_list_id.to_string(), copy)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes the lists Domo created with a new project that reuse_or_create_list did not take
+    pub(super) async fn delete_unused_lists(
+        &self,
+        project_id: &str,
+        unused: Vec<List>,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        for list in unused {
+            if let Some(list_id) = list.id {
+                self.delete_project_list(project_id, &list_id.to_string())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes a project that could not be finished, returning the error that stopped it.
+    /// When the delete fails too, the error says so, as the partly built project is left behind.
+    pub(super) async fn discard_project(
+        &self,
+        project_id: &str,
+        error: Box<dyn Error + Send + Sync + 'static>,
+    ) -> Box<dyn Error + Send + Sync + 'static> {
+        match self.delete_project(project_id).await {
+            Ok(()) => error,
+            Err(e) => format!(
+                "{}; the partly built project {} could not be deleted: {}",
+                error, project_id, e
+            )
+            .into(),
+        }
+    }
+
+    /// Takes the list in existing with the same name and type as wanted, moving it to wanted's index,
+    /// or creates one in the project when there is none
+    pub(super) async fn reuse_or_create_list(
+        &self,
+        project_id: &str,
+        existing: &mut Vec<List>,
+        wanted: &List,
+    ) -> Result<List, Box<dyn Error + Send + Sync + 'static>> {
+        let reused = existing
+            .iter()
+            .position(|l| l.name == wanted.name && l.list_type == wanted.list_type);
+        match reused {
+            Some(i) => {
+                let mut list = existing.remove(i);
+                let list_id = list.id.ok_or("list without an id")?;
+                if list.index != wanted.index {
+                    list.index = wanted.index;
+                    list = self
+                        .put_project_list(project_id, &list_id.to_string(), list)
+                        .await?;
+                }
+                Ok(list)
+            }
+            None => {
+                let mut list = List::new();
+                list.name = wanted.name.clone();
+                list.list_type = wanted.list_type.clone();
+                list.index = wanted.index;
+                self.post_project_list(project_id, list).await
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+
+use super::Task;
+use crate::public::{Client, UserId};
+
+/// Narrows the tasks returned by get_project_tasks_filtered.
+/// The workflow api has no task search, so every part is checked on the client.
+#[derive(Debug, Default, Clone)]
+pub struct TaskFilter {
+    /// Only tasks owned by this user
+    pub owned_by: Option<UserId>,
+
+    /// Only tasks with every one of these tags, ignoring case. Empty allows any tags.
+    pub tags: Vec<String>,
+
+    /// Only tasks due before this time. Tasks without a due date are left out.
+    pub due_before: Option<DateTime<Utc>>,
+
+    /// Only archived tasks when true, or only tasks that are not archived when false
+    pub archived: Option<bool>,
+}
+
+impl TaskFilter {
+    /// True when the task passes every part of the filter
+    pub fn matches(&self, task: &Task) -> bool {
+        if self.owned_by.is_some() && task.owned_by != self.owned_by {
+            return false;
+        }
+        if !self.tags.iter().all(|wanted| {
+            task.tags
+                .as_ref()
+                .is_some_and(|tags| tags.iter().any(|t| t.eq_ignore_ascii_case(wanted)))
+        }) {
+            return false;
+        }
+        if let Some(before) = self.due_before {
+            if task.due_date.is_none_or(|due| due >= before) {
+                return false;
+            }
+        }
+        if self
+            .archived
+            .is_some_and(|archived| task.archived != archived)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+impl Client {
+    /// Returns the tasks in a project that match the filter, reading every task first
+    pub async fn get_project_tasks_filtered(
+        &self,
+        project_id: &str,
+        filter: &TaskFilter,
+    ) -> Result<Vec<Task>, Box<dyn Error + Send + Sync + 'static>> {
+        let tasks = self.get_project_tasks_all(project_id).await?;
+        Ok(tasks.into_iter().filter(|t| filter.matches(t)).collect())
+    }
+}
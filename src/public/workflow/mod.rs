@@ -2,6 +2,16 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::{error::Error, path::PathBuf};
 
+use super::UserId;
+
+pub mod bundle;
+pub use bundle::{ProjectBundle, ProjectImport};
+
+mod clone;
+
+mod filter;
+pub use filter::TaskFilter;
+
 /// “Projects and Tasks” is a project management tool that helps you take real action with simple planning, assigning, and task-tracking features. You can create projects with various tasks and assignments. Those tasks exist within swim lanes or lists, and can be moved from list to list to show progress through a particular workflow. You can use default lists or create new custom lists. You can also add attachments to individual tasks to reference relevant materials and other artifacts.
 /// Note: You will need to ensure that your client application has access to the Workflow scope in order to access the Projects and Tasks endpoints.
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -17,7 +27,7 @@ pub struct Project {
     pub description: Option<String>,
 
     /// The ID of the user who created the project
-    pub created_by: Option<u64>,
+    pub created_by: Option<UserId>,
 
     /// Date the project was created
     pub created_date: Option<DateTime<Utc>>,
@@ -29,7 +39,7 @@ pub struct Project {
     pub public: Option<bool>,
 
     /// The ID's of the members of the project
-    pub members: Option<Vec<u64>>,
+    pub members: Option<Vec<UserId>>,
 }
 
 impl Project {
@@ -51,11 +61,11 @@ impl Project {
             id: Some(String::from("0")),
             name: Some(String::from("Project Name")),
             description: Some(String::from("Project Description")),
-            created_by: Some(12345),
+            created_by: Some(UserId(12345)),
             created_date: Some(Utc::now()),
             due_date: Some(Utc::now()),
             public: Some(true),
-            members: Some(vec![0, 1, 2, 3]),
+            members: Some(vec![UserId(0), UserId(1), UserId(2), UserId(3)]),
         }
     }
 }
@@ -72,7 +82,7 @@ pub struct List {
 
     /// The type of List (i.e. TODO, WORKING_ON, COMPLETED)
     #[serde(rename = "type")]
-    pub list_type: Option<String>,
+    pub list_type: Option<ListType>,
 
     /// The ordered index of the list within the project.
     pub index: u32,
@@ -91,10 +101,50 @@ impl List {
         List {
             id: Some(0),
             name: Some(String::from("List Name")),
-            list_type: Some(String::from("")),
+            list_type: Some(ListType::Todo),
             index: 0,
         }
     }
+
+    /// Checks that the list type is one Domo accepts: a known type, or a custom one of letters, digits,
+    /// underscores and dashes. Custom types are not changed, so one read from Domo passes as it was sent.
+    pub fn validate(&self) -> Result<(), InvalidList> {
+        match &self.list_type {
+            Some(ListType::Other(t))
+                if t.is_empty()
+                    || !t
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') =>
+            {
+                Err(InvalidList(format!(
+                    "list type {:?} is not TODO, WORKING_ON, COMPLETED or a custom type of letters, digits, _ and -",
+                    t
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+string_enum!(
+    /// The kind of a project list, which decides how Domo treats the tasks in it.
+    /// Custom types are kept as Other.
+    ListType {
+        Todo => "TODO",
+        WorkingOn => "WORKING_ON",
+        Completed => "COMPLETED",
+    }
+);
+
+/// A list that Domo would reject
+#[derive(Debug)]
+pub struct InvalidList(String);
+
+impl std::error::Error for InvalidList {}
+impl std::fmt::Display for InvalidList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid list: {}", self.0)
+    }
 }
 
 /// The task object
@@ -126,13 +176,13 @@ pub struct Task {
     pub priority: Option<u32>,
 
     /// The ID of the Domo user that created the task
-    pub created_by: Option<u64>,
+    pub created_by: Option<UserId>,
 
     /// The ID of the Domo user that owns the task
-    pub owned_by: Option<u64>,
+    pub owned_by: Option<UserId>,
 
     /// An array of user IDs that are assigned as contributors to the task
-    pub contributors: Option<Vec<u64>>,
+    pub contributors: Option<Vec<UserId>>,
 
     /// The number of attachments that task has
     pub attachment_count: Option<u32>,
@@ -173,9 +223,9 @@ impl Task {
             created_date: Some(Utc::now()),
             due_date: Some(Utc::now()),
             priority: Some(0),
-            created_by: Some(27),
-            owned_by: Some(27),
-            contributors: Some(vec![0, 1, 2, 3]),
+            created_by: Some(UserId(27)),
+            owned_by: Some(UserId(27)),
+            contributors: Some(vec![UserId(0), UserId(1), UserId(2), UserId(3)]),
             attachment_count: Some(0),
             tags: Some(vec![
                 String::from("A"),
@@ -320,7 +370,7 @@ impl super::Client {
     pub async fn get_project_members(
         &self,
         id: &str,
-    ) -> Result<Vec<u64>, Box<dyn Error + Send + Sync + 'static>> {
+    ) -> Result<Vec<UserId>, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("workflow").await?;
         let mut response = surf::get(&format!(
             "{}{}{}{}",
@@ -336,11 +386,12 @@ impl super::Client {
     }
 
     /// Update the members of a given project id.
+    /// Returns the ids of the project's members.
     pub async fn put_project_members(
         &self,
         id: &str,
-        members: Vec<u64>,
-    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        members: Vec<UserId>,
+    ) -> Result<Vec<UserId>, Box<dyn Error + Send + Sync + 'static>> {
         let at = self.get_access_token("workflow").await?;
         let mut response = surf::put(&format!(
             "{}{}{}{}",
@@ -388,6 +439,7 @@ impl super::Client {
         project_id: &str,
         list: List,
     ) -> Result<List, Box<dyn Error + Send + Sync + 'static>> {
+        list.validate()?;
         let at = self.get_access_token("workflow").await?;
         let mut response = surf::post(&format!(
             "{}{}{}{}",
@@ -437,6 +489,7 @@ impl super::Client {
         list_id: &str,
         list: List,
     ) -> Result<List, Box<dyn Error + Send + Sync + 'static>> {
+        list.validate()?;
         let at = self.get_access_token("workflow").await?;
         let mut response = surf::put(&format!(
             "{}{}{}{}{}",
@@ -499,6 +552,18 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns every task in a project, paging through get_project_tasks
+    pub async fn get_project_tasks_all(
+        &self,
+        id: &str,
+    ) -> Result<Vec<Task>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(50, |limit, offset| {
+            self.get_project_tasks(id, Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
     /// Retrieves all tasks from a given project id and list id
     ///
     /// limit: The maximum amount of results to return (defaults to 10 with a maximum of 50)
@@ -526,6 +591,19 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Returns every task in a project list, paging through get_project_list_tasks
+    pub async fn get_project_list_tasks_all(
+        &self,
+        project_id: &str,
+        list_id: &str,
+    ) -> Result<Vec<Task>, Box<dyn Error + Send + Sync + 'static>> {
+        super::Paginator::new(50, |limit, offset| {
+            self.get_project_list_tasks(project_id, list_id, Some(limit), Some(offset))
+        })
+        .all()
+        .await
+    }
+
     /// Add a task to a project list.
     ///
     /// Required attributes:
@@ -608,7 +686,7 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
-    /// TODO May just need to set the archived flag
+    /// Permanently deletes a task. archive_task hides a task while keeping its history.
     pub async fn delete_project_list_task(
         &self,
         project_id: &str,
@@ -629,6 +707,44 @@ impl super::Client {
         Ok(response.body_json().await?)
     }
 
+    /// Archives a task, hiding it from the list without deleting it
+    pub async fn archive_task(
+        &self,
+        project_id: &str,
+        list_id: &str,
+        task_id: &str,
+    ) -> Result<Task, Box<dyn Error + Send + Sync + 'static>> {
+        self.set_task_archived(project_id, list_id, task_id, true)
+            .await
+    }
+
+    /// Restores an archived task to its list
+    pub async fn unarchive_task(
+        &self,
+        project_id: &str,
+        list_id: &str,
+        task_id: &str,
+    ) -> Result<Task, Box<dyn Error + Send + Sync + 'static>> {
+        self.set_task_archived(project_id, list_id, task_id, false)
+            .await
+    }
+
+    /// Reads the task and puts it back with the archived flag set, as the api has no partial update
+    async fn set_task_archived(
+        &self,
+        project_id: &str,
+        list_id: &str,
+        task_id: &str,
+        archived: bool,
+    ) -> Result<Task, Box<dyn Error + Send + Sync + 'static>> {
+        let mut task = self
+            .get_project_list_task(project_id, list_id, task_id)
+            .await?;
+        task.archived = archived;
+        self.put_project_list_task(project_id, list_id, task_id, task)
+            .await
+    }
+
     /// Retrieve details about all of the attachments belonging to a particular task.
     pub async fn get_project_list_task_attachments(
         &self,
@@ -1,8 +1,15 @@
 use super::util;
-use domo::public::stream::Stream;
-use domo::public::Client;
+use domo::public::stream::{
+    ManifestPart, Stream, StreamSearchQuery, StreamUploader, UploadObserver, UploadReport,
+};
+use domo::public::{Client, DataSetId, StreamId, UserId};
 
+use chrono::Utc;
+use std::collections::HashMap;
+use std::error::Error;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 use structopt::StructOpt;
 
@@ -22,29 +29,66 @@ pub enum StreamCommand {
     #[structopt(name = "list-all")]
     ListAll {},
 
-    /// Create a new stream
+    /// Create a new stream, or a stream for an existing dataset with --dataset-id
     #[structopt(name = "create")]
-    Create {},
+    Create {
+        /// Create the stream for this existing dataset instead of editing a template
+        #[structopt(long = "dataset-id")]
+        dataset_id: Option<DataSetId>,
+        /// The update method used with --dataset-id: APPEND, REPLACE, or UPSERT
+        #[structopt(long = "update-method", default_value = "REPLACE")]
+        update_method: String,
+    },
 
     /// Retrieves the details of an existing stream
     #[structopt(name = "retrieve")]
-    Retrieve { stream_id: String },
+    Retrieve { stream_id: StreamId },
 
     /// Update a stream
     #[structopt(name = "update")]
-    Update { stream_id: String },
+    Update { stream_id: StreamId },
+
+    /// Changes only the update method of a stream: APPEND, REPLACE, or UPSERT
+    #[structopt(name = "set-update-method")]
+    SetUpdateMethod {
+        stream_id: StreamId,
+        update_method: String,
+    },
+
+    /// Changes only the key column used for UPSERT updates
+    #[structopt(name = "set-key-column")]
+    SetKeyColumn {
+        stream_id: StreamId,
+        key_column_name: String,
+    },
 
     /// Deletes a Stream from your Domo instance. This does not a delete the associated DataSet.
     #[structopt(name = "delete")]
-    Delete { stream_id: String },
+    Delete { stream_id: StreamId },
 
     /// Search streams by owner id
     #[structopt(name = "search-owners")]
-    SearchOwners { owner_id: String },
+    SearchOwners { owner_id: UserId },
 
     /// Search streams by dataset id
     #[structopt(name = "search-ids")]
-    SearchDatasetId { dataset_id: String },
+    SearchDatasetId { dataset_id: DataSetId },
+
+    /// Search streams by any combination of field criteria
+    #[structopt(name = "search")]
+    Search {
+        /// A field=value criteria, for example dataSource.id=abc. Can be repeated.
+        #[structopt(
+            short = "f",
+            long = "field",
+            required = true,
+            parse(try_from_str = util::parse_key_value)
+        )]
+        fields: Vec<(String, String)>,
+        /// Match streams meeting any of the criteria instead of all of them
+        #[structopt(long = "any")]
+        any: bool,
+    },
 
     /// Returns Stream Execution objects.
     #[structopt(name = "list-executions")]
@@ -53,17 +97,17 @@ pub enum StreamCommand {
         limit: Option<u32>,
         #[structopt(short = "o", long = "offset")]
         offset: Option<u32>,
-        stream_id: String,
+        stream_id: StreamId,
     },
 
     /// When you’re ready to upload data to your DataSet via a Stream, you first tell Domo that you’re ready to start sending data by creating an Execution.
     #[structopt(name = "create-execution")]
-    CreateExecution { stream_id: String },
+    CreateExecution { stream_id: StreamId },
 
     /// Retrieves a stream execution by id
     #[structopt(name = "retrieve-execution")]
     RetrieveExecution {
-        stream_id: String,
+        stream_id: StreamId,
         execution_id: String,
     },
 
@@ -72,22 +116,92 @@ pub enum StreamCommand {
     UploadPart {
         #[structopt(parse(from_os_str))]
         file: PathBuf,
-        stream_id: String,
+        stream_id: StreamId,
         execution_id: String,
         part_id: String,
     },
 
+    /// Uploads a csv file or stdin through a new execution: splits it into parts as it is read, uploads them, and commits.
+    /// The execution is aborted if any part fails, unless --resume is used.
+    #[structopt(name = "upload")]
+    Upload {
+        stream_id: StreamId,
+        /// A csv file with a header row, a .parquet file when built with the parquet feature, or - to read csv from stdin
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// The most rows to put in each part
+        #[structopt(long = "max-rows", default_value = "1000000")]
+        max_rows: usize,
+        /// The most bytes to put in each part
+        #[structopt(long = "max-bytes", default_value = "104857600")]
+        max_bytes: usize,
+        /// How many parts to upload at the same time
+        #[structopt(short = "c", long = "concurrency", default_value = "4")]
+        concurrency: usize,
+        /// How many times to retry a failed part
+        #[structopt(long = "retries", default_value = "3")]
+        retries: u32,
+        /// Keep a manifest of uploaded parts in this file and resume from it if it already exists
+        #[structopt(long = "resume", parse(from_os_str))]
+        resume: Option<PathBuf>,
+    },
+
     /// Commits stream execution to import combined set of data parts that have been successfully uploaded.
     #[structopt(name = "commit-execution")]
     CommitExecution {
-        stream_id: String,
+        stream_id: StreamId,
+        execution_id: String,
+    },
+
+    /// Waits for a stream execution to finish with SUCCESS, ERROR, or ABORTED
+    #[structopt(name = "wait-execution")]
+    WaitExecution {
+        stream_id: StreamId,
         execution_id: String,
+        /// How many seconds to wait before giving up
+        #[structopt(long = "timeout", default_value = "3600")]
+        timeout: u64,
+    },
+
+    /// Uploads every csv file in a directory as its own part, in file name order, and commits the execution
+    #[structopt(name = "upload-dir")]
+    UploadDir {
+        stream_id: StreamId,
+        #[structopt(parse(from_os_str))]
+        dir: PathBuf,
+        /// The files have no header row
+        #[structopt(long = "no-header")]
+        no_header: bool,
+        /// How many parts to upload at once
+        #[structopt(short = "c", long = "concurrency", default_value = "4")]
+        concurrency: usize,
+        /// How many times to retry a failed part
+        #[structopt(long = "retries", default_value = "3")]
+        retries: u32,
+    },
+
+    /// Tails a stream's executions, printing state changes and the dataset row count as they happen
+    #[structopt(name = "watch")]
+    Watch {
+        stream_id: StreamId,
+        /// How many seconds to wait between polls
+        #[structopt(short = "i", long = "interval", default_value = "10")]
+        interval: u64,
+    },
+
+    /// Aborts active executions that were started more than --older-than minutes ago
+    #[structopt(name = "cleanup")]
+    Cleanup {
+        stream_id: StreamId,
+        /// How many minutes an execution may stay active before it is considered stale
+        #[structopt(long = "older-than", default_value = "60")]
+        older_than: u64,
     },
 
     /// If needed during an execution, aborts an entire Stream execution.
     #[structopt(name = "abort-execution")]
     AbortExecution {
-        stream_id: String,
+        stream_id: StreamId,
         execution_id: String,
     },
 }
@@ -99,26 +213,12 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             util::vec_obj_template_output(r, template);
         }
         StreamCommand::ListAll {} => {
-            let mut offset = 0_u32;
-            let mut r: Vec<Stream> = Vec::new();
-            loop {
-                let mut ret = dc.get_streams(Some(50), Some(offset)).await.unwrap();
-                let mut b = false;
-                if ret.len() < 50 {
-                    b = true;
-                }
-                //Either way slurp all the elements into the aggregator
-                r.append(&mut ret);
-                offset += 50;
-                if b {
-                    break;
-                }
-            }
+            let r = dc.get_streams_all().await.unwrap();
             util::vec_obj_template_output(r, template);
         }
         StreamCommand::SearchOwners { owner_id } => {
             let r = dc
-                .get_stream_search_dataset_owner_id(&owner_id)
+                .get_stream_search_dataset_owner_id(owner_id)
                 .await
                 .unwrap();
             util::vec_obj_template_output(r, template);
@@ -127,24 +227,69 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             let r = dc.get_stream_search_dataset_id(&dataset_id).await.unwrap();
             util::vec_obj_template_output(r, template);
         }
-        StreamCommand::Create {} => {
+        StreamCommand::Search { fields, any } => {
+            let mut q: Option<StreamSearchQuery> = None;
+            for (name, value) in fields {
+                let c = StreamSearchQuery::field(&name, &value);
+                q = Some(match q {
+                    None => c,
+                    Some(q) if any => q.or(c),
+                    Some(q) => q.and(c),
+                });
+            }
+            let r = dc.search_streams(q.unwrap()).await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        StreamCommand::Create {
+            dataset_id: Some(dataset_id),
+            update_method,
+        } => {
+            let r = dc
+                .post_stream_for_dataset(&dataset_id, &update_method)
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
+        StreamCommand::Create {
+            dataset_id: None, ..
+        } => {
             let r = Stream::template();
             let r = util::edit_obj(editor, r, "").unwrap();
             let r = dc.post_stream(r).await.unwrap();
             util::obj_template_output(r, template);
         }
         StreamCommand::Retrieve { stream_id } => {
-            let r = dc.get_stream(&stream_id).await.unwrap();
+            let r = dc.get_stream(stream_id).await.unwrap();
             util::obj_template_output(r, template);
         }
         StreamCommand::Update { stream_id } => {
-            let r = dc.get_stream(&stream_id).await.unwrap();
+            let r = dc.get_stream(stream_id).await.unwrap();
             let r = util::edit_obj(editor, r, "").unwrap();
-            let r = dc.patch_stream(&stream_id, r).await.unwrap();
+            let r = dc.patch_stream(stream_id, r).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        StreamCommand::SetUpdateMethod {
+            stream_id,
+            update_method,
+        } => {
+            let r = dc
+                .set_stream_update_method(stream_id, &update_method)
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
+        StreamCommand::SetKeyColumn {
+            stream_id,
+            key_column_name,
+        } => {
+            let r = dc
+                .set_stream_key_column(stream_id, &key_column_name)
+                .await
+                .unwrap();
             util::obj_template_output(r, template);
         }
         StreamCommand::Delete { stream_id } => {
-            dc.delete_stream(&stream_id).await.unwrap();
+            dc.delete_stream(stream_id).await.unwrap();
         }
         StreamCommand::ListExecutions {
             stream_id,
@@ -152,13 +297,13 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             offset,
         } => {
             let r = dc
-                .get_stream_executions(&stream_id, limit, offset)
+                .get_stream_executions(stream_id, limit, offset)
                 .await
                 .unwrap();
             util::vec_obj_template_output(r, template);
         }
         StreamCommand::CreateExecution { stream_id } => {
-            let r = dc.post_stream_execution(&stream_id).await.unwrap();
+            let r = dc.post_stream_execution(stream_id).await.unwrap();
             util::obj_template_output(r, template);
         }
         StreamCommand::RetrieveExecution {
@@ -166,7 +311,7 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             execution_id,
         } => {
             let r = dc
-                .get_stream_execution(&stream_id, &execution_id)
+                .get_stream_execution(stream_id, &execution_id)
                 .await
                 .unwrap();
             util::obj_template_output(r, template);
@@ -177,27 +322,204 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             execution_id,
             part_id,
         } => {
-            dc.put_stream_execution_part(&stream_id, &execution_id, &part_id, file)
+            dc.put_stream_execution_part_with_progress(
+                stream_id,
+                &execution_id,
+                &part_id,
+                file,
+                util::progress_bar("Uploading"),
+            )
+            .await
+            .unwrap();
+            util::progress_finish();
+        }
+        StreamCommand::Upload {
+            stream_id,
+            file,
+            max_rows,
+            max_bytes,
+            concurrency,
+            retries,
+            resume,
+        } => {
+            let mut uploader = StreamUploader::new(&dc, stream_id)
+                .max_rows(max_rows)
+                .max_bytes(max_bytes)
+                .concurrency(concurrency)
+                .retries(retries)
+                .observer(Arc::new(UploadLog));
+            if let Some(manifest) = resume {
+                uploader = uploader.manifest(manifest);
+            }
+            let r = if file.as_os_str() == "-" {
+                uploader.upload_csv_reader(std::io::stdin()).await.unwrap()
+            } else if file.extension().is_some_and(|e| e == "parquet") {
+                upload_parquet(uploader, file).await
+            } else {
+                uploader.upload_csv_file(file).await.unwrap()
+            };
+            print_upload_summary(&r);
+            util::obj_template_output(r, template);
+        }
+        StreamCommand::CommitExecution {
+            stream_id,
+            execution_id,
+        } => {
+            let r = dc
+                .put_stream_execution_commit(stream_id, &execution_id)
                 .await
                 .unwrap();
+            util::obj_template_output(r, template);
         }
-        StreamCommand::CommitExecution {
+        StreamCommand::WaitExecution {
             stream_id,
             execution_id,
+            timeout,
         } => {
             let r = dc
-                .put_stream_execution_commit(&stream_id, &execution_id)
+                .wait_for_execution(stream_id, &execution_id, Duration::from_secs(timeout))
                 .await
                 .unwrap();
             util::obj_template_output(r, template);
         }
+        StreamCommand::UploadDir {
+            stream_id,
+            dir,
+            no_header,
+            concurrency,
+            retries,
+        } => {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+                .unwrap()
+                .map(|e| e.unwrap().path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "csv"))
+                .collect();
+            files.sort();
+            let r = StreamUploader::new(&dc, stream_id)
+                .has_header(!no_header)
+                .concurrency(concurrency)
+                .retries(retries)
+                .observer(Arc::new(UploadLog))
+                .upload_csv_files(files)
+                .await
+                .unwrap();
+            print_upload_summary(&r);
+            util::obj_template_output(r, template);
+        }
+        StreamCommand::Watch {
+            stream_id,
+            interval,
+        } => {
+            let stream = dc.get_stream(stream_id).await.unwrap();
+            let dataset_id = stream.dataset.and_then(|d| d.id);
+            let mut states: HashMap<u32, String> = HashMap::new();
+            let mut rows = None;
+            //Executions are listed oldest first, so each poll re-reads a window at the end of the list
+            let mut offset = 0_u32;
+            let mut first = true;
+            loop {
+                let mut count = offset;
+                loop {
+                    let page = dc
+                        .get_stream_executions(stream_id, Some(500), Some(count))
+                        .await
+                        .unwrap();
+                    let n = page.len() as u32;
+                    count += n;
+                    for e in page {
+                        let terminal = e.state().is_some_and(|s| s.is_terminal());
+                        let (id, state) = match (e.id, e.current_state) {
+                            (Some(id), Some(state)) => (id, state),
+                            _ => continue,
+                        };
+                        if states.get(&id) != Some(&state) && !(first && terminal) {
+                            println!("{} execution {}: {}", Utc::now().to_rfc3339(), id, state);
+                        }
+                        states.insert(id, state);
+                    }
+                    if n < 500 {
+                        break;
+                    }
+                }
+                offset = count.saturating_sub(50);
+                first = false;
+                if let Some(dataset_id) = &dataset_id {
+                    let ds = dc.get_dataset(dataset_id).await.unwrap();
+                    if ds.rows.is_some() && ds.rows != rows {
+                        println!(
+                            "{} dataset rows: {}",
+                            Utc::now().to_rfc3339(),
+                            ds.rows.unwrap_or_default()
+                        );
+                        rows = ds.rows;
+                    }
+                }
+                async_std::task::sleep(Duration::from_secs(interval)).await;
+            }
+        }
+        StreamCommand::Cleanup {
+            stream_id,
+            older_than,
+        } => {
+            let r = dc
+                .abort_stale_executions(stream_id, Duration::from_secs(older_than * 60))
+                .await
+                .unwrap();
+            util::vec_obj_template_output(r, template);
+        }
         StreamCommand::AbortExecution {
             stream_id,
             execution_id,
         } => {
-            dc.put_stream_execution_abort(&stream_id, &execution_id)
+            dc.put_stream_execution_abort(stream_id, &execution_id)
                 .await
                 .unwrap();
         }
     }
 }
+
+#[cfg(feature = "parquet")]
+async fn upload_parquet(uploader: StreamUploader<'_>, file: PathBuf) -> UploadReport {
+    uploader.upload_parquet(file).await.unwrap()
+}
+
+#[cfg(not(feature = "parquet"))]
+async fn upload_parquet(_uploader: StreamUploader<'_>, _file: PathBuf) -> UploadReport {
+    util::fail("parquet uploads require building with the parquet feature")
+}
+
+/// Logs upload events to stderr so stdout stays clean for the report
+struct UploadLog;
+
+impl UploadObserver for UploadLog {
+    fn started(&self, execution_id: &str) {
+        eprintln!("Uploading to execution {}", execution_id);
+    }
+
+    fn part_uploaded(&self, part: &ManifestPart) {
+        eprintln!(
+            "Part {} uploaded ({} rows, {} bytes)",
+            part.part_id, part.rows, part.bytes
+        );
+    }
+
+    fn part_skipped(&self, part: &ManifestPart) {
+        eprintln!("Part {} already uploaded, skipping", part.part_id);
+    }
+
+    fn part_retry(&self, part: &ManifestPart, attempt: u32, error: &(dyn Error + Send + Sync)) {
+        eprintln!("Part {} failed, retry {}: {}", part.part_id, attempt, error);
+    }
+}
+
+fn print_upload_summary(r: &UploadReport) {
+    eprintln!(
+        "Committed {} rows in {} parts in {:.1}s ({:.0} rows/s, {:.0} bytes/s, {} retries)",
+        r.rows,
+        r.parts.len(),
+        r.duration.as_secs_f64(),
+        r.rows_per_second(),
+        r.bytes_per_second(),
+        r.retries
+    );
+}
@@ -1,8 +1,15 @@
-use domo::public::user::User;
-use domo::public::Client;
+use domo::public::dataset::value_cell;
+use domo::public::user::{User, UserUpdate};
+use domo::public::{Client, UserId};
 
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
+use super::filter::{self, Filter};
 use super::util;
 
 /// Wraps the user api
@@ -15,71 +22,325 @@ pub enum UserCommand {
         limit: Option<u32>,
         #[structopt(short = "o", long = "offset")]
         offset: Option<u32>,
+        /// Only show matches, e.g. "department == 'Finance' && role == 'Privileged'"
+        #[structopt(short = "f", long = "filter")]
+        filter: Option<Filter>,
     },
 
     /// Get a list of all users.
     #[structopt(name = "list-all")]
-    ListAll {},
+    ListAll {
+        /// Only show matches, e.g. "department == 'Finance' && role == 'Privileged'"
+        #[structopt(short = "f", long = "filter")]
+        filter: Option<Filter>,
+    },
+
+    /// Writes every user with only the chosen columns, as csv unless --template says otherwise
+    #[structopt(name = "export")]
+    Export {
+        /// Comma separated user fields to include, e.g. id,email,department,role. Defaults to every field.
+        #[structopt(short = "c", long = "columns", use_delimiter = true)]
+        columns: Vec<String>,
+        /// Write to this file instead of stdout
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
 
     /// Create a new user
     #[structopt(name = "create")]
     Create {},
 
-    /// Retrieves the details of an existing user.
+    /// Retrieves the details of an existing user by id or --email.
     #[structopt(name = "retrieve")]
-    Retrieve { user_id: String },
+    Retrieve {
+        #[structopt(required_unless = "email")]
+        user_id: Option<UserId>,
+        /// Look the user up by email instead of id
+        #[structopt(short = "e", long = "email", conflicts_with = "user-id")]
+        email: Option<String>,
+    },
 
-    /// Update a user
+    /// Update a user in the editor, or change only the fields given with --set
     #[structopt(name = "update")]
-    Update { user_id: String },
+    Update {
+        user_id: UserId,
+        /// A field to change, by its api name, e.g. --set title="Data Engineer". May be repeated.
+        #[structopt(
+            short = "s",
+            long = "set",
+            number_of_values = 1,
+            parse(try_from_str = util::parse_key_value)
+        )]
+        set: Vec<(String, String)>,
+    },
+
+    /// Lists the roles defined in the instance
+    #[structopt(name = "roles")]
+    Roles {},
+
+    /// Sets a user's role by role name or id, leaving the rest of the user unchanged
+    #[structopt(name = "set-role")]
+    SetRole { user_id: UserId, role: String },
+
+    /// Updates users from a csv with an id column and a column per field to change, e.g. id,title,department.
+    /// Only the supplied columns are changed and blank cells are left alone.
+    #[structopt(name = "bulk-update")]
+    BulkUpdate {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// Print the planned changes without updating anyone
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+    },
+
+    /// Compares Domo users with a roster csv keyed by email and prints the users to create, update, and deactivate.
+    /// With --apply the plan is carried out; deactivating deletes the user and also needs --delete-missing.
+    #[structopt(name = "reconcile")]
+    Reconcile {
+        #[structopt(short = "f", long = "file", parse(from_os_str))]
+        file: PathBuf,
+        /// Carry out the plan instead of only printing it
+        #[structopt(long = "apply")]
+        apply: bool,
+        /// Delete users missing from the roster when applying
+        #[structopt(long = "delete-missing")]
+        delete_missing: bool,
+    },
+
+    /// Reassigns a user's datasets and pages, removes their group memberships, and then deletes them
+    #[structopt(name = "offboard")]
+    Offboard {
+        user_id: UserId,
+        /// Give the user's datasets and pages to this user first
+        #[structopt(long = "reassign-to")]
+        reassign_to: Option<UserId>,
+        /// Print what would be done without changing anything
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+    },
 
     /// Permanently deletes a user from your Domo instance
     #[structopt(name = "delete")]
-    Delete { user_id: String },
+    Delete { user_id: UserId },
+
+    /// Permanently deletes every user id listed in a file, one per line, after showing who will be deleted
+    #[structopt(name = "bulk-delete")]
+    BulkDelete {
+        #[structopt(short = "f", long = "file", parse(from_os_str))]
+        file: PathBuf,
+        /// Delete without asking for confirmation
+        #[structopt(short = "y", long = "yes")]
+        yes: bool,
+    },
 }
 
 pub async fn execute(dc: Client, editor: &str, template: Option<String>, command: UserCommand) {
     match command {
-        UserCommand::List { limit, offset } => {
+        UserCommand::List {
+            limit,
+            offset,
+            filter,
+        } => {
             let r = dc.get_users(limit, offset).await.unwrap();
+            let r = filter::apply(r, &filter);
             util::vec_obj_template_output(r, template);
         }
-        UserCommand::ListAll {} => {
-            let mut offset = 0_u32;
-            let mut r: Vec<User> = Vec::new();
-            loop {
-                let mut ret = dc.get_users(Some(50), Some(offset)).await.unwrap();
-                let mut b = false;
-                if ret.len() < 50 {
-                    b = true;
-                }
-                //Either way slurp all the elements into the aggregator
-                r.append(&mut ret);
-                offset += 50;
-                if b {
-                    break;
-                }
-            }
+        UserCommand::ListAll { filter } => {
+            let r = dc.get_users_all().await.unwrap();
+            let r = filter::apply(r, &filter);
             util::vec_obj_template_output(r, template);
         }
+        UserCommand::Export { columns, output } => {
+            let users = dc.get_users_all().await.unwrap();
+            export_users(users, columns, output, template);
+        }
         UserCommand::Create {} => {
             let r = User::template();
             let r = util::edit_obj(editor, r, "").unwrap();
             let r = dc.post_user(r).await.unwrap();
             util::obj_template_output(r, template);
         }
-        UserCommand::Retrieve { user_id } => {
-            let r = dc.get_user(&user_id).await.unwrap();
+        UserCommand::Retrieve {
+            user_id: Some(user_id),
+            ..
+        } => {
+            let r = dc.get_user(user_id).await.unwrap();
             util::obj_template_output(r, template);
         }
-        UserCommand::Update { user_id } => {
-            let r = dc.get_user(&user_id).await.unwrap();
+        UserCommand::Retrieve { email, .. } => {
+            let email = email.unwrap();
+            match dc.get_user_by_email(&email).await.unwrap() {
+                Some(r) => util::obj_template_output(r, template),
+                None => util::fail(format!("No user with email {}", email)),
+            }
+        }
+        UserCommand::Update { user_id, set } if !set.is_empty() => {
+            let mut update = UserUpdate::new();
+            for (k, v) in &set {
+                update = update.set(k, v.trim().trim_matches('"'));
+            }
+            match dc.update_user(user_id, &update).await {
+                Ok(r) => util::obj_template_output(r, template),
+                Err(e) => util::fail(e),
+            }
+        }
+        UserCommand::Update { user_id, .. } => {
+            let r = dc.get_user(user_id).await.unwrap();
             let r = util::edit_obj(editor, r, "").unwrap();
-            let r = dc.put_user(&user_id, r).await.unwrap();
+            let r = dc.put_user(user_id, r).await.unwrap();
             util::obj_template_output(r, template);
         }
+        UserCommand::Roles {} => {
+            let r = dc.get_roles().await.unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        UserCommand::SetRole { user_id, role } => {
+            let role_id = match role.parse::<u64>() {
+                Ok(id) => id,
+                Err(_) => match dc.get_role_by_name(&role).await.unwrap() {
+                    Some(r) => match r.id {
+                        Some(id) => id,
+                        None => util::fail(format!("role {} has no id", role)),
+                    },
+                    None => util::fail(format!("No role named {}", role)),
+                },
+            };
+            //roleId takes over from the deprecated role name, so drop the name to avoid sending both
+            let mut r = dc.get_user(user_id).await.unwrap();
+            r.role = None;
+            r.role_id = Some(role_id);
+            let r = dc.put_user(user_id, r).await.unwrap();
+            util::obj_template_output(r, template);
+        }
+        UserCommand::BulkUpdate { file, dry_run } => {
+            let plans = dc.plan_user_updates(file).await.unwrap();
+            if dry_run {
+                for p in &plans {
+                    println!("{}", p);
+                }
+            } else {
+                let r = dc.apply_user_updates(plans).await.unwrap();
+                util::vec_obj_template_output(r, template);
+            }
+        }
+        UserCommand::Reconcile {
+            file,
+            apply,
+            delete_missing,
+        } => {
+            let plan = dc.plan_reconcile(file).await.unwrap();
+            println!("{}", plan);
+            if apply {
+                dc.apply_reconcile(plan, delete_missing).await.unwrap();
+            }
+        }
+        UserCommand::Offboard {
+            user_id,
+            reassign_to,
+            dry_run,
+        } => {
+            let plan = dc.plan_user_offboard(user_id, reassign_to).await.unwrap();
+            println!("{}", plan);
+            if !dry_run {
+                dc.offboard_user(&plan).await.unwrap();
+            }
+        }
         UserCommand::Delete { user_id } => {
-            dc.delete_user(&user_id).await.unwrap();
+            dc.delete_user(user_id).await.unwrap();
+        }
+        UserCommand::BulkDelete { file, yes } => {
+            let ids: Vec<UserId> = util::read_ids(&file);
+            let rows: Vec<Vec<String>> = stream::iter(ids.iter().copied())
+                .map(|id| {
+                    let dc = &dc;
+                    async move {
+                        match dc.get_user(id).await {
+                            Ok(u) => vec![
+                                id.to_string(),
+                                u.name.unwrap_or_default(),
+                                u.email.unwrap_or_default(),
+                            ],
+                            Err(e) => vec![id.to_string(), format!("(not found: {})", e)],
+                        }
+                    }
+                })
+                .buffered(8)
+                .collect()
+                .await;
+            let columns = ["id", "name", "email"].map(String::from);
+            util::print_table(&columns, &rows);
+            if !yes && !util::confirm(&format!("Permanently delete these {} users?", ids.len())) {
+                util::fail("Nothing deleted");
+            }
+            if let Err(e) = dc.delete_users(&ids).await {
+                util::fail(e);
+            }
+        }
+    }
+}
+
+/// Writes the chosen columns of each user to the output as csv, json, yaml, or a table
+fn export_users(
+    users: Vec<User>,
+    columns: Vec<String>,
+    output: Option<PathBuf>,
+    template: Option<String>,
+) {
+    let fields: Vec<String> = match serde_json::to_value(User::template()).unwrap() {
+        Value::Object(m) => m.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    let columns = if columns.is_empty() {
+        fields.clone()
+    } else {
+        columns
+    };
+    if let Some(c) = columns.iter().find(|c| !fields.contains(c)) {
+        util::fail(format!(
+            "Unknown column {}, expected one of {}",
+            c,
+            fields.join(",")
+        ));
+    }
+    let values: Vec<Value> = users
+        .iter()
+        .map(|u| serde_json::to_value(u).unwrap())
+        .collect();
+    let rows: Vec<Vec<Value>> = values
+        .iter()
+        .map(|v| columns.iter().map(|c| v[c].clone()).collect())
+        .collect();
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path).unwrap()),
+        None => Box::new(io::stdout()),
+    };
+    match template.as_deref() {
+        Some("json") | Some("yaml") => {
+            let objects: Vec<serde_json::Map<String, Value>> = rows
+                .into_iter()
+                .map(|row| columns.iter().cloned().zip(row).collect())
+                .collect();
+            if template.as_deref() == Some("json") {
+                serde_json::to_writer(out, &objects).unwrap();
+            } else {
+                serde_yaml::to_writer(out, &objects).unwrap();
+            }
+        }
+        Some("table") => {
+            let rows: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| row.iter().map(value_cell).collect())
+                .collect();
+            util::write_table(&mut out, &columns, &rows).unwrap();
+        }
+        _ => {
+            let mut w = csv::Writer::from_writer(out);
+            w.write_record(&columns).unwrap();
+            for row in &rows {
+                w.write_record(row.iter().map(value_cell)).unwrap();
+            }
+            w.flush().unwrap();
         }
     }
 }
@@ -1,18 +1,26 @@
-use domo::public::dataset::QueryResult;
+use domo::public::dataset::{value_cell, QueryResult};
+use domo::public::Progress;
 
 use std::env;
 use std::error::Error;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 use std::fs;
 use std::fs::File;
 use std::io;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
 use std::process::Command;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
 use csv::{ReaderBuilder, Writer};
 use serde::Serialize;
 use serde_json::Value;
 
+use super::notify;
+
 pub fn vec_obj_template_output<T: Serialize + Debug>(r: Vec<T>, template: Option<String>) {
     match template.as_deref() {
         Some("debug") => println!("{:#?}", r),
@@ -28,6 +36,38 @@ pub fn vec_obj_template_output<T: Serialize + Debug>(r: Vec<T>, template: Option
                 w.serialize(o).unwrap();
             }
         }
+        Some("table") => {
+            //Every top level field becomes a column, in the order they are first seen
+            let values: Vec<Value> = r.iter().map(|o| serde_json::to_value(o).unwrap()).collect();
+            let mut columns: Vec<String> = Vec::new();
+            for v in &values {
+                match v {
+                    Value::Object(m) => {
+                        for k in m.keys() {
+                            if !columns.contains(k) {
+                                columns.push(k.clone());
+                            }
+                        }
+                    }
+                    _ => {
+                        if !columns.iter().any(|c| c == "value") {
+                            columns.push(String::from("value"));
+                        }
+                    }
+                }
+            }
+            let rows: Vec<Vec<String>> = values
+                .iter()
+                .map(|v| match v {
+                    Value::Object(m) => columns
+                        .iter()
+                        .map(|c| m.get(c).map(table_cell).unwrap_or_default())
+                        .collect(),
+                    other => vec![table_cell(other)],
+                })
+                .collect();
+            print_table(&columns, &rows);
+        }
         _ => println!("{}", serde_yaml::to_string(&r).unwrap()),
     }
 }
@@ -70,6 +110,15 @@ pub fn query_template_output(r: QueryResult, template: Option<String>) {
                 w.write_record(None::<&[u8]>).unwrap();
             }
         }
+        Some("table") => {
+            let rows: Vec<Vec<String>> = r
+                .rows
+                .unwrap_or_default()
+                .iter()
+                .map(|row| row.iter().map(table_cell).collect())
+                .collect();
+            print_table(&r.columns.unwrap_or_default(), &rows);
+        }
         _ => println!("{}", serde_yaml::to_string(&r).unwrap()),
     }
 }
@@ -99,7 +148,168 @@ pub fn csv_template_output(r: String, template: Option<String>) {
             }
             println!("{}", serde_yaml::to_string(&aggr).unwrap());
         }
-        _ => println!("{}", r),
+        //Write the raw csv untouched so it can be piped into other tools
+        _ => io::stdout().write_all(r.as_bytes()).unwrap(),
+    }
+}
+
+/// The widest a single column may be when rendering a table before it is truncated
+const TABLE_MAX_WIDTH: usize = 40;
+
+fn table_cell(v: &Value) -> String {
+    let s = value_cell(v).replace(['\n', '\r', '\t'], " ");
+    if s.chars().count() > TABLE_MAX_WIDTH {
+        let mut t: String = s.chars().take(TABLE_MAX_WIDTH - 3).collect();
+        t.push_str("...");
+        t
+    } else {
+        s
+    }
+}
+
+/// Prints rows as aligned columns under a header, followed by a row count
+pub fn print_table(columns: &[String], rows: &[Vec<String>]) {
+    write_table(&mut io::stdout(), columns, rows).unwrap();
+}
+
+/// Writes rows as aligned columns under a header, followed by a row count
+pub fn write_table(w: &mut dyn Write, columns: &[String], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i < widths.len() {
+                widths[i] = widths[i].max(cell.chars().count());
+            }
+        }
+    }
+    let line = |cells: &[String]| {
+        widths
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                format!(
+                    "{:<w$}",
+                    cells.get(i).map(|c| c.as_str()).unwrap_or(""),
+                    w = w
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+    writeln!(w, "{}", line(columns))?;
+    writeln!(
+        w,
+        "{}",
+        widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<String>>()
+            .join("-+-")
+    )?;
+    for row in rows {
+        writeln!(w, "{}", line(row))?;
+    }
+    writeln!(
+        w,
+        "({} row{})",
+        rows.len(),
+        if rows.len() == 1 { "" } else { "s" }
+    )
+}
+
+fn human_bytes(b: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut b = b;
+    let mut u = 0;
+    while b >= 1024.0 && u < UNITS.len() - 1 {
+        b /= 1024.0;
+        u += 1;
+    }
+    format!("{:.1} {}", b, UNITS[u])
+}
+
+/// Formats a duration as e.g. 2h 5m 3s, leaving off the larger units when they are zero
+pub fn human_duration(d: Duration) -> String {
+    let s = d.as_secs();
+    match (s / 3600, s / 60 % 60, s % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, s) => format!("{}h {}m {}s", h, m, s),
+    }
+}
+
+/// Prints the error and exits with status 1, posting the failure to --notify-buzz-url first if it was given.
+/// Every failure exit in the cli goes through here so none of them skip the notification.
+pub fn fail(message: impl Display) -> ! {
+    let message = message.to_string();
+    eprintln!("{}", message);
+    async_std::task::block_on(notify::send(Err(&message)));
+    std::process::exit(1)
+}
+
+/// Builds a progress callback that draws a progress bar with throughput and ETA on stderr.
+/// Nothing is drawn when stderr is not a terminal, so piped output stays clean.
+/// Call progress_finish once the transfer is complete.
+pub fn progress_bar(label: &str) -> Progress {
+    if !io::stderr().is_terminal() {
+        return Arc::new(|_, _| {});
+    }
+    let label = String::from(label);
+    let start = Instant::now();
+    let last: Mutex<Option<Instant>> = Mutex::new(None);
+    Arc::new(move |done, total| {
+        let now = Instant::now();
+        let mut last = last.lock().unwrap();
+        let finished = total.map(|t| done >= t).unwrap_or(false);
+        //Redrawing on every read is expensive, so only redraw a few times a second
+        if !finished
+            && last
+                .map(|l| now - l < Duration::from_millis(100))
+                .unwrap_or(false)
+        {
+            return;
+        }
+        *last = Some(now);
+        let elapsed = (now - start).as_secs_f64().max(0.001);
+        let rate = done as f64 / elapsed;
+        let line = match total {
+            Some(t) if t > 0 => {
+                let frac = (done as f64 / t as f64).min(1.0);
+                let filled = (frac * 30.0) as usize;
+                let eta = if rate > 0.0 {
+                    (t.saturating_sub(done)) as f64 / rate
+                } else {
+                    0.0
+                };
+                format!(
+                    "{} [{}{}] {:>3.0}% {} / {} {}/s ETA {:.0}s",
+                    label,
+                    "=".repeat(filled),
+                    " ".repeat(30 - filled),
+                    frac * 100.0,
+                    human_bytes(done as f64),
+                    human_bytes(t as f64),
+                    human_bytes(rate),
+                    eta
+                )
+            }
+            _ => format!(
+                "{} {} {}/s",
+                label,
+                human_bytes(done as f64),
+                human_bytes(rate)
+            ),
+        };
+        eprint!("\r{}\x1b[K", line);
+    })
+}
+
+/// Ends the line a progress bar was drawing on
+pub fn progress_finish() {
+    if io::stderr().is_terminal() {
+        eprintln!();
     }
 }
 
@@ -148,3 +358,75 @@ pub fn edit_md(editor: &str, markdown: &str) -> Result<String, Box<dyn Error>> {
     //When it's finished read the contents of the file back in as a string
     Ok(fs::read_to_string(&dir)?)
 }
+
+/// Reads one id per line, skipping blank lines and # comments. Exits on a line that isn't an id.
+pub fn read_ids<T: FromStr>(path: &Path) -> Vec<T> {
+    let mut ids = Vec::new();
+    for (n, line) in fs::read_to_string(path).unwrap().lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.parse() {
+            Ok(id) => ids.push(id),
+            Err(_) => {
+                fail(format!("{}:{}: not an id: {}", path.display(), n + 1, line));
+            }
+        }
+    }
+    ids
+}
+
+/// Asks a yes/no question on stderr and reads the answer from stdin. Anything but y or yes is a no.
+pub fn confirm(prompt: &str) -> bool {
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush().ok();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Parses a name=value argument, trimming the name. The value is kept as given and may contain '='.
+pub fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((k, v)) if !k.trim().is_empty() => Ok((String::from(k.trim()), String::from(v))),
+        _ => Err(format!("expected name=value, got {}", s)),
+    }
+}
+
+/// Parses a time given as milliseconds since the epoch, an RFC 3339 timestamp, a date,
+/// "now", or a time relative to now such as -7d, -12h, -30m or -2w.
+pub fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+    if s == "now" {
+        return Ok(Utc::now());
+    }
+    if let Some(rel) = s.strip_prefix('-') {
+        let (n, unit) = rel.split_at(rel.len().saturating_sub(1));
+        if let Ok(n) = n.parse::<i64>() {
+            let ago = match unit {
+                "m" => chrono::Duration::minutes(n),
+                "h" => chrono::Duration::hours(n),
+                "d" => chrono::Duration::days(n),
+                "w" => chrono::Duration::weeks(n),
+                _ => return Err(format!("unknown time unit in {}, use m, h, d or w", s)),
+            };
+            return Ok(Utc::now() - ago);
+        }
+    }
+    if let Ok(ms) = s.parse::<i64>() {
+        return Utc
+            .timestamp_millis_opt(ms)
+            .single()
+            .ok_or_else(|| format!("{} is out of range", s));
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(Utc.from_utc_datetime(&d.and_hms_opt(0, 0, 0).unwrap()));
+    }
+    Err(format!("can not read {} as a time", s))
+}
@@ -2,7 +2,7 @@ use std::error::Error;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Message {
     /// An optional title to include with the message
@@ -12,6 +12,94 @@ pub struct Message {
     pub text: String,
 }
 
+/// Builds a Message out of markdown blocks, separated by blank lines.
+/// The result can be sent with post_buzz_message or as a reply with reply_to_event.
+#[derive(Debug, Default)]
+pub struct MessageBuilder {
+    title: Option<String>,
+    blocks: Vec<String>,
+    /// Whether the last block is a paragraph that mentions may be added to
+    paragraph: bool,
+}
+
+impl MessageBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(String::from(title));
+        self
+    }
+
+    /// A paragraph of markdown text
+    pub fn text(mut self, text: &str) -> Self {
+        self.push(String::from(text), true);
+        self
+    }
+
+    /// A bold heading followed by markdown text
+    pub fn section(mut self, heading: &str, text: &str) -> Self {
+        self.push(format!("**{}**\n{}", heading, text), false);
+        self
+    }
+
+    /// A fenced code block, optionally tagged with a language for highlighting
+    pub fn code(mut self, language: Option<&str>, code: &str) -> Self {
+        let fence = if code.contains("```") { "~~~" } else { "```" };
+        self.push(
+            format!(
+                "{}{}\n{}\n{}",
+                fence,
+                language.unwrap_or_default(),
+                code.trim_end_matches('\n'),
+                fence
+            ),
+            false,
+        );
+        self
+    }
+
+    /// A bulleted list with one item per line
+    pub fn bullets<I, S>(mut self, items: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let list: Vec<String> = items
+            .into_iter()
+            .map(|i| format!("* {}", i.as_ref()))
+            .collect();
+        self.push(list.join("\n"), false);
+        self
+    }
+
+    /// Mentions a user by display name at the end of the current paragraph, or in a new one
+    pub fn mention(mut self, display_name: &str) -> Self {
+        let mention = format!("@{}", display_name);
+        match self.blocks.last_mut() {
+            Some(last) if self.paragraph => {
+                last.push(' ');
+                last.push_str(&mention);
+            }
+            _ => self.push(mention, true),
+        }
+        self
+    }
+
+    pub fn build(self) -> Message {
+        Message {
+            title: self.title,
+            text: self.blocks.join("\n\n"),
+        }
+    }
+
+    fn push(&mut self, block: String, paragraph: bool) {
+        self.blocks.push(block);
+        self.paragraph = paragraph;
+    }
+}
+
 impl super::Client {
     /// A webhook can be created in the product.
     /// When created, a url can be obtained to post messages back into a channel
@@ -20,9 +108,16 @@ impl super::Client {
         url: &str,
         message: Message,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
-        surf::post(url.to_string())
-            .body(surf::Body::from_json(&message)?)
-            .await?;
-        Ok(())
+        self.post_json(url, &[], &message).await
+    }
+
+    /// Posts a message to every webhook url at once, returning each one's result in the order of urls.
+    /// Requests to the same url still respect the client's rate limit.
+    pub async fn broadcast_buzz_message(
+        &self,
+        urls: &[String],
+        message: Message,
+    ) -> Vec<Result<(), Box<dyn Error + Send + Sync + 'static>>> {
+        futures::future::join_all(urls.iter().map(|url| self.post_json(url, &[], &message))).await
     }
 }
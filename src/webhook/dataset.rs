@@ -1,6 +1,11 @@
 use std::error::Error;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use serde_json::Value;
+use serde_json::{Map, Value};
+
+use crate::public::dataset::Schema;
+use crate::public::Progress;
 
 impl super::Client {
     /// A webhook dataset can be created via the product.
@@ -12,9 +17,271 @@ impl super::Client {
         url: &str,
         obj: Value,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
-        surf::post(url.to_string())
-            .body(surf::Body::from_json(&obj)?)
-            .await?;
+        self.post_json(url, &[], &obj).await
+    }
+
+    /// Sends a csv file to a webhook dataset in batches, one json object per row keyed by the header.
+    /// Empty cells are sent as null and cells that read as numbers as numbers, except ones with
+    /// leading zeros such as zip codes or ones in STRING columns of the schema.
+    /// With a schema every row is checked before it is sent, as for DatasetWebhookWriter::schema,
+    /// and the first invalid row stops the upload. Progress is reported in bytes of the file read.
+    /// Returns how many rows were sent.
+    pub async fn post_dataset_csv(
+        &self,
+        url: &str,
+        path: &Path,
+        batch_size: usize,
+        schema: Option<Schema>,
+        progress: Progress,
+    ) -> Result<u64, Box<dyn Error + Send + Sync + 'static>> {
+        let total = std::fs::metadata(path)?.len();
+        let mut rdr = csv::Reader::from_path(path)?;
+        let headers = rdr.headers()?.clone();
+        let types: Vec<Option<String>> = headers
+            .iter()
+            .map(|h| schema.as_ref().and_then(|s| column_type(s, h)))
+            .collect();
+        let mut writer = DatasetWebhookWriter::new(self, url).batch_size(batch_size);
+        if let Some(schema) = schema {
+            writer = writer.schema(schema);
+        }
+        let mut rows = 0;
+        for record in rdr.records() {
+            let record = record?;
+            let row: Map<String, Value> = headers
+                .iter()
+                .zip(record.iter())
+                .zip(types.iter())
+                .map(|((k, v), t)| (String::from(k), csv_value(v, t.as_deref())))
+                .collect();
+            if let Err(e) = writer.write(Value::Object(row)).await {
+                let line = record.position().map_or(0, |p| p.line());
+                return Err(format!("line {}: {}", line, e).into());
+            }
+            rows += 1;
+            progress(record.position().map_or(0, |p| p.byte()), Some(total));
+        }
+        writer.close().await?;
+        progress(total, Some(total));
+        Ok(rows)
+    }
+}
+
+/// The json value for a csv cell
+fn csv_value(cell: &str, column_type: Option<&str>) -> Value {
+    if cell.is_empty() {
+        return Value::Null;
+    }
+    let leading_zero = cell.len() > 1 && cell.starts_with('0') && !cell.starts_with("0.");
+    if !leading_zero && column_type != Some("STRING") {
+        if let Ok(n) = cell.parse::<i64>() {
+            return Value::from(n);
+        }
+        if let Some(n) = cell
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+        {
+            return Value::Number(n);
+        }
+    }
+    Value::String(String::from(cell))
+}
+
+/// Why the Domo json connector would drop or mistype part of a row
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowProblem {
+    /// The row is not a json object, so none of it can be read
+    NotAnObject,
+    /// The key is not a column of the schema, so its value would be dropped
+    UnknownColumn(String),
+    /// The value does not fit the column's type
+    WrongType {
+        column: String,
+        column_type: String,
+        value: Value,
+    },
+}
+
+impl std::fmt::Display for RowProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowProblem::NotAnObject => write!(f, "row is not an object"),
+            RowProblem::UnknownColumn(c) => write!(f, "{} is not a column", c),
+            RowProblem::WrongType {
+                column,
+                column_type,
+                value,
+            } => write!(f, "{} is {} but got {}", column, column_type, value),
+        }
+    }
+}
+
+/// A row that does not fit the schema given to a DatasetWebhookWriter
+#[derive(Debug)]
+pub struct InvalidRow {
+    pub problems: Vec<RowProblem>,
+}
+
+impl std::error::Error for InvalidRow {}
+impl std::fmt::Display for InvalidRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let problems: Vec<String> = self.problems.iter().map(|p| p.to_string()).collect();
+        write!(f, "invalid row: {}", problems.join(", "))
+    }
+}
+
+fn column_type(schema: &Schema, name: &str) -> Option<String> {
+    schema
+        .columns
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .find(|c| c.name.as_deref() == Some(name))
+        .and_then(|c| c.column_type.as_deref())
+        .map(|t| t.to_uppercase())
+}
+
+/// Checks a row against a schema. Missing columns and nulls are allowed, as they arrive as nulls.
+/// Numbers, and strings that read as numbers, fit LONG, DOUBLE and DECIMAL columns; DATE columns
+/// take YYYY-MM-DD strings and DATETIME columns RFC 3339 or YYYY-MM-DD HH:MM:SS strings.
+/// Objects and arrays fit no column.
+pub fn check_row(schema: &Schema, row: &Value) -> Vec<RowProblem> {
+    let row = match row.as_object() {
+        Some(r) => r,
+        None => return vec![RowProblem::NotAnObject],
+    };
+    let mut problems = Vec::new();
+    for (key, value) in row {
+        let column_type = match column_type(schema, key) {
+            Some(t) => t,
+            None => {
+                problems.push(RowProblem::UnknownColumn(key.clone()));
+                continue;
+            }
+        };
+        if !value_fits(&column_type, value) {
+            problems.push(RowProblem::WrongType {
+                column: key.clone(),
+                column_type,
+                value: value.clone(),
+            });
+        }
+    }
+    problems
+}
+
+fn value_fits(column_type: &str, value: &Value) -> bool {
+    let text = value.as_str().map(str::trim);
+    match (column_type, value) {
+        (_, Value::Null) => true,
+        (_, Value::Object(_)) | (_, Value::Array(_)) => false,
+        ("STRING", _) => true,
+        ("LONG", Value::Number(n)) => n.is_i64() || n.is_u64(),
+        ("DOUBLE", Value::Number(_)) | ("DECIMAL", Value::Number(_)) => true,
+        ("LONG", Value::String(_)) => text.is_some_and(|t| t.parse::<i64>().is_ok()),
+        ("DOUBLE", Value::String(_)) | ("DECIMAL", Value::String(_)) => {
+            text.is_some_and(|t| t.parse::<f64>().is_ok())
+        }
+        ("DATE", Value::String(_)) => {
+            text.is_some_and(|t| chrono::NaiveDate::parse_from_str(t, "%Y-%m-%d").is_ok())
+        }
+        ("DATETIME", Value::String(_)) => text.is_some_and(|t| {
+            chrono::DateTime::parse_from_rfc3339(t).is_ok()
+                || chrono::NaiveDateTime::parse_from_str(t, "%Y-%m-%d %H:%M:%S").is_ok()
+        }),
+        _ => false,
+    }
+}
+
+/// Buffers rows for a webhook dataset and posts them as json arrays.
+///
+/// The buffer is sent once it holds batch_size rows, or on the first write after flush_interval has
+/// passed since the last send. There is no background timer, so call flush when writes stop for a while,
+/// and close when done. Batches are retried as the client is configured to, and a batch that still fails
+/// stays buffered for the next flush.
+pub struct DatasetWebhookWriter<'a> {
+    client: &'a super::Client,
+    url: String,
+    batch_size: usize,
+    flush_interval: Option<Duration>,
+    schema: Option<Schema>,
+    buffer: Vec<Value>,
+    last_flush: Instant,
+}
+
+impl<'a> DatasetWebhookWriter<'a> {
+    /// A writer sending batches of 100 rows
+    pub fn new(client: &'a super::Client, url: &str) -> Self {
+        DatasetWebhookWriter {
+            client,
+            url: String::from(url),
+            batch_size: 100,
+            flush_interval: None,
+            schema: None,
+            buffer: Vec::new(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+
+    /// Checks each row against the schema as it is written. A row that does not fit is returned
+    /// as an InvalidRow error and is not buffered, so nothing reaches the dataset dropped or mistyped.
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// The rows waiting to be sent
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Adds a row, sending the buffer if it is full or the flush interval has passed
+    pub async fn write(
+        &mut self,
+        row: Value,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        if let Some(schema) = &self.schema {
+            let problems = check_row(schema, &row);
+            if !problems.is_empty() {
+                return Err(Box::new(InvalidRow { problems }));
+            }
+        }
+        self.buffer.push(row);
+        let due = self
+            .flush_interval
+            .is_some_and(|i| self.last_flush.elapsed() >= i);
+        if self.buffer.len() >= self.batch_size || due {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Sends everything buffered, in batches of batch_size
+    pub async fn flush(&mut self) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        while !self.buffer.is_empty() {
+            let n = self.batch_size.min(self.buffer.len());
+            self.client
+                .post_json(&self.url, &[], &self.buffer[..n])
+                .await?;
+            self.buffer.drain(..n);
+        }
+        self.last_flush = Instant::now();
         Ok(())
     }
+
+    /// Sends anything still buffered
+    pub async fn close(mut self) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        self.flush().await
+    }
 }
@@ -2,34 +2,189 @@ use std::error::Error;
 
 use serde::{Deserialize, Serialize};
 
+use super::buzz::Message;
+use crate::public::buzz::Event;
+use crate::public::{CardId, PageId};
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct C {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct M {
+    content: C,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel_id: Option<String>,
+}
+
+/// A message an integration posts to Buzz
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IntegrationMessage {
+    /// Shown above the text
+    pub title: Option<String>,
+
+    /// Markdown text
+    pub text: String,
+
+    /// Links, images, cards and pages shown with the message
+    pub attachments: Vec<Attachment>,
+}
+
+impl IntegrationMessage {
+    pub fn new(text: &str) -> Self {
+        IntegrationMessage {
+            text: String::from(text),
+            ..Default::default()
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(String::from(title));
+        self
+    }
+
+    pub fn attach(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+}
+
+impl From<&str> for IntegrationMessage {
+    fn from(text: &str) -> Self {
+        IntegrationMessage::new(text)
+    }
+}
+
+impl From<String> for IntegrationMessage {
+    fn from(text: String) -> Self {
+        IntegrationMessage {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+/// Something shown along with an integration message. Cards and pages are rendered as previews.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Attachment {
+    Link { url: String, title: Option<String> },
+    Image { url: String },
+    Card { id: CardId },
+    Page { id: PageId },
+}
+
+/// Where a reply to an event is posted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplyTarget {
+    /// The thread the event happened in, or its channel when it was not in a thread
+    #[default]
+    Origin,
+    /// The thread the event happened in. Replying this way to an event outside a thread is an error.
+    Thread,
+    /// The channel the event happened in, outside of any thread
+    Channel,
+}
+
+impl ReplyTarget {
+    /// The id of the channel or thread to post to. None leaves it to the callback, which posts where the event happened.
+    fn channel_id(
+        &self,
+        event: &Event,
+    ) -> Result<Option<String>, Box<dyn Error + Send + Sync + 'static>> {
+        let thread = event.thread.as_ref().and_then(|t| t.id.clone());
+        let channel = || event.channel.as_ref().and_then(|c| c.id.clone());
+        match self {
+            ReplyTarget::Origin => Ok(thread.or_else(channel)),
+            ReplyTarget::Thread => Ok(Some(thread.ok_or("event did not happen in a thread")?)),
+            ReplyTarget::Channel => Ok(Some(
+                event
+                    .thread
+                    .as_ref()
+                    .and_then(|t| t.parent_id.clone())
+                    .or_else(channel)
+                    .ok_or("event has no channel")?,
+            )),
+        }
+    }
+}
+
 impl super::Client {
     /// Allows a message to be posted back in response to an event
     /// Use fields off of the subscription to utilize this function
+    ///
+    /// Plain text can be passed as the message, or an IntegrationMessage with a title and attachments.
     pub async fn post_integration_message(
         &self,
         url: &str,
         token: &str,
-        message: &str,
+        message: impl Into<IntegrationMessage>,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
-        #[derive(Serialize, Deserialize, Debug, Default)]
-        #[serde(default, rename_all = "camelCase")]
-        struct C {
-            text: String,
-        }
-        #[derive(Serialize, Deserialize, Debug, Default)]
-        #[serde(default, rename_all = "camelCase")]
-        struct M {
-            content: C,
-        }
+        let message = message.into();
         let m = M {
             content: C {
-                text: message.to_string(),
+                text: message.text,
+                title: message.title,
+                attachments: message.attachments,
             },
+            channel_id: None,
         };
-        surf::post(url.to_string())
-            .header("x-buzz-bot-token", token)
-            .body(surf::Body::from_json(&m)?)
-            .await?;
-        Ok(())
+        self.post_json(url, &[("x-buzz-bot-token", token)], &m)
+            .await
+    }
+
+    /// Replies to an event through its callback url, sending the headers Buzz included with it.
+    /// The reply goes to the thread the event happened in, if any, so conversations stay threaded.
+    /// The title, if any, is shown in bold above the text.
+    ///
+    /// Callbacks are only valid for about an hour after the event was sent.
+    pub async fn reply_to_event(
+        &self,
+        event: &Event,
+        message: Message,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        self.reply_to_event_in(event, message, ReplyTarget::Origin)
+            .await
+    }
+
+    /// Replies to an event as reply_to_event does, posting into the thread or its parent channel as chosen
+    pub async fn reply_to_event_in(
+        &self,
+        event: &Event,
+        message: Message,
+        target: ReplyTarget,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let channel_id = target.channel_id(event)?;
+        let callback = event.callback.as_ref().ok_or("event has no callback")?;
+        let url = callback.url.as_deref().ok_or("event callback has no url")?;
+        let text = match message.title {
+            Some(title) => format!("**{}**\n\n{}", title, message.text),
+            None => message.text,
+        };
+        let headers: Vec<(&str, &str)> = callback
+            .headers
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        self.post_json(
+            url,
+            &headers,
+            &M {
+                content: C {
+                    text,
+                    ..Default::default()
+                },
+                channel_id,
+            },
+        )
+        .await
     }
 }
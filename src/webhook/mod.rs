@@ -1,13 +1,132 @@
+use std::error::Error;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::public::{RateLimiter, TimeoutError};
+
 pub mod buzz;
 pub mod dataset;
 pub mod integration;
+pub mod template;
+
+/// Sends to webhook urls.
+///
+/// Every request is checked for a 2xx answer. Connection errors, timeouts, 429 and 5xx answers
+/// are retried with backoff, honoring Retry-After when it is given, and requests to the same url
+/// can be spaced out with a rate limit shared by all the webhook methods.
+pub struct Client {
+    retries: u32,
+    timeout: Duration,
+    /// Spaces requests to each url
+    rate_limit: Option<RateLimiter<String>>,
+}
 
-pub struct Client {}
+/// A webhook answered with a status other than 2xx
+#[derive(Debug)]
+pub struct WebhookError {
+    pub url: String,
+    pub status: u16,
+    pub body: String,
+}
+
+impl std::error::Error for WebhookError {}
+impl std::fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} answered {}: {}", self.url, self.status, self.body)
+    }
+}
 
 /// Client initialization and helper methods
 impl Client {
+    /// A client that retries 3 times, times requests out after 30 seconds, and has no rate limit
     pub fn new() -> Self {
-        Self {}
+        Self {
+            retries: 3,
+            timeout: Duration::from_secs(30),
+            rate_limit: None,
+        }
+    }
+
+    /// How many times a failed request is retried, waiting 1, 2, 4... seconds between tries
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// How long to wait for each try before giving up on it
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The most requests per second to send to any one url.
+    /// Requests are never spaced more than an hour apart, which is also what zero, negative or NaN rates get.
+    pub fn rate_limit(mut self, per_second: f64) -> Self {
+        self.rate_limit = Some(RateLimiter::per_second(per_second));
+        self
+    }
+
+    /// Posts body as json with the headers, retrying and rate limiting as configured
+    async fn post_json<T: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &T,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let body = serde_json::to_vec(body)?;
+        let mut attempt = 0;
+        loop {
+            self.wait_turn(url).await;
+            let mut req = surf::post(url)
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+            for (name, value) in headers {
+                req = req.header(*name, *value);
+            }
+            let (error, retry_after): (Box<dyn Error + Send + Sync + 'static>, _) =
+                match async_std::future::timeout(self.timeout, req).await {
+                    Ok(Ok(response)) if response.status().is_success() => return Ok(()),
+                    Ok(Ok(mut response)) => {
+                        let status = response.status();
+                        let retry_after = response
+                            .header("Retry-After")
+                            .and_then(|v| v.as_str().parse().ok())
+                            .map(Duration::from_secs);
+                        let e = Box::new(WebhookError {
+                            url: String::from(url),
+                            status: status as u16,
+                            body: response.body_string().await.unwrap_or_default(),
+                        });
+                        if !(status == surf::StatusCode::TooManyRequests
+                            || status.is_server_error())
+                        {
+                            return Err(e);
+                        }
+                        (e, retry_after)
+                    }
+                    Ok(Err(e)) => (e.into(), None),
+                    Err(_) => (
+                        Box::new(TimeoutError {
+                            message: format!("{} did not answer within {:?}", url, self.timeout),
+                        }),
+                        None,
+                    ),
+                };
+            if attempt >= self.retries {
+                return Err(error);
+            }
+            async_std::task::sleep(retry_after.unwrap_or(Duration::from_secs(1 << attempt.min(6))))
+                .await;
+            attempt += 1;
+        }
+    }
+
+    /// Sleeps until the rate limit allows another request to url
+    async fn wait_turn(&self, url: &str) {
+        if let Some(limit) = &self.rate_limit {
+            limit.wait(String::from(url)).await;
+        }
     }
 }
 
@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+use regex::{Captures, Regex};
+
+/// A template used a variable that was not given
+#[derive(Debug)]
+pub struct MissingTemplateVars {
+    pub names: Vec<String>,
+}
+
+impl std::error::Error for MissingTemplateVars {}
+impl std::fmt::Display for MissingTemplateVars {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "template variables not given: {}", self.names.join(", "))
+    }
+}
+
+/// Replaces each {{name}} in a message template with its value, e.g. "{{count}} rows failed in {{env}}".
+/// Every variable the template uses must be given; the missing ones are reported together.
+pub fn render_template(
+    template: &str,
+    vars: &HashMap<String, String>,
+) -> Result<String, MissingTemplateVars> {
+    let re = Regex::new(r"\{\{\s*([A-Za-z0-9_.-]+)\s*\}\}").unwrap();
+    let mut missing = Vec::new();
+    let rendered = re.replace_all(template, |c: &Captures| match vars.get(&c[1]) {
+        Some(v) => v.clone(),
+        None => {
+            if !missing.iter().any(|m| m == &c[1]) {
+                missing.push(String::from(&c[1]));
+            }
+            String::new()
+        }
+    });
+    if !missing.is_empty() {
+        return Err(MissingTemplateVars { names: missing });
+    }
+    Ok(rendered.into_owned())
+}
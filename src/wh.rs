@@ -1,7 +1,14 @@
+use domo::public::dataset::Schema;
+use domo::public::{CardId, PageId};
 use domo::webhook::buzz::Message;
+use domo::webhook::integration::{Attachment, IntegrationMessage};
+use domo::webhook::template::render_template;
 use domo::webhook::Client;
 
 use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 use super::util;
@@ -16,6 +23,17 @@ pub enum WebhookCommand {
         url: String,
         #[structopt(long = "token", env = "DOMO_INTEGRATION_WH_TOKEN")]
         token: String,
+        #[structopt(long = "title")]
+        title: Option<String>,
+        /// A card to show a preview of. May be repeated.
+        #[structopt(long = "card", number_of_values = 1)]
+        cards: Vec<CardId>,
+        /// A page to show a preview of. May be repeated.
+        #[structopt(long = "page", number_of_values = 1)]
+        pages: Vec<PageId>,
+        /// A link to attach. May be repeated.
+        #[structopt(long = "link", number_of_values = 1)]
+        links: Vec<String>,
     },
     #[structopt(name = "create-buzz-message")]
     CreateBuzzMessage {
@@ -23,6 +41,34 @@ pub enum WebhookCommand {
         #[structopt(long = "url", env = "DOMO_BUZZ_WH_URL")]
         url: String,
         title: Option<String>,
+        /// A markdown file to send instead of opening the editor. {{name}} is replaced by the value given with --var.
+        #[structopt(long = "template", parse(from_os_str))]
+        template: Option<PathBuf>,
+        /// A template variable, e.g. --var env=prod. May be repeated.
+        #[structopt(
+            long = "var",
+            number_of_values = 1,
+            parse(try_from_str = util::parse_key_value)
+        )]
+        vars: Vec<(String, String)>,
+    },
+    /// Posts the same message to several Buzz webhooks at once, e.g. an announcement to every team channel
+    #[structopt(name = "broadcast-buzz-message")]
+    BroadcastBuzzMessage {
+        /// A webhook url to post to. May be repeated.
+        #[structopt(long = "url", number_of_values = 1, required = true)]
+        urls: Vec<String>,
+        title: Option<String>,
+        /// A markdown file to send instead of opening the editor. {{name}} is replaced by the value given with --var.
+        #[structopt(long = "template", parse(from_os_str))]
+        template: Option<PathBuf>,
+        /// A template variable, e.g. --var env=prod. May be repeated.
+        #[structopt(
+            long = "var",
+            number_of_values = 1,
+            parse(try_from_str = util::parse_key_value)
+        )]
+        vars: Vec<(String, String)>,
     },
     #[structopt(name = "create-dataset-json")]
     CreateDatasetJson {
@@ -30,20 +76,86 @@ pub enum WebhookCommand {
         #[structopt(long = "url", env = "DOMO_DATASET_WH_URL")]
         url: String,
     },
+    /// Sends each row of a csv file to a webhook dataset as a json object keyed by the header
+    #[structopt(name = "send-csv")]
+    SendCsv {
+        /// This is your webhook url.
+        #[structopt(long = "url", env = "DOMO_DATASET_WH_URL")]
+        url: String,
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// Rows sent per request
+        #[structopt(short = "b", long = "batch-size", default_value = "100")]
+        batch_size: usize,
+        /// A yaml or json file with a columns list of name and type, to check every row against before sending
+        #[structopt(long = "schema", parse(from_os_str))]
+        schema: Option<PathBuf>,
+    },
+    /// Prints every request posted to this port, such as Buzz events or dataset webhook rows,
+    /// to check a subscription before deploying a service for it. Needs the tide feature.
+    #[structopt(name = "listen")]
+    Listen {
+        #[structopt(short = "p", long = "port", default_value = "8080")]
+        port: u16,
+    },
 }
 
 pub async fn execute(editor: &str, command: WebhookCommand) {
     let c = Client::new();
     match command {
-        WebhookCommand::CreateIntegrationMessage { url, token } => {
+        WebhookCommand::CreateIntegrationMessage {
+            url,
+            token,
+            title,
+            cards,
+            pages,
+            links,
+        } => {
             let t = util::edit_md(editor, "Your message here").unwrap();
-            c.post_integration_message(&url, &token, &t).await.unwrap();
+            let mut m = IntegrationMessage::from(t);
+            m.title = title;
+            m.attachments
+                .extend(cards.into_iter().map(|id| Attachment::Card { id }));
+            m.attachments
+                .extend(pages.into_iter().map(|id| Attachment::Page { id }));
+            m.attachments.extend(
+                links
+                    .into_iter()
+                    .map(|url| Attachment::Link { url, title: None }),
+            );
+            c.post_integration_message(&url, &token, m).await.unwrap();
         }
-        WebhookCommand::CreateBuzzMessage { url, title } => {
-            let t = util::edit_md(editor, "Your message here").unwrap();
-            let m = Message { title, text: t };
+        WebhookCommand::CreateBuzzMessage {
+            url,
+            title,
+            template,
+            vars,
+        } => {
+            let m = buzz_message(editor, title, template, vars);
             c.post_buzz_message(&url, m).await.unwrap();
         }
+        WebhookCommand::BroadcastBuzzMessage {
+            urls,
+            title,
+            template,
+            vars,
+        } => {
+            let m = buzz_message(editor, title, template, vars);
+            let results = c.broadcast_buzz_message(&urls, m).await;
+            let mut failed = 0;
+            for (url, r) in urls.iter().zip(results) {
+                match r {
+                    Ok(()) => println!("sent {}", url),
+                    Err(e) => {
+                        failed += 1;
+                        println!("failed {}: {}", url, e);
+                    }
+                }
+            }
+            if failed > 0 {
+                util::fail(format!("{} of {} urls failed", failed, urls.len()));
+            }
+        }
         WebhookCommand::CreateDatasetJson { url } => {
             let r = json!({
                 "a": "Column A Value",
@@ -53,5 +165,81 @@ pub async fn execute(editor: &str, command: WebhookCommand) {
             let r = util::edit_obj(editor, r, "").unwrap();
             c.post_dataset_json(&url, r).await.unwrap();
         }
+        WebhookCommand::SendCsv {
+            url,
+            file,
+            batch_size,
+            schema,
+        } => {
+            let schema: Option<Schema> =
+                schema.map(|p| serde_yaml::from_str(&fs::read_to_string(p).unwrap()).unwrap());
+            let rows = c
+                .post_dataset_csv(
+                    &url,
+                    &file,
+                    batch_size,
+                    schema,
+                    util::progress_bar("Sending"),
+                )
+                .await
+                .unwrap();
+            util::progress_finish();
+            println!("Sent {} rows", rows);
+        }
+        WebhookCommand::Listen { port } => listen(port).await,
+    }
+}
+
+#[cfg(feature = "tide")]
+async fn listen(port: u16) {
+    async fn print(mut req: tide::Request<()>) -> tide::Result {
+        println!("{} {}", req.method(), req.url().path());
+        let mut names: Vec<String> = req.header_names().map(|n| n.to_string()).collect();
+        names.sort();
+        for name in &names {
+            if let Some(values) = req.header(name.as_str()) {
+                println!("{}: {}", name, values.as_str());
+            }
+        }
+        let body = req.body_string().await?;
+        match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(v) => println!("{}", serde_json::to_string_pretty(&v)?),
+            Err(_) => println!("{}", body),
+        }
+        println!();
+        Ok(tide::Response::new(tide::StatusCode::Ok))
+    }
+    let mut app = tide::new();
+    app.at("/").post(print);
+    app.at("/*").post(print);
+    eprintln!("Listening on 0.0.0.0:{}", port);
+    app.listen(format!("0.0.0.0:{}", port)).await.unwrap();
+}
+
+#[cfg(not(feature = "tide"))]
+async fn listen(_port: u16) {
+    util::fail("webhook listen requires building with the tide feature")
+}
+
+/// Reads a message from the template, or from the editor, filling in the --var values
+fn buzz_message(
+    editor: &str,
+    title: Option<String>,
+    template: Option<PathBuf>,
+    vars: Vec<(String, String)>,
+) -> Message {
+    let values: HashMap<String, String> = vars.into_iter().collect();
+    let render = template.is_some() || !values.is_empty();
+    let t = match template {
+        Some(path) => fs::read_to_string(path).unwrap(),
+        None => util::edit_md(editor, "Your message here").unwrap(),
+    };
+    if render {
+        Message {
+            title: title.map(|t| render_template(&t, &values).unwrap()),
+            text: render_template(&t, &values).unwrap(),
+        }
+    } else {
+        Message { title, text: t }
     }
 }
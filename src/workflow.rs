@@ -1,6 +1,9 @@
-use domo::public::workflow::{List, Project, Task};
-use domo::public::Client;
+use domo::public::workflow::{List, Project, ProjectBundle, Task, TaskFilter};
+use domo::public::{Client, UserId};
 
+use chrono::{DateTime, Utc};
+
+use std::fs;
 use std::io;
 use std::io::Write;
 use std::path::PathBuf;
@@ -33,6 +36,31 @@ pub enum WorkflowCommand {
     #[structopt(name = "update")]
     Update { project_id: String },
 
+    /// Creates a new project with the lists of an existing one, and with --include-tasks its open tasks
+    #[structopt(name = "clone")]
+    Clone {
+        project_id: String,
+        name: String,
+        #[structopt(long = "include-tasks")]
+        include_tasks: bool,
+    },
+
+    /// Writes a project with its members, lists and tasks as yaml, with users as emails so it can be imported elsewhere
+    #[structopt(name = "export")]
+    Export {
+        project_id: String,
+        /// Write to this file instead of stdout
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Creates a project from a file written by export, matching users by email
+    #[structopt(name = "import")]
+    Import {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+
     /// Permanently deletes a project from your Domo instance.
     #[structopt(name = "delete")]
     Delete { project_id: String },
@@ -47,6 +75,45 @@ pub enum WorkflowCommand {
         project_id: String,
     },
 
+    /// Retrieves the tasks in a project that match every filter given
+    #[structopt(name = "tasks")]
+    Tasks {
+        project_id: String,
+        /// Only tasks owned by the user with this email
+        #[structopt(long = "owner")]
+        owner: Option<String>,
+        /// Only tasks with this tag, may be repeated to require several
+        #[structopt(long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+        /// Only tasks due before this time, e.g. 2024-06-01 or -1d
+        #[structopt(long = "due-before", parse(try_from_str = util::parse_time))]
+        due_before: Option<DateTime<Utc>>,
+        /// Only archived tasks with true, or only tasks that are not archived with false
+        #[structopt(long = "archived")]
+        archived: Option<bool>,
+    },
+
+    /// Retrieves every task in a project, or with --list in one of its lists
+    #[structopt(name = "list-tasks-all")]
+    ListTasksAll {
+        project_id: String,
+        #[structopt(long = "list")]
+        list_id: Option<String>,
+    },
+
+    /// Moves every task owned by one user to another, e.g. when someone leaves the project
+    #[structopt(name = "reassign-tasks")]
+    ReassignTasks {
+        project_id: String,
+        #[structopt(long = "from")]
+        from: UserId,
+        #[structopt(long = "to")]
+        to: UserId,
+        /// Only reassign the tasks in this list
+        #[structopt(long = "list")]
+        list_id: Option<String>,
+    },
+
     /// Retrieves a list of ids of the users that are members of the given project id.
     #[structopt(name = "list-members")]
     ListMembers { project_id: String },
@@ -108,6 +175,25 @@ pub enum WorkflowCommand {
         project_id: String,
         list_id: String,
         task_id: String,
+        /// Delete rather than suggest archive-task, which keeps the task's history
+        #[structopt(long = "force")]
+        force: bool,
+    },
+
+    /// Archive a task, hiding it without deleting it
+    #[structopt(name = "archive-task")]
+    ArchiveListTask {
+        project_id: String,
+        list_id: String,
+        task_id: String,
+    },
+
+    /// Restore an archived task
+    #[structopt(name = "unarchive-task")]
+    UnarchiveListTask {
+        project_id: String,
+        list_id: String,
+        task_id: String,
     },
 
     /// Retrieve details about all of the attachments belonging to a particular task.
@@ -219,6 +305,92 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
                 .unwrap();
             util::vec_obj_template_output(r, template);
         }
+        WorkflowCommand::Clone {
+            project_id,
+            name,
+            include_tasks,
+        } => {
+            let r = dc
+                .clone_project(&project_id, &name, include_tasks)
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
+        WorkflowCommand::Export { project_id, output } => {
+            let r = dc.export_project(&project_id).await.unwrap();
+            let yaml = serde_yaml::to_string(&r).unwrap();
+            match output {
+                Some(path) => fs::write(path, yaml).unwrap(),
+                None => print!("{}", yaml),
+            }
+        }
+        WorkflowCommand::Import { file } => {
+            let bundle: ProjectBundle =
+                serde_yaml::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let r = dc.import_project(bundle).await.unwrap();
+            for email in &r.unmatched_users {
+                eprintln!("no user with email {}, left out", email);
+            }
+            util::obj_template_output(r, template);
+        }
+        WorkflowCommand::Tasks {
+            project_id,
+            owner,
+            tags,
+            due_before,
+            archived,
+        } => {
+            let owned_by = match owner {
+                Some(email) => match dc.get_user_by_email(&email).await.unwrap() {
+                    Some(user) => match user.id {
+                        Some(id) => Some(id),
+                        None => util::fail(format!("user with email {} has no id", email)),
+                    },
+                    None => util::fail(format!("no user with email {}", email)),
+                },
+                None => None,
+            };
+            let filter = TaskFilter {
+                owned_by,
+                tags,
+                due_before,
+                archived,
+            };
+            let r = dc
+                .get_project_tasks_filtered(&project_id, &filter)
+                .await
+                .unwrap();
+            util::vec_obj_template_output(r, template);
+        }
+        WorkflowCommand::ListTasksAll {
+            project_id,
+            list_id,
+        } => {
+            let r = all_tasks(&dc, &project_id, list_id.as_deref()).await;
+            util::vec_obj_template_output(r, template);
+        }
+        WorkflowCommand::ReassignTasks {
+            project_id,
+            from,
+            to,
+            list_id,
+        } => {
+            let tasks = all_tasks(&dc, &project_id, list_id.as_deref()).await;
+            let mut updated = Vec::new();
+            for mut task in tasks.into_iter().filter(|t| t.owned_by == Some(from)) {
+                let (task_id, task_list) = match (task.id, task.project_list_id) {
+                    (Some(t), Some(l)) => (t.to_string(), l.to_string()),
+                    _ => continue,
+                };
+                task.owned_by = Some(to);
+                let r = dc
+                    .put_project_list_task(&project_id, &task_list, &task_id, task)
+                    .await
+                    .unwrap();
+                updated.push(r);
+            }
+            util::vec_obj_template_output(updated, template);
+        }
         WorkflowCommand::ListListTasks {
             project_id,
             list_id,
@@ -274,11 +446,40 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
             project_id,
             list_id,
             task_id,
+            force,
         } => {
+            if !force {
+                util::fail(format!(
+                    "Deleting a task can not be undone. Use archive-task {} {} {} to hide it instead, or --force to delete it.",
+                    project_id, list_id, task_id
+                ));
+            }
             dc.delete_project_list_task(&project_id, &list_id, &task_id)
                 .await
                 .unwrap();
         }
+        WorkflowCommand::ArchiveListTask {
+            project_id,
+            list_id,
+            task_id,
+        } => {
+            let r = dc
+                .archive_task(&project_id, &list_id, &task_id)
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
+        WorkflowCommand::UnarchiveListTask {
+            project_id,
+            list_id,
+            task_id,
+        } => {
+            let r = dc
+                .unarchive_task(&project_id, &list_id, &task_id)
+                .await
+                .unwrap();
+            util::obj_template_output(r, template);
+        }
         WorkflowCommand::ListListTaskAttachments {
             project_id,
             list_id,
@@ -326,3 +527,14 @@ pub async fn execute(dc: Client, editor: &str, template: Option<String>, command
         }
     }
 }
+
+/// Every task in the project, or in one of its lists
+async fn all_tasks(dc: &Client, project_id: &str, list_id: Option<&str>) -> Vec<Task> {
+    match list_id {
+        Some(list_id) => dc
+            .get_project_list_tasks_all(project_id, list_id)
+            .await
+            .unwrap(),
+        None => dc.get_project_tasks_all(project_id).await.unwrap(),
+    }
+}